@@ -21,8 +21,14 @@ pub enum ApiError {
     ParseStakeStateError(Pubkey),
     #[error("Could not deserialize Validator list {0}")]
     ParseValidatorListError(Pubkey),
+    #[error("Could not deserialize DepositReceipt {0}")]
+    ParseDepositReceiptError(Pubkey),
     #[error("Stake voter_pubkey is invalid or missing")]
     InvalidStakeVoteAccount,
+    #[error("Amount exceeds the DepositReceipt's amount")]
+    InvalidClaimAmount,
+    #[error("Deposit is below the StakePoolDepositStakeAuthority's min_deposit_lst")]
+    DepositBelowMinimum,
     #[error("Internal Error")]
     InternalError,
 }
@@ -55,7 +61,13 @@ impl IntoResponse for ApiError {
                 error!("Parse ValidatorList error: {e}");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Pubkey ValidatorList error")
             }
+            ApiError::ParseDepositReceiptError(e) => {
+                error!("Parse DepositReceipt error: {e}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Pubkey DepositReceipt error")
+            }
             ApiError::InvalidStakeVoteAccount => (StatusCode::BAD_REQUEST, "Stake voter_pubkey is invalid or missing"),
+            ApiError::InvalidClaimAmount => (StatusCode::BAD_REQUEST, "Amount exceeds the DepositReceipt's amount"),
+            ApiError::DepositBelowMinimum => (StatusCode::BAD_REQUEST, "Deposit is below the StakePoolDepositStakeAuthority's min_deposit_lst"),
             ApiError::InternalError => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error"),
         };
         (
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use axum::{http::StatusCode, routing::get, Router};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 
+mod get_deposit_receipt_fee;
 mod get_deposit_stake_instructions;
 
 pub struct RouterState {
@@ -10,10 +11,15 @@ pub struct RouterState {
 }
 
 pub fn get_routes(state: Arc<RouterState>) -> Router {
-    let api_routes = Router::new().route(
-        "/get-deposit-stake-instructions",
-        get(get_deposit_stake_instructions::get_deposit_stake_instructions),
-    );
+    let api_routes = Router::new()
+        .route(
+            "/get-deposit-stake-instructions",
+            get(get_deposit_stake_instructions::get_deposit_stake_instructions),
+        )
+        .route(
+            "/get-deposit-receipt-fee",
+            get(get_deposit_receipt_fee::get_deposit_receipt_fee),
+        );
 
     let app = Router::new().nest("/api/v1", api_routes).fallback(fallback);
 
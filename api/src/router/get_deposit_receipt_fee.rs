@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use bincode::deserialize;
+use jito_bytemuck::AccountDeserialize;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, sysvar};
+use stake_deposit_interceptor::state::DepositReceipt;
+
+use crate::{error::ApiError, utils::pubkey_from_str};
+
+use super::RouterState;
+
+#[derive(Deserialize)]
+pub(crate) struct GetDepositReceiptFeeQuery {
+    /// DepositReceipt to calculate the fee for
+    #[serde(deserialize_with = "pubkey_from_str")]
+    deposit_receipt: Pubkey,
+    /// Optional amount of "pool" tokens to claim. Defaults to the DepositReceipt's full amount.
+    amount: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct GetDepositReceiptFeeResponse {
+    /// Cluster time the fee was calculated at
+    unix_timestamp: i64,
+    /// Total amount of "pool" tokens held by the DepositReceipt
+    lst_amount: u64,
+    /// Amount of "pool" tokens the fee was calculated for
+    amount: u64,
+    /// Amount of "pool" tokens owed as fees
+    fee_amount: u64,
+}
+
+/// Calculates the fee currently owed to claim "pool" tokens from a DepositReceipt.
+pub(crate) async fn get_deposit_receipt_fee(
+    State(state): State<Arc<RouterState>>,
+    Query(query): Query<GetDepositReceiptFeeQuery>,
+) -> crate::Result<impl IntoResponse> {
+    let deposit_receipt_account_data = state
+        .rpc_client
+        .get_account_data(&query.deposit_receipt)
+        .await
+        .map_err(ApiError::RpcError)?;
+    DepositReceipt::check_version(deposit_receipt_account_data.as_slice())
+        .map_err(|_| ApiError::ParseDepositReceiptError(query.deposit_receipt))?;
+    let deposit_receipt =
+        DepositReceipt::try_from_account_data(deposit_receipt_account_data.as_slice())
+            .map_err(|_| ApiError::ParseDepositReceiptError(query.deposit_receipt))?;
+
+    let clock_account_data = state
+        .rpc_client
+        .get_account_data(&sysvar::clock::id())
+        .await
+        .map_err(ApiError::RpcError)?;
+    let clock: Clock =
+        deserialize(clock_account_data.as_slice()).map_err(|_| ApiError::InternalError)?;
+
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let amount = query.amount.unwrap_or(lst_amount);
+    if amount > lst_amount {
+        return Err(ApiError::InvalidClaimAmount);
+    }
+    let fee_amount = deposit_receipt.calculate_fee_amount_for(amount, clock.unix_timestamp);
+
+    Ok(Json(GetDepositReceiptFeeResponse {
+        unix_timestamp: clock.unix_timestamp,
+        lst_amount,
+        amount,
+        fee_amount,
+    }))
+}
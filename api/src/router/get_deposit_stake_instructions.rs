@@ -61,7 +61,9 @@ pub(crate) async fn get_deposit_stake_instructions(
         .await
         .map_err(ApiError::RpcError)?;
 
-    let stake_deposit_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked(
+    StakePoolDepositStakeAuthority::check_version(stake_deposit_authority_account_data.as_slice())
+        .map_err(|_| ApiError::ParseStakeDepositAuthorityError(query.stake_deposit_authority))?;
+    let stake_deposit_authority = StakePoolDepositStakeAuthority::try_from_account_data(
         stake_deposit_authority_account_data.as_slice(),
     )
     .map_err(|_| ApiError::ParseStakeDepositAuthorityError(query.stake_deposit_authority))?;
@@ -79,8 +81,12 @@ pub(crate) async fn get_deposit_stake_instructions(
     let stake_state: stake::state::StakeStateV2 = deserialize(stake_account_data.as_slice())
         .map_err(|_| ApiError::ParseStakeStateError(query.stake))?;
 
-    let vote_account = match stake_state {
-        stake::state::StakeStateV2::Stake(_, stake, _) => Ok(stake.delegation.voter_pubkey),
+    let (vote_account, delegated_lamports, rent_lamports) = match stake_state {
+        stake::state::StakeStateV2::Stake(meta, stake, _) => Ok((
+            stake.delegation.voter_pubkey,
+            stake.delegation.stake,
+            meta.rent_exempt_reserve,
+        )),
         _ => Err(ApiError::InvalidStakeVoteAccount),
     }?;
 
@@ -108,6 +114,22 @@ pub(crate) async fn get_deposit_stake_instructions(
         .referrer_token_account
         .unwrap_or(stake_deposit_authority.vault);
 
+    // Fail early instead of returning instructions the program rejects for minting fewer pool
+    // tokens than the StakePoolDepositStakeAuthority's `min_deposit_lst`
+    let min_deposit_lst = u64::from(stake_deposit_authority.min_deposit_lst);
+    if min_deposit_lst > 0 {
+        let pool_tokens_minted = calc_vault_pool_tokens_for_deposit(
+            &stake_pool,
+            delegated_lamports,
+            rent_lamports,
+            referrer_token_account == stake_deposit_authority.vault,
+        )
+        .ok_or(ApiError::InternalError)?;
+        if pool_tokens_minted < min_deposit_lst {
+            return Err(ApiError::DepositBelowMinimum);
+        }
+    }
+
     let pool_withdraw_authority = find_withdraw_authority_program_address(
         &spl_stake_pool::id(),
         &stake_deposit_authority.stake_pool,
@@ -129,7 +151,7 @@ pub(crate) async fn get_deposit_stake_instructions(
         &stake_pool.manager_fee_account,
         &referrer_token_account,
         &stake_pool.pool_mint,
-        &spl_token::id(),
+        &stake_pool.token_program_id,
         &query.deposit_receipt_base,
         &stake_deposit_authority.base,
     );
@@ -139,3 +161,27 @@ pub(crate) async fn get_deposit_stake_instructions(
 
     Ok(Json(GetDepositStakeResponse { instructions }))
 }
+
+/// Pool tokens the StakePool's DepositStake mints into the vault at the current exchange rate,
+/// after the stake and SOL deposit fees. The referral fee comes back to the vault when it is
+/// also the referrer.
+fn calc_vault_pool_tokens_for_deposit(
+    stake_pool: &StakePool,
+    delegated_lamports: u64,
+    rent_lamports: u64,
+    vault_is_referrer: bool,
+) -> Option<u64> {
+    let new_pool_tokens =
+        stake_pool.calc_pool_tokens_for_deposit(delegated_lamports.checked_add(rent_lamports)?)?;
+    let new_pool_tokens_from_stake = stake_pool.calc_pool_tokens_for_deposit(delegated_lamports)?;
+    let new_pool_tokens_from_sol = new_pool_tokens.checked_sub(new_pool_tokens_from_stake)?;
+    let total_fee = stake_pool
+        .calc_pool_tokens_stake_deposit_fee(new_pool_tokens_from_stake)?
+        .checked_add(stake_pool.calc_pool_tokens_sol_deposit_fee(new_pool_tokens_from_sol)?)?;
+    let pool_tokens_user = new_pool_tokens.checked_sub(total_fee)?;
+    if vault_is_referrer {
+        pool_tokens_user.checked_add(stake_pool.calc_pool_tokens_stake_referral_fee(total_fee)?)
+    } else {
+        Some(pool_tokens_user)
+    }
+}
@@ -2,14 +2,19 @@ use std::num::NonZeroU32;
 
 use jito_bytemuck::AccountDeserialize;
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, stake};
+use solana_sdk::{
+    clock::Clock, pubkey::Pubkey, signature::Keypair, signer::Signer, stake, sysvar,
+};
 use spl_stake_pool::{find_stake_program_address, find_withdraw_authority_program_address};
 use stake_deposit_interceptor::{
     instruction::{
         create_deposit_stake_instruction, create_init_deposit_stake_authority_instruction,
         derive_stake_pool_deposit_stake_authority,
     },
-    state::StakePoolDepositStakeAuthority,
+    state::{
+        DepositReceipt, FeeCurve, FeeCurveBreakpoint, FeeCurveConfig,
+        StakePoolDepositStakeAuthority,
+    },
 };
 
 use crate::{
@@ -29,7 +34,7 @@ fn get_stake_deposit_authority(
     stake_deposit_authority_address: &Pubkey,
 ) -> Result<StakePoolDepositStakeAuthority, Error> {
     let account_data = rpc_client.get_account_data(stake_deposit_authority_address)?;
-    let stake_deposit_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked(
+    let stake_deposit_authority = StakePoolDepositStakeAuthority::try_from_account_data(
         account_data.as_slice(),
     )
     .map_err(|err| {
@@ -41,6 +46,70 @@ fn get_stake_deposit_authority(
     Ok(*stake_deposit_authority)
 }
 
+fn get_deposit_receipt(
+    rpc_client: &RpcClient,
+    deposit_receipt_address: &Pubkey,
+) -> Result<DepositReceipt, Error> {
+    let account_data = rpc_client.get_account_data(deposit_receipt_address)?;
+    let deposit_receipt = DepositReceipt::try_from_account_data(account_data.as_slice())
+        .map_err(|err| {
+            format!(
+                "Invalid deposit_receipt {}: {}",
+                deposit_receipt_address, err
+            )
+        })?;
+    Ok(*deposit_receipt)
+}
+
+/// Parse a fee curve in the form of `linear`, `step:<STEPS>`,
+/// `exponential:<HALF_LIFE_SECONDS>`, or
+/// `piecewise:<ELAPSED_BPS>=<FEE_RATIO_BPS>,<ELAPSED_BPS>=<FEE_RATIO_BPS>,...`
+pub fn parse_fee_curve(value: &str) -> Result<FeeCurveConfig, String> {
+    let (curve_type, params) = match value.split_once(':') {
+        Some((curve_type, params)) => (curve_type, Some(params)),
+        None => (value, None),
+    };
+    let fee_curve = match (curve_type, params) {
+        ("linear", None) => FeeCurveConfig::Linear,
+        ("step", Some(steps)) => FeeCurveConfig::Step {
+            steps: steps
+                .parse::<u32>()
+                .map_err(|err| format!("Invalid step count {}: {}", steps, err))?,
+        },
+        ("exponential", Some(half_life_seconds)) => FeeCurveConfig::Exponential {
+            half_life_seconds: half_life_seconds.parse::<u32>().map_err(|err| {
+                format!("Invalid half life seconds {}: {}", half_life_seconds, err)
+            })?,
+        },
+        ("piecewise", Some(breakpoints)) => FeeCurveConfig::PiecewiseLinear {
+            breakpoints: breakpoints
+                .split(',')
+                .map(|breakpoint| {
+                    let (elapsed_bps, fee_ratio_bps) = breakpoint
+                        .split_once('=')
+                        .ok_or(format!("Invalid breakpoint {}", breakpoint))?;
+                    let elapsed_bps = elapsed_bps
+                        .parse::<u16>()
+                        .map_err(|err| format!("Invalid breakpoint {}: {}", breakpoint, err))?;
+                    let fee_ratio_bps = fee_ratio_bps
+                        .parse::<u16>()
+                        .map_err(|err| format!("Invalid breakpoint {}: {}", breakpoint, err))?;
+                    Ok(FeeCurveBreakpoint::new(elapsed_bps, fee_ratio_bps))
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+        },
+        _ => return Err(format!("Unrecognized fee curve {}", value)),
+    };
+    // Use the same validation as the program
+    FeeCurve::from_config(&fee_curve).map_err(|err| format!("{}: {}", value, err))?;
+    Ok(fee_curve)
+}
+
+/// Validator for the `--fee-curve` argument
+pub fn is_fee_curve(value: String) -> Result<(), String> {
+    parse_fee_curve(&value).map(|_| ())
+}
+
 /// Create a StakePoolStakeDepositAuthority on the
 /// stake-pool-interceptor program.
 pub fn command_create_stake_deposit_authority(
@@ -49,6 +118,7 @@ pub fn command_create_stake_deposit_authority(
     fee_wallet: &Pubkey,
     cool_down_seconds: u64,
     initial_fee_bps: u32,
+    fee_curve: FeeCurveConfig,
     authority: &Pubkey,
 ) -> CommandResult {
     // Ephemeral keypair used for stake_deposit_authority PDA seed.
@@ -60,10 +130,11 @@ pub fn command_create_stake_deposit_authority(
         stake_pool_address,
         &stake_pool.pool_mint,
         &spl_stake_pool::id(),
-        &spl_token::id(),
+        &stake_pool.token_program_id,
         fee_wallet,
         cool_down_seconds,
         initial_fee_bps,
+        fee_curve,
         authority,
         &base.pubkey(),
     );
@@ -157,7 +228,7 @@ pub fn command_deposit_stake(
         &stake_pool.manager_fee_account,
         &referrer_token_account,
         &stake_pool.pool_mint,
-        &spl_token::id(),
+        &stake_pool.token_program_id,
         &deposit_receipt_base.pubkey(),
         &stake_deposit_authority.base,
     );
@@ -169,3 +240,36 @@ pub fn command_deposit_stake(
     send_transaction(config, transaction)?;
     Ok(())
 }
+
+/// Display a DepositReceipt and the fee that would currently be owed to claim it.
+pub fn command_show_deposit_receipt(
+    config: &Config,
+    deposit_receipt_address: &Pubkey,
+) -> CommandResult {
+    let deposit_receipt = get_deposit_receipt(&config.rpc_client, deposit_receipt_address)?;
+    let clock_account = config.rpc_client.get_account(&sysvar::clock::id())?;
+    let clock: Clock = bincode::deserialize(&clock_account.data)
+        .map_err(|err| format!("Invalid clock sysvar: {}", err))?;
+
+    let fee_amount = deposit_receipt.calculate_fee_amount(clock.unix_timestamp);
+    println!("DepositReceipt: {}", deposit_receipt_address);
+    println!("Owner: {}", deposit_receipt.owner);
+    println!("Stake pool: {}", deposit_receipt.stake_pool);
+    println!(
+        "Stake deposit authority: {}",
+        deposit_receipt.stake_pool_deposit_stake_authority
+    );
+    println!("Deposit time: {}", u64::from(deposit_receipt.deposit_time));
+    println!("Pool tokens: {}", u64::from(deposit_receipt.lst_amount));
+    println!(
+        "Cool down seconds: {}",
+        u64::from(deposit_receipt.cool_down_seconds)
+    );
+    println!(
+        "Initial fee bps: {}",
+        u32::from(deposit_receipt.initial_fee_bps)
+    );
+    println!("Fee curve: {:?}", deposit_receipt.fee_curve.config());
+    println!("Current fee: {}", fee_amount);
+    Ok(())
+}
@@ -2919,7 +2919,17 @@ fn main() {
                         .value_name("INITIAL_FEE_BPS")
                         .takes_value(true)
                         .required(true)
-                        .help("The fee rate (in basis points) that will be charged at time 0 and linearly decay until cool_down_seconds has elapsed"),
+                        .help("The fee rate (in basis points) that will be charged at time 0 and decay along the fee curve until cool_down_seconds has elapsed"),
+                )
+                .arg(
+                    Arg::with_name("fee_curve")
+                        .long("fee-curve")
+                        .validator(interceptor::is_fee_curve)
+                        .value_name("FEE_CURVE")
+                        .takes_value(true)
+                        .default_value("linear")
+                        .help("Curve the fee decays along: linear, step:<STEPS>, exponential:<HALF_LIFE_SECONDS>, \
+                            or piecewise:<ELAPSED_BPS>=<FEE_RATIO_BPS>,... where breakpoints are relative to cool_down_seconds and initial_fee_bps"),
                 )
                 .arg(
                     Arg::with_name("authority")
@@ -2967,6 +2977,18 @@ fn main() {
                         .help("Pool token account to receive the referral fees for deposits. \
                             Defaults to the token receiver."),
                 )
+            )
+            .subcommand(SubCommand::with_name("show-deposit-receipt")
+                .about("Show a DepositReceipt and the fee currently owed to claim it")
+                .arg(
+                    Arg::with_name("deposit_receipt")
+                        .index(1)
+                        .validator(is_pubkey)
+                        .value_name("DEPOSIT_RECEIPT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("DepositReceipt address"),
+                )
         )
         )
         .get_matches();
@@ -3376,12 +3398,16 @@ fn main() {
                 let cool_down_seconds = value_t_or_exit!(arg_matches, "cool_down_seconds", u64);
                 let initial_fee_bps = value_t_or_exit!(arg_matches, "initial_fee_bps", u32);
                 let authority = pubkey_of(arg_matches, "authority").unwrap();
+                let fee_curve =
+                    interceptor::parse_fee_curve(arg_matches.value_of("fee_curve").unwrap())
+                        .unwrap();
                 command_create_stake_deposit_authority(
                     &config,
                     &stake_pool_address,
                     &fee_wallet,
                     cool_down_seconds,
                     initial_fee_bps,
+                    fee_curve,
                     &authority,
                 )
             }
@@ -3407,6 +3433,10 @@ fn main() {
                     &referrer,
                 )
             }
+            ("show-deposit-receipt", Some(arg_matches)) => {
+                let deposit_receipt = pubkey_of(arg_matches, "deposit_receipt").unwrap();
+                interceptor::command_show_deposit_receipt(&config, &deposit_receipt)
+            }
             _ => unreachable!(),
         },
         _ => unreachable!(),
@@ -1,5 +1,6 @@
 use ::{
     solana_sdk::{
+        instruction::AccountMeta,
         pubkey::Pubkey,
         signature::Keypair,
         signer::Signer,
@@ -23,9 +24,14 @@ use ::{
         instruction::create_claim_pool_tokens_instruction,
         instruction::derive_stake_deposit_receipt,
     },
-    spl_associated_token_account::{
-        get_associated_token_address,
-        instruction::create_associated_token_account,
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_token_2022::{
+        extension::{ transfer_hook, StateWithExtensions },
+        state::Mint,
+    },
+    spl_transfer_hook_interface::offchain::{
+        add_extra_account_metas_for_execute,
+        AccountFetchError,
     },
     jito_bytemuck::AccountDeserialize,
     solana_account_decoder::UiAccountEncoding,
@@ -94,19 +100,29 @@ impl InterceptorCranker {
             // Get raw bytes using bytemuck and interpret as little-endian
             let deposit_time = u64::from(receipt.deposit_time);
             let cool_down = u64::from(receipt.cool_down_seconds);
+            // Fee owed if the receipt were claimed now, using the receipt's fee curve
+            let fee_amount = receipt.calculate_fee_amount(now as i64);
 
             info!(
                 "Receipt {} raw bytes:\n\
                  Interpreted values:\n\
                  deposit_time: {}\n\
                  cool_down: {}\n\
-                 current_time: {}",
+                 current_time: {}\n\
+                 current_fee: {}",
                 receipt.base,
                 deposit_time,
                 cool_down,
-                now
+                now,
+                fee_amount
             );
 
+            // Tokenized receipts can only be claimed by whoever holds their receipt token
+            if receipt.is_tokenized() {
+                info!("Receipt {} is tokenized, skipping", receipt.base);
+                continue;
+            }
+
             if deposit_time > now {
                 info!(
                     "Receipt {} not yet expired (future deposit time). Current time: {}, Deposit time: {}",
@@ -120,7 +136,9 @@ impl InterceptorCranker {
             // Safe addition check
             match deposit_time.checked_add(cool_down) {
                 Some(expiry_time) => {
-                    if now > expiry_time {
+                    // Only crank once the cool down has ended and no fee is owed, as the
+                    // owner's signature is required to claim during the cool down.
+                    if now > expiry_time && fee_amount == 0 {
                         info!(
                             "Receipt {} is expired. Current time: {}, Expiry time: {}",
                             receipt.base,
@@ -189,7 +207,11 @@ impl InterceptorCranker {
             accounts
                 .into_iter()
                 .filter_map(|(pubkey, account)| {
-                    match DepositReceipt::try_from_slice_unchecked(account.data.as_slice()) {
+                    if let Err(e) = DepositReceipt::check_version(account.data.as_slice()) {
+                        error!("Skipping receipt {} with unsupported layout: {}", pubkey, e);
+                        return None;
+                    }
+                    match DepositReceipt::try_from_account_data(account.data.as_slice()) {
                         Ok(receipt) => {
                             info!(
                                 "Found receipt:\n\
@@ -227,43 +249,18 @@ impl InterceptorCranker {
             &receipt.stake_pool_deposit_stake_authority
         ).await?;
 
-        let owner_ata = get_associated_token_address(
-            &receipt.owner,
+        // The pool mint may be owned by either SPL Token or Token-2022
+        let pool_mint_account = self.rpc_client.get_account(
             &stake_pool_deposit_authority.pool_mint
-        );
+        ).await?;
+        let token_program = pool_mint_account.owner;
 
-        let fee_wallet_token_account = get_associated_token_address(
-            &stake_pool_deposit_authority.fee_wallet,
-            &stake_pool_deposit_authority.pool_mint
+        let owner_ata = get_associated_token_address_with_program_id(
+            &receipt.owner,
+            &stake_pool_deposit_authority.pool_mint,
+            &token_program
         );
 
-        // Check if account exists
-        match self.rpc_client.get_account(&fee_wallet_token_account).await {
-            Ok(_) => {
-                info!("Fee wallet token account exists: {}", fee_wallet_token_account);
-            }
-            Err(_) => {
-                info!("Creating fee wallet token account: {}", fee_wallet_token_account);
-                let create_ata_ix = create_associated_token_account(
-                    &self.payer.pubkey(),
-                    &stake_pool_deposit_authority.fee_wallet,
-                    &stake_pool_deposit_authority.pool_mint,
-                    &spl_token::id()
-                );
-
-                let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
-                let create_ata_tx = Transaction::new_signed_with_payer(
-                    &[create_ata_ix],
-                    Some(&self.payer.pubkey()),
-                    &[self.payer.as_ref()],
-                    recent_blockhash
-                );
-
-                self.rpc_client.send_and_confirm_transaction(&create_ata_tx).await?;
-                info!("Created fee wallet token account");
-            }
-        }
-
         // Derive the correct PDA
         let (derived_receipt_address, _) = derive_stake_deposit_receipt(
             &self.program_id,
@@ -271,19 +268,45 @@ impl InterceptorCranker {
             &receipt.base
         );
 
-        let claim_ix = create_claim_pool_tokens_instruction(
+        let mut claim_ix = create_claim_pool_tokens_instruction(
             &self.program_id,
             &derived_receipt_address,  // Use the derived address here instead of receipt.base
             &receipt.owner,
             &stake_pool_deposit_authority.vault,
             &owner_ata,
-            &fee_wallet_token_account,
             &receipt.stake_pool_deposit_stake_authority,
             &stake_pool_deposit_authority.pool_mint,
-            &spl_token::id(),
+            &token_program,
+            &receipt.rent_refund_destination(),
             true
         );
 
+        // Append the extra accounts required by the pool mint's transfer hook, if any
+        let pool_mint = StateWithExtensions::<Mint>::unpack(&pool_mint_account.data)?;
+        if let Some(transfer_hook_program_id) = transfer_hook::get_program_id(&pool_mint) {
+            add_extra_account_metas_for_execute(
+                &mut claim_ix,
+                &transfer_hook_program_id,
+                &stake_pool_deposit_authority.vault,
+                &stake_pool_deposit_authority.pool_mint,
+                &owner_ata,
+                &receipt.stake_pool_deposit_stake_authority,
+                u64::from(receipt.lst_amount),
+                |address| {
+                    let rpc_client = self.rpc_client.clone();
+                    async move {
+                        rpc_client
+                            .get_account_with_commitment(&address, rpc_client.commitment()).await
+                            .map(|response| response.value.map(|account| account.data))
+                            .map_err(AccountFetchError::from)
+                    }
+                }
+            ).await?;
+        }
+
+        // Collect the keeper tip, if any. The keeper follows the transfer hook accounts.
+        claim_ix.accounts.push(AccountMeta::new(self.payer.pubkey(), true));
+
         let recent_blockhash = self.rpc_client.get_latest_blockhash().await?;
         let claim_tx = Transaction::new_signed_with_payer(
             &[claim_ix],
@@ -314,7 +337,10 @@ impl InterceptorCranker {
     ) -> Result<StakePoolDepositStakeAuthority, CrankerError> {
         let account = self.rpc_client.get_account(pubkey).await.map_err(CrankerError::RpcError)?;
 
-        StakePoolDepositStakeAuthority::try_from_slice_unchecked(account.data.as_slice())
+        StakePoolDepositStakeAuthority::check_version(account.data.as_slice()).map_err(|e|
+            CrankerError::DeserializeError(e.to_string())
+        )?;
+        StakePoolDepositStakeAuthority::try_from_account_data(account.data.as_slice())
             .map(|auth| auth.clone())
             .map_err(|e| CrankerError::DeserializeError(e.to_string()))
     }
@@ -53,4 +53,103 @@ pub enum StakeDepositInterceptorError {
     /// 16 : Invalid stake-pool program
     #[error("StakePool program is invalid")]
     InvalidStakePoolProgram,
+    /// 17 : Claim amount is zero or exceeds the DepositReceipt's amount
+    #[error("Claim amount is invalid")]
+    InvalidClaimAmount,
+    /// 18 : Split amount is zero or would leave nothing on the original DepositReceipt
+    #[error("Split amount is invalid")]
+    InvalidSplitAmount,
+    /// 19 : DepositReceipts do not share the same owner, authority, and fee parameters
+    #[error("DepositReceipts cannot be merged")]
+    IncompatibleDepositReceipts,
+    /// 20 : FeeCurve parameters are invalid
+    #[error("Invalid fee curve")]
+    InvalidFeeCurve,
+    /// 21 : FeeExemption address must match PDA derivation
+    #[error("Invalid FeeExemption")]
+    InvalidFeeExemption,
+    /// 22 : Signer is not the pending authority of the StakePoolDepositStakeAuthority
+    #[error("Invalid pending authority")]
+    InvalidPendingAuthority,
+    /// 23 : Pending parameters cannot take effect before the minimum update delay
+    #[error("Parameter update delay is too short")]
+    ParameterUpdateDelayTooShort,
+    /// 24 : There are no pending parameters to apply
+    #[error("No pending parameters")]
+    NoPendingParameters,
+    /// 25 : Pending parameters cannot be applied before `effective_at`
+    #[error("Pending parameters are not yet effective")]
+    PendingParametersNotEffective,
+    /// 26 : Fee recipients are invalid or their shares exceed 100%
+    #[error("Invalid fee recipients")]
+    InvalidFeeRecipients,
+    /// 27 : The vault still holds pool tokens
+    #[error("Vault is not empty")]
+    VaultNotEmpty,
+    /// 28 : DepositReceipts created through the StakePoolDepositStakeAuthority are still open
+    #[error("DepositReceipts are outstanding")]
+    OutstandingDepositReceipts,
+    /// 29 : StakePoolDepositStakeAuthority was created before outstanding LST was tracked
+    #[error("Outstanding LST is not tracked")]
+    OutstandingLstNotTracked,
+    /// 30 : The vault holds no pool tokens beyond those owed to depositors and fee recipients
+    #[error("No excess vault tokens")]
+    NoExcessVaultTokens,
+    /// 31 : The operation is paused by the StakePoolDepositStakeAuthority's authority
+    #[error("Paused")]
+    Paused,
+    /// 32 : The deposit mints more pool tokens than `max_deposit_lst_per_receipt`
+    #[error("Max deposit per receipt exceeded")]
+    MaxDepositPerReceiptExceeded,
+    /// 33 : The deposit would bring `total_outstanding_lst` above `max_total_outstanding_lst`
+    #[error("Max total outstanding LST exceeded")]
+    MaxTotalOutstandingLstExceeded,
+    /// 34 : The deposit mints fewer pool tokens than `min_deposit_lst`
+    #[error("Deposit is below the minimum")]
+    DepositBelowMinimum,
+    /// 35 : Account does not match the DepositReceipt's rent payer
+    #[error("Invalid rent payer")]
+    InvalidRentPayer,
+    /// 36 : The DepositReceipt is tokenized and can only be claimed by burning its receipt token
+    #[error("Deposit receipt is tokenized")]
+    DepositReceiptTokenized,
+    /// 37 : Account does not match the DepositReceipt's receipt mint
+    #[error("Invalid receipt mint")]
+    InvalidReceiptMint,
+    /// 38 : The StakePoolDepositStakeAuthority requires the DepositReceipt owner to sign the deposit
+    #[error("Owner signature required")]
+    OwnerSignatureRequired,
+    /// 39 : Unknown ReceiptTransferPolicy
+    #[error("Invalid receipt transfer policy")]
+    InvalidReceiptTransferPolicy,
+    /// 40 : The StakePoolDepositStakeAuthority's ReceiptTransferPolicy does not allow the transfer
+    #[error("Receipt transfer forbidden")]
+    ReceiptTransferForbidden,
+    /// 41 : The account has an older layout and must be upgraded with MigrateAccount
+    #[error("Account migration required")]
+    AccountMigrationRequired,
+    /// 42 : The account has a layout version this program does not know
+    #[error("Unsupported account version")]
+    UnsupportedAccountVersion,
+    /// 43 : The StakePoolRegistry already records the maximum number of authorities
+    #[error("Stake pool registry full")]
+    StakePoolRegistryFull,
+    /// 44 : The StakePoolDepositStakeAuthority is not recorded by the StakePoolRegistry
+    #[error("Deposit stake authority not registered")]
+    DepositStakeAuthorityNotRegistered,
+    /// 45 : The StakePoolDepositStakeAuthority is installed as the StakePool's stake_deposit_authority
+    #[error("Deposit stake authority is active")]
+    DepositStakeAuthorityActive,
+    /// 46 : Invalid StakePoolRegistry
+    #[error("StakePoolRegistry key is invalid")]
+    InvalidStakePoolRegistry,
+    /// 47 : The StakePoolDepositStakeAuthority is still recorded by the StakePoolRegistry
+    #[error("Deposit stake authority is registered")]
+    DepositStakeAuthorityRegistered,
+    /// 48 : The keeper tip exceeds `MAX_KEEPER_TIP_BPS` of a DepositReceipt's rent
+    #[error("Invalid keeper tip")]
+    InvalidKeeperTip,
+    /// 49 : FeeExemptions granted through the StakePoolDepositStakeAuthority are still open
+    #[error("FeeExemptions are outstanding")]
+    OutstandingFeeExemptions,
 }
@@ -0,0 +1,318 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::{FeeCurveConfig, FeeRecipient, ReceiptTransferPolicy};
+
+/// Prefix of every event logged by the program, used to tell them apart from other
+/// `Program data:` logs in the same transaction.
+pub const EVENT_DISCRIMINATOR: [u8; 8] = *b"sdi_evnt";
+
+/// Borsh encoded events logged via `sol_log_data` for every state change.
+///
+/// Released variants are never changed, so indexers built against an older version keep decoding
+/// them. Additional data is logged as a new variant appended to the end instead.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum StakeDepositInterceptorEvent {
+    /// A StakePoolDepositStakeAuthority was initialized.
+    InitStakePoolDepositStakeAuthority {
+        deposit_stake_authority: Pubkey,
+        stake_pool: Pubkey,
+        authority: Pubkey,
+        fee_wallet: Pubkey,
+        cool_down_seconds: u64,
+        initial_fee_bps: u32,
+        fee_curve: FeeCurveConfig,
+    },
+    /// Fee parameter changes were queued.
+    UpdateStakePoolDepositStakeAuthority {
+        deposit_stake_authority: Pubkey,
+        fee_wallet: Pubkey,
+        cool_down_seconds: u64,
+        initial_fee_bps: u32,
+        fee_curve: FeeCurveConfig,
+        effective_at: i64,
+    },
+    /// Queued fee parameters took effect.
+    ApplyPendingParameters {
+        deposit_stake_authority: Pubkey,
+        fee_wallet: Pubkey,
+        cool_down_seconds: u64,
+        initial_fee_bps: u32,
+        fee_curve: FeeCurveConfig,
+    },
+    /// A new authority was proposed.
+    ProposeAuthority {
+        deposit_stake_authority: Pubkey,
+        authority: Pubkey,
+        pending_authority: Pubkey,
+    },
+    /// The pending authority accepted the transfer.
+    AcceptAuthority {
+        deposit_stake_authority: Pubkey,
+        previous_authority: Pubkey,
+        authority: Pubkey,
+    },
+    /// A pending authority transfer was cancelled.
+    CancelAuthorityTransfer {
+        deposit_stake_authority: Pubkey,
+        pending_authority: Pubkey,
+    },
+    /// A FeeExemption was granted.
+    AddFeeExemption {
+        deposit_stake_authority: Pubkey,
+        depositor: Pubkey,
+        initial_fee_bps: u32,
+    },
+    /// A FeeExemption was revoked.
+    RemoveFeeExemption {
+        deposit_stake_authority: Pubkey,
+        depositor: Pubkey,
+    },
+    /// A DepositReceipt was created for pool tokens minted into the vault.
+    Deposit {
+        deposit_receipt: Pubkey,
+        deposit_stake_authority: Pubkey,
+        owner: Pubkey,
+        pool_tokens_minted: u64,
+        deposit_time: u64,
+        initial_fee_bps: u32,
+    },
+    /// A DepositReceipt's owner changed.
+    ChangeDepositReceiptOwner {
+        deposit_receipt: Pubkey,
+        previous_owner: Pubkey,
+        owner: Pubkey,
+    },
+    /// `lst_amount` was moved from a DepositReceipt into a new DepositReceipt.
+    SplitDepositReceipt {
+        deposit_receipt: Pubkey,
+        new_deposit_receipt: Pubkey,
+        lst_amount: u64,
+    },
+    /// The source DepositReceipt was merged into the destination and closed.
+    MergeDepositReceipts {
+        destination_deposit_receipt: Pubkey,
+        source_deposit_receipt: Pubkey,
+        lst_amount: u64,
+        deposit_time: u64,
+    },
+    /// Pool tokens were claimed from a DepositReceipt. `fee_amount` accrued in the vault and the
+    /// destination received `net_amount`, after any Token-2022 transfer fee.
+    ClaimPoolTokens {
+        deposit_receipt: Pubkey,
+        owner: Pubkey,
+        destination: Pubkey,
+        fee_amount: u64,
+        net_amount: u64,
+        remaining_lst_amount: u64,
+    },
+    /// The fee recipients were replaced.
+    SetFeeRecipients {
+        deposit_stake_authority: Pubkey,
+        fee_recipients: Vec<FeeRecipient>,
+    },
+    /// Accrued fees were swept from the vault. The amounts are those received by the fee wallet
+    /// and each fee recipient, after any Token-2022 transfer fee.
+    SweepFees {
+        deposit_stake_authority: Pubkey,
+        fee_wallet_amount: u64,
+        fee_recipient_amounts: Vec<u64>,
+    },
+    /// A StakePoolDepositStakeAuthority and its vault were closed.
+    CloseStakePoolDepositStakeAuthority {
+        deposit_stake_authority: Pubkey,
+        stake_pool: Pubkey,
+        destination: Pubkey,
+    },
+    /// Pool tokens in the vault beyond `total_outstanding_lst` and `accrued_fees` were recovered.
+    RecoverExcessVaultTokens {
+        deposit_stake_authority: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+        transfer_fee: u64,
+    },
+    /// The paused operations were replaced.
+    SetPauseState {
+        deposit_stake_authority: Pubkey,
+        deposits_paused: bool,
+        early_claims_paused: bool,
+    },
+    /// The deposit caps were changed. 0 means no limit.
+    SetDepositCaps {
+        deposit_stake_authority: Pubkey,
+        max_deposit_lst_per_receipt: u64,
+        max_total_outstanding_lst: u64,
+    },
+    /// The keeper tip of permissionless claims was changed.
+    SetKeeperTip {
+        deposit_stake_authority: Pubkey,
+        keeper_tip_lamports: u64,
+    },
+    /// A DepositReceipt was claimed and closed, and the `pool_tokens_withdrawn` left after the
+    /// `fee_amount` were withdrawn from the StakePool as `lamports_withdrawn` to `withdrawn_to`,
+    /// either a new stake account or the owner.
+    ClaimAndWithdraw {
+        deposit_receipt: Pubkey,
+        owner: Pubkey,
+        withdrawn_to: Pubkey,
+        fee_amount: u64,
+        pool_tokens_withdrawn: u64,
+        lamports_withdrawn: u64,
+    },
+    /// A 1-of-1 `receipt_mint` token representing the DepositReceipt was minted to the owner.
+    TokenizeDepositReceipt {
+        deposit_receipt: Pubkey,
+        owner: Pubkey,
+        receipt_mint: Pubkey,
+    },
+    /// Whether the DepositReceipt owner must sign `DepositStake` was changed.
+    SetRequireOwnerSignature {
+        deposit_stake_authority: Pubkey,
+        require_owner_signature: bool,
+    },
+    /// The policy applied when a DepositReceipt's owner changes was changed.
+    SetReceiptTransferPolicy {
+        deposit_stake_authority: Pubkey,
+        receipt_transfer_policy: ReceiptTransferPolicy,
+    },
+    /// A StakePoolDepositStakeAuthority or DepositReceipt was upgraded to `version`.
+    MigrateAccount {
+        account: Pubkey,
+        previous_version: u8,
+        version: u8,
+    },
+    /// A StakePoolDepositStakeAuthority was recorded in its StakePool's registry.
+    RegisterStakePoolDepositStakeAuthority {
+        stake_pool_registry: Pubkey,
+        stake_pool: Pubkey,
+        deposit_stake_authority: Pubkey,
+        active_deposit_stake_authority: Pubkey,
+    },
+    /// A StakePoolDepositStakeAuthority was removed from its StakePool's registry.
+    DeregisterStakePoolDepositStakeAuthority {
+        stake_pool_registry: Pubkey,
+        stake_pool: Pubkey,
+        deposit_stake_authority: Pubkey,
+        active_deposit_stake_authority: Pubkey,
+    },
+    /// The minimum pool tokens minted by a single deposit was changed. 0 means no minimum.
+    SetMinDepositLst {
+        deposit_stake_authority: Pubkey,
+        min_deposit_lst: u64,
+    },
+    /// Token-2022 withheld `transfer_fee` from the tokens sent by the preceding `ClaimPoolTokens`,
+    /// on top of its `net_amount`.
+    ClaimPoolTokensTransferFee {
+        deposit_receipt: Pubkey,
+        transfer_fee: u64,
+    },
+    /// `keeper_tip_lamports` of a closed DepositReceipt's rent were paid to the keeper that
+    /// cranked its claim.
+    PayKeeperTip {
+        deposit_receipt: Pubkey,
+        keeper: Pubkey,
+        keeper_tip_lamports: u64,
+    },
+    /// Token-2022 withheld `transfer_fee` in total from the tokens sent by the preceding
+    /// `SweepFees`.
+    SweepFeesTransferFee {
+        deposit_stake_authority: Pubkey,
+        transfer_fee: u64,
+    },
+    /// A DepositReceipt's `deposit_time` was reset by the `ResetDepositTime` transfer policy when
+    /// its owner changed.
+    ResetDepositTime {
+        deposit_receipt: Pubkey,
+        deposit_time: u64,
+    },
+    /// A StakePool's registry re-read the StakePool's `stake_deposit_authority`.
+    RefreshStakePoolRegistry {
+        stake_pool_registry: Pubkey,
+        stake_pool: Pubkey,
+        active_deposit_stake_authority: Pubkey,
+    },
+}
+
+impl StakeDepositInterceptorEvent {
+    /// Log the event, which shows up as `Program data: <base64>` in the transaction logs.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.to_log_data()]);
+    }
+
+    /// Serialize the event with the `EVENT_DISCRIMINATOR` prefix.
+    pub fn to_log_data(&self) -> Vec<u8> {
+        let mut data = EVENT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&borsh::to_vec(self).unwrap());
+        data
+    }
+
+    /// Decode the (base64 decoded) data of a `Program data:` log. Returns `None` if the data is
+    /// not an event from this program.
+    pub fn from_log_data(data: &[u8]) -> Option<Self> {
+        let event_data = data.strip_prefix(&EVENT_DISCRIMINATOR)?;
+        Self::try_from_slice(event_data).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_round_trip() {
+        let event = StakeDepositInterceptorEvent::ClaimPoolTokens {
+            deposit_receipt: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            fee_amount: 20,
+            net_amount: 980,
+            remaining_lst_amount: 0,
+        };
+        let data = event.to_log_data();
+        assert_eq!(
+            StakeDepositInterceptorEvent::from_log_data(&data),
+            Some(event)
+        );
+    }
+
+    #[test]
+    fn test_released_variants_are_unchanged() {
+        // ClaimPoolTokens is the 13th variant with 3 pubkeys and 3 u64s since it was released
+        let data = borsh::to_vec(&StakeDepositInterceptorEvent::ClaimPoolTokens {
+            deposit_receipt: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            fee_amount: 20,
+            net_amount: 980,
+            remaining_lst_amount: 0,
+        })
+        .unwrap();
+        assert_eq!(data[0], 12);
+        assert_eq!(data.len(), 1 + 3 * 32 + 3 * 8);
+
+        // ChangeDepositReceiptOwner is the 10th variant with 3 pubkeys
+        let data = borsh::to_vec(&StakeDepositInterceptorEvent::ChangeDepositReceiptOwner {
+            deposit_receipt: Pubkey::new_unique(),
+            previous_owner: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+        })
+        .unwrap();
+        assert_eq!(data[0], 9);
+        assert_eq!(data.len(), 1 + 3 * 32);
+    }
+
+    #[test]
+    fn test_from_log_data_rejects_foreign_data() {
+        let event = StakeDepositInterceptorEvent::RemoveFeeExemption {
+            deposit_stake_authority: Pubkey::new_unique(),
+            depositor: Pubkey::new_unique(),
+        };
+        // Missing discriminator
+        let data = borsh::to_vec(&event).unwrap();
+        assert_eq!(StakeDepositInterceptorEvent::from_log_data(&data), None);
+        // Trailing bytes
+        let mut data = event.to_log_data();
+        data.push(0);
+        assert_eq!(StakeDepositInterceptorEvent::from_log_data(&data), None);
+    }
+}
@@ -6,10 +6,15 @@ use solana_program::{
     system_program,
     sysvar,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use shank::ShankInstruction;
 
-/// Initialize arguments for StakePoolDepositStakeAuthority
+use crate::state::{ FeeCurveConfig, FeeRecipient, ReceiptTransferPolicy };
+
+/// Initialize arguments for StakePoolDepositStakeAuthority.
+///
+/// Released args are never changed, so existing clients keep working. Additional fields go in
+/// InitStakePoolDepositStakeAuthorityV2Args instead.
 #[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct InitStakePoolDepositStakeAuthorityArgs {
     pub fee_wallet: Pubkey,
@@ -17,7 +22,30 @@ pub struct InitStakePoolDepositStakeAuthorityArgs {
     pub initial_fee_bps: u32,
 }
 
-/// Update arguments for StakePoolDepositStakeAuthority
+/// Initialize arguments for StakePoolDepositStakeAuthority with a FeeCurve.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct InitStakePoolDepositStakeAuthorityV2Args {
+    pub fee_wallet: Pubkey,
+    pub cool_down_seconds: u64,
+    pub initial_fee_bps: u32,
+    pub fee_curve: FeeCurveConfig,
+}
+
+impl From<InitStakePoolDepositStakeAuthorityArgs> for InitStakePoolDepositStakeAuthorityV2Args {
+    fn from(args: InitStakePoolDepositStakeAuthorityArgs) -> Self {
+        Self {
+            fee_wallet: args.fee_wallet,
+            cool_down_seconds: args.cool_down_seconds,
+            initial_fee_bps: args.initial_fee_bps,
+            fee_curve: FeeCurveConfig::Linear,
+        }
+    }
+}
+
+/// Update arguments for StakePoolDepositStakeAuthority.
+///
+/// Released args are never changed, so existing clients keep working. Additional fields go in
+/// UpdateStakePoolDepositStakeAuthorityV2Args instead.
 #[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
 pub struct UpdateStakePoolDepositStakeAuthorityArgs {
     pub fee_wallet: Option<Pubkey>,
@@ -25,6 +53,43 @@ pub struct UpdateStakePoolDepositStakeAuthorityArgs {
     pub initial_fee_bps: Option<u32>,
 }
 
+/// Update arguments for StakePoolDepositStakeAuthority, including the parameters added since
+/// UpdateStakePoolDepositStakeAuthorityArgs was released.
+#[derive(Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UpdateStakePoolDepositStakeAuthorityV2Args {
+    pub fee_wallet: Option<Pubkey>,
+    pub cool_down_seconds: Option<u64>,
+    pub initial_fee_bps: Option<u32>,
+    pub fee_curve: Option<FeeCurveConfig>,
+    /// Timestamp the changes can be applied at. Defaults to the minimum update delay from now.
+    pub effective_at: Option<i64>,
+    /// Maximum pool tokens minted by a single deposit, 0 for no limit. Takes effect immediately.
+    pub max_deposit_lst_per_receipt: Option<u64>,
+    /// Maximum pool tokens owed to all DepositReceipts after a deposit, 0 for no limit. Takes
+    /// effect immediately.
+    pub max_total_outstanding_lst: Option<u64>,
+    /// Minimum pool tokens minted by a single deposit, 0 for no minimum. Takes effect immediately.
+    pub min_deposit_lst: Option<u64>,
+    /// Lamports of a closed DepositReceipt's rent paid to the keeper of a permissionless claim.
+    /// Takes effect immediately.
+    pub keeper_tip_lamports: Option<u64>,
+    /// Whether the DepositReceipt owner must sign `DepositStake`. Takes effect immediately.
+    pub require_owner_signature: Option<bool>,
+    /// Policy applied when a DepositReceipt's owner changes. Takes effect immediately.
+    pub receipt_transfer_policy: Option<ReceiptTransferPolicy>,
+}
+
+impl From<UpdateStakePoolDepositStakeAuthorityArgs> for UpdateStakePoolDepositStakeAuthorityV2Args {
+    fn from(args: UpdateStakePoolDepositStakeAuthorityArgs) -> Self {
+        Self {
+            fee_wallet: args.fee_wallet,
+            cool_down_seconds: args.cool_down_seconds,
+            initial_fee_bps: args.initial_fee_bps,
+            ..Self::default()
+        }
+    }
+}
+
 /// Arguments for DepositStake.
 ///
 /// NOTE: we must pass the owner as a separate arg (or account) as
@@ -48,11 +113,71 @@ pub struct DepositStakeWithSlippageArgs {
     pub minimum_pool_tokens_out: u64,
 }
 
+/// Arguments for DepositSol.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct DepositSolArgs {
+    pub owner: Pubkey,
+    pub lamports: u64,
+}
+
+/// Arguments for DepositSolWithSlippage.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct DepositSolWithSlippageArgs {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub minimum_pool_tokens_out: u64,
+}
+
+/// Arguments for SetFeeRecipients.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SetFeeRecipientsArgs {
+    /// Recipients of a share of every fee. The `fee_wallet` receives the remainder.
+    pub fee_recipients: Vec<FeeRecipient>,
+}
+
+/// Arguments for AddFeeExemption.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AddFeeExemptionArgs {
+    /// Initial fee rate (in bps) for the depositor's DepositReceipts. 0 is a full exemption.
+    pub initial_fee_bps: u32,
+}
+
+/// Arguments for ClaimPoolTokensPartial.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ClaimPoolTokensPartialArgs {
+    /// Amount of "pool" tokens to claim from the DepositReceipt, fees included.
+    pub amount: u64,
+}
+
+/// Arguments for SplitDepositReceipt.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SplitDepositReceiptArgs {
+    /// Amount of "pool" tokens to move to the new DepositReceipt.
+    pub lst_amount: u64,
+}
+
+/// Arguments for SetPauseState.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SetPauseStateArgs {
+    /// Reject DepositStake and DepositSol.
+    pub deposits_paused: bool,
+    /// Reject claims during the cool down. Claims after the cool down are always allowed.
+    pub early_claims_paused: bool,
+}
+
+/// Arguments for ClaimAndWithdrawStake and ClaimAndWithdrawSol.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ClaimAndWithdrawArgs {
+    /// Minimum lamports the StakePool must withdraw for the net "pool" tokens, if any.
+    pub minimum_lamports_out: Option<u64>,
+}
+
 /// Instructions supported by the StakeDepositInterceptor program.
 #[derive(ShankInstruction)]
 #[derive(ShankInstruction, Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
 pub enum StakeDepositInterceptorInstruction {
-    ///   Initializes the StakePoolDepositStakeAuthority for the given StakePool.
+    ///   Initializes the StakePoolDepositStakeAuthority for the given StakePool with a `Linear`
+    ///   fee curve.
     ///
     ///   0. `[w,s]` Payer that will fund the StakePoolDepositStakeAuthority account.
     ///   1. `[w]` New StakePoolDepositStakeAuthority to create.
@@ -78,14 +203,13 @@ pub enum StakeDepositInterceptorInstruction {
     #[account(10, name = "system_program", desc = "System program")]
     InitStakePoolDepositStakeAuthority(InitStakePoolDepositStakeAuthorityArgs),
 
-    ///   Updates the StakePoolDepositStakeAuthority for the given StakePool.
+    ///   Queues changes to the StakePoolDepositStakeAuthority's fee parameters for the given
+    ///   StakePool, like UpdateStakePoolDepositStakeAuthorityV2 with the other parameters unset.
     ///
     ///   0. `[w]` StakePoolDepositStakeAuthority PDA to be updated
     ///   1. `[s]` Authority
-    ///   2. `[]` (Optional) New authority
     #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
     #[account(1, signer, name = "authority", desc = "Authority that can update the deposit authority")]
-    #[account(2, optional, name = "new_authority", desc = "Optional new authority")]
     UpdateStakePoolDepositStakeAuthority(UpdateStakePoolDepositStakeAuthorityArgs),
 
     ///   Deposit some stake into the pool. The "pool" token minted is held by the DepositReceipt's
@@ -97,7 +221,7 @@ pub enum StakeDepositInterceptorInstruction {
     ///   2. `[w]` DepositReceipt to be created
     ///   3. `[w]` Stake pool
     ///   4. `[w]` Validator stake list storage account
-    ///   5. `[]` Stake pool deposit authority (aka the StakePoolDepositStakeAuthority PDA)
+    ///   5. `[w]` Stake pool deposit authority (aka the StakePoolDepositStakeAuthority PDA)
     ///   6. `[s]` Base for PDA seed
     ///   7. `[]` Stake pool withdraw authority
     ///   8. `[w]` Stake account to join the pool
@@ -112,12 +236,16 @@ pub enum StakeDepositInterceptorInstruction {
     ///   17. `[]` Pool token program id
     ///   18. `[]` Stake program id
     ///   19. `[]` System program id
+    ///   20. `[]` (Optional) FeeExemption PDA for the owner, only honored when the owner signs
+    ///   20.. `[s]` (Optional) DepositReceipt owner, required when the StakePoolDepositStakeAuthority
+    ///       has `require_owner_signature` set. Found by key among the accounts after the System
+    ///       program, whether or not the FeeExemption is passed.
     #[account(0, writable, signer, name = "payer", desc = "Funding account")]
     #[account(1, name = "stake_pool_program", desc = "Stake pool program id")]
     #[account(2, writable, name = "deposit_receipt", desc = "PDA to store deposit receipt")]
     #[account(3, writable, name = "stake_pool", desc = "StakePool to deposit into")]
     #[account(4, writable, name = "validator_stake_list", desc = "Validator stake list storage account")]
-    #[account(5, name = "deposit_stake_authority", desc = "StakePool stake_deposit_authority")]
+    #[account(5, writable, name = "deposit_stake_authority", desc = "StakePool stake_deposit_authority")]
     #[account(6, signer, name = "base", desc = "Base for PDA seed")]
     #[account(7, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
     #[account(8, writable, name = "stake", desc = "Stake account to join the pool")]
@@ -132,6 +260,8 @@ pub enum StakeDepositInterceptorInstruction {
     #[account(17, name = "token_program", desc = "Pool token program id")]
     #[account(18, name = "stake_program", desc = "Stake program id")]
     #[account(19, name = "system_program", desc = "System program id")]
+    #[account(20, optional, name = "fee_exemption", desc = "FeeExemption PDA for the owner")]
+    #[account(21, optional, signer, name = "owner", desc = "DepositReceipt owner, found by key after the System program")]
     DepositStake(DepositStakeArgs),
 
     ///   Deposit stake with slippage protection. The "pool" token minted is held by the DepositReceipt's
@@ -143,7 +273,7 @@ pub enum StakeDepositInterceptorInstruction {
     ///   2. `[w]` DepositReceipt to be created
     ///   3. `[w]` Stake pool
     ///   4. `[w]` Validator stake list storage account
-    ///   5. `[]` Stake pool deposit authority (aka the StakePoolDepositStakeAuthority PDA)
+    ///   5. `[w]` Stake pool deposit authority (aka the StakePoolDepositStakeAuthority PDA)
     ///   6. `[s]` Base for PDA seed
     ///   7. `[]` Stake pool withdraw authority
     ///   8. `[w]` Stake account to join the pool
@@ -158,12 +288,16 @@ pub enum StakeDepositInterceptorInstruction {
     ///   17. `[]` Pool token program id
     ///   18. `[]` Stake program id
     ///   19. `[]` System program id
+    ///   20. `[]` (Optional) FeeExemption PDA for the owner, only honored when the owner signs
+    ///   20.. `[s]` (Optional) DepositReceipt owner, required when the StakePoolDepositStakeAuthority
+    ///       has `require_owner_signature` set. Found by key among the accounts after the System
+    ///       program, whether or not the FeeExemption is passed.
     #[account(0, writable, signer, name = "payer", desc = "Funding account")]
     #[account(1, name = "stake_pool_program", desc = "Stake pool program id")]
     #[account(2, writable, name = "deposit_receipt", desc = "PDA to store deposit receipt")]
     #[account(3, writable, name = "stake_pool", desc = "StakePool to deposit into")]
     #[account(4, writable, name = "validator_stake_list", desc = "Validator stake list storage account")]
-    #[account(5, name = "deposit_stake_authority", desc = "StakePool stake_deposit_authority")]
+    #[account(5, writable, name = "deposit_stake_authority", desc = "StakePool stake_deposit_authority")]
     #[account(6, signer, name = "base", desc = "Base for PDA seed")]
     #[account(7, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
     #[account(8, writable, name = "stake", desc = "Stake account to join the pool")]
@@ -178,46 +312,637 @@ pub enum StakeDepositInterceptorInstruction {
     #[account(17, name = "token_program", desc = "Pool token program id")]
     #[account(18, name = "stake_program", desc = "Stake program id")]
     #[account(19, name = "system_program", desc = "System program id")]
+    #[account(20, optional, name = "fee_exemption", desc = "FeeExemption PDA for the owner")]
+    #[account(21, optional, signer, name = "owner", desc = "DepositReceipt owner, found by key after the System program")]
     DepositStakeWithSlippage(DepositStakeWithSlippageArgs),
 
     ///   Update the `owner` of the DepositReceipt so the new owner
-    ///   has the authority to claim the "pool" tokens.
+    ///   has the authority to claim the "pool" tokens. Subject to the
+    ///   StakePoolDepositStakeAuthority's `ReceiptTransferPolicy`.
     ///
     ///   0. `[w]` DepositReceipt PDA
     ///   1. `[s]` current owner of the DepositReceipt
     ///   2. `[]` new owner for the DepositReceipt
+    ///   3. `[]` StakePoolDepositStakeAuthority PDA of the DepositReceipt
     #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
     #[account(1, signer, name = "current_owner", desc = "Current owner of the receipt")]
     #[account(2, name = "new_owner", desc = "New owner for the receipt")]
+    #[account(3, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
     ChangeDepositReceiptOwner,
 
     ///   Claim the "pool" tokens held by the program from a former DepositStake
     ///   transaction. Fees will be deducted from the destination token account
-    ///   if this instruction is invoked during the cool down period.
+    ///   if this instruction is invoked during the cool down period. Fees stay in
+    ///   the vault and accrue on the StakePoolDepositStakeAuthority until swept.
     ///
     ///   0. `[w]` DepositReceipt PDA
     ///   1. `[w,s]` owner of the DepositReceipt
     ///   2. `[w]` vault token account to send tokens from
-    ///   3. `[w]` destination token account
-    ///   4. `[w]` fee wallet token account
-    ///   5. `[]` StakePoolDepositStakeAuthority PDA
-    ///   6. `[]` Pool token mint
-    ///   7. `[]` Token program id
-    ///   8. `[]` System program id
+    ///   3. `[w]` destination token account, owned by the owner unless the owner signs
+    ///   4. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   5. `[]` Pool token mint
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[w]` Rent payer of the DepositReceipt (the owner for DepositReceipts without one)
+    ///   9.. `[]` Extra accounts required by the pool mint's transfer hook, if any
+    ///   last. `[w,s]` (Optional) Keeper receiving the keeper tip of a permissionless claim,
+    ///      after the transfer hook accounts. Only a signing last account is treated as the keeper.
     #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
     #[account(1, writable, signer, name = "owner", desc = "Owner of the receipt")]
     #[account(2, writable, name = "vault", desc = "Vault token account")]
     #[account(3, writable, name = "destination", desc = "Destination token account")]
-    #[account(4, writable, name = "fee_wallet", desc = "Fee wallet token account")]
-    #[account(5, name = "deposit_authority", desc = "Deposit authority PDA")]
-    #[account(6, name = "pool_mint", desc = "Pool token mint")]
-    #[account(7, name = "token_program", desc = "Token program")]
-    #[account(8, name = "system_program", desc = "System program")]
+    #[account(4, writable, name = "deposit_authority", desc = "Deposit authority PDA")]
+    #[account(5, name = "pool_mint", desc = "Pool token mint")]
+    #[account(6, name = "token_program", desc = "Token program")]
+    #[account(7, name = "system_program", desc = "System program")]
+    #[account(8, writable, name = "rent_payer", desc = "Rent payer of the receipt, refunded when it is closed")]
     ClaimPoolTokens,
+
+    ///   Claim a portion of the "pool" tokens held by the program from a former DepositStake
+    ///   transaction. Fees are only charged on the claimed `amount`, accrue like those of
+    ///   ClaimPoolTokens, and the DepositReceipt's `lst_amount` is reduced accordingly. The
+    ///   DepositReceipt is closed once nothing is left.
+    ///
+    ///   0. `[w]` DepositReceipt PDA
+    ///   1. `[w,s]` owner of the DepositReceipt
+    ///   2. `[w]` vault token account to send tokens from
+    ///   3. `[w]` destination token account, owned by the owner unless the owner signs
+    ///   4. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   5. `[]` Pool token mint
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[w]` Rent payer of the DepositReceipt (the owner for DepositReceipts without one)
+    ///   9.. `[]` Extra accounts required by the pool mint's transfer hook, if any
+    ///   last. `[w,s]` (Optional) Keeper receiving the keeper tip of a permissionless claim,
+    ///      after the transfer hook accounts. Only a signing last account is treated as the keeper.
+    #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(1, writable, signer, name = "owner", desc = "Owner of the receipt")]
+    #[account(2, writable, name = "vault", desc = "Vault token account")]
+    #[account(3, writable, name = "destination", desc = "Destination token account")]
+    #[account(4, writable, name = "deposit_authority", desc = "Deposit authority PDA")]
+    #[account(5, name = "pool_mint", desc = "Pool token mint")]
+    #[account(6, name = "token_program", desc = "Token program")]
+    #[account(7, name = "system_program", desc = "System program")]
+    #[account(8, writable, name = "rent_payer", desc = "Rent payer of the receipt, refunded when it is closed")]
+    ClaimPoolTokensPartial(ClaimPoolTokensPartialArgs),
+
+    ///   Split `lst_amount` off of an existing DepositReceipt into a new DepositReceipt.
+    ///   The new DepositReceipt inherits the owner, deposit time, and fee parameters
+    ///   of the original.
+    ///
+    ///   0. `[w,s]` Payer for the new DepositReceipt account
+    ///   1. `[w]` DepositReceipt PDA to split from
+    ///   2. `[s]` owner of the DepositReceipt
+    ///   3. `[w]` new DepositReceipt PDA
+    ///   4. `[s]` Base for the new DepositReceipt PDA
+    ///   5. `[]` System program id
+    ///   6. `[w]` StakePoolDepositStakeAuthority PDA of the DepositReceipt
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(2, signer, name = "owner", desc = "Owner of the receipt")]
+    #[account(3, writable, name = "new_deposit_receipt", desc = "PDA storing the new deposit receipt")]
+    #[account(4, signer, name = "base", desc = "Base for new receipt PDA seed")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(6, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    SplitDepositReceipt(SplitDepositReceiptArgs),
+
+    ///   Merge the source DepositReceipt into the destination DepositReceipt. Both
+    ///   DepositReceipts must have the same owner, StakePoolDepositStakeAuthority, and
+    ///   fee parameters. The deposit time of the destination becomes the `lst_amount`
+    ///   weighted average of both. The source DepositReceipt is closed and its
+    ///   lamports refunded to its rent payer.
+    ///
+    ///   0. `[w]` destination DepositReceipt PDA
+    ///   1. `[w]` source DepositReceipt PDA
+    ///   2. `[w,s]` owner of both DepositReceipts
+    ///   3. `[w]` StakePoolDepositStakeAuthority PDA of both DepositReceipts
+    ///   4. `[w]` Rent payer of the source DepositReceipt (the owner for DepositReceipts without one)
+    #[account(0, writable, name = "destination_deposit_receipt", desc = "PDA storing the receipt to merge into")]
+    #[account(1, writable, name = "source_deposit_receipt", desc = "PDA storing the receipt to merge and close")]
+    #[account(2, writable, signer, name = "owner", desc = "Owner of both receipts")]
+    #[account(3, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(4, writable, name = "source_rent_payer", desc = "Rent payer of the source receipt")]
+    MergeDepositReceipts,
+
+    ///   Deposit SOL directly into the pool's reserve account. The "pool" token minted is held by
+    ///   the DepositReceipt's Vault token Account rather than a token Account designated by the
+    ///   depositor. Requires the StakePoolDepositStakeAuthority to be the StakePool's
+    ///   `sol_deposit_authority`.
+    ///
+    ///   0. `[w,s]` payer of the new account rent
+    ///   1. `[]` stake pool program id
+    ///   2. `[w]` DepositReceipt to be created
+    ///   3. `[w]` Stake pool
+    ///   4. `[w]` Stake pool sol deposit authority (aka the StakePoolDepositStakeAuthority PDA)
+    ///   5. `[s]` Base for PDA seed
+    ///   6. `[]` Stake pool withdraw authority
+    ///   7. `[w]` Reserve stake account, to deposit SOL
+    ///   8. `[w,s]` Account providing the lamports to be deposited into the pool
+    ///   9. `[w]` Vault account to receive pool tokens
+    ///   10. `[w]` Account to receive pool fee tokens
+    ///   11. `[w]` Account to receive a portion of pool fee tokens as referral fees
+    ///   12. `[w]` Pool token mint account
+    ///   13. `[]` Pool token program id
+    ///   14. `[]` System program id
+    ///   15. `[]` (Optional) FeeExemption PDA for the owner, only honored when the owner is the
+    ///       payer or the account providing the lamports
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, name = "stake_pool_program", desc = "Stake pool program id")]
+    #[account(2, writable, name = "deposit_receipt", desc = "PDA to store deposit receipt")]
+    #[account(3, writable, name = "stake_pool", desc = "StakePool to deposit into")]
+    #[account(4, writable, name = "deposit_stake_authority", desc = "StakePool sol_deposit_authority")]
+    #[account(5, signer, name = "base", desc = "Base for PDA seed")]
+    #[account(6, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
+    #[account(7, writable, name = "reserve_stake_account", desc = "Reserve stake account, to deposit SOL")]
+    #[account(8, writable, signer, name = "lamports_from", desc = "Account providing the lamports to be deposited into the pool")]
+    #[account(9, writable, name = "vault", desc = "Vault account to receive pool tokens")]
+    #[account(10, writable, name = "manager_fee_account", desc = "Account to receive pool fee tokens")]
+    #[account(11, writable, name = "referrer_pool_tokens_account", desc = "Account to receive a portion of pool fee tokens as referral fees")]
+    #[account(12, writable, name = "pool_mint", desc = "Pool token mint account")]
+    #[account(13, name = "token_program", desc = "Pool token program id")]
+    #[account(14, name = "system_program", desc = "System program id")]
+    #[account(15, optional, name = "fee_exemption", desc = "FeeExemption PDA for the owner")]
+    DepositSol(DepositSolArgs),
+
+    ///   Deposit SOL directly into the pool's reserve account with slippage protection. The "pool"
+    ///   token minted is held by the DepositReceipt's Vault token Account rather than a token
+    ///   Account designated by the depositor.
+    ///
+    ///   0. `[w,s]` payer of the new account rent
+    ///   1. `[]` stake pool program id
+    ///   2. `[w]` DepositReceipt to be created
+    ///   3. `[w]` Stake pool
+    ///   4. `[w]` Stake pool sol deposit authority (aka the StakePoolDepositStakeAuthority PDA)
+    ///   5. `[s]` Base for PDA seed
+    ///   6. `[]` Stake pool withdraw authority
+    ///   7. `[w]` Reserve stake account, to deposit SOL
+    ///   8. `[w,s]` Account providing the lamports to be deposited into the pool
+    ///   9. `[w]` Vault account to receive pool tokens
+    ///   10. `[w]` Account to receive pool fee tokens
+    ///   11. `[w]` Account to receive a portion of pool fee tokens as referral fees
+    ///   12. `[w]` Pool token mint account
+    ///   13. `[]` Pool token program id
+    ///   14. `[]` System program id
+    ///   15. `[]` (Optional) FeeExemption PDA for the owner, only honored when the owner is the
+    ///       payer or the account providing the lamports
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, name = "stake_pool_program", desc = "Stake pool program id")]
+    #[account(2, writable, name = "deposit_receipt", desc = "PDA to store deposit receipt")]
+    #[account(3, writable, name = "stake_pool", desc = "StakePool to deposit into")]
+    #[account(4, writable, name = "deposit_stake_authority", desc = "StakePool sol_deposit_authority")]
+    #[account(5, signer, name = "base", desc = "Base for PDA seed")]
+    #[account(6, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
+    #[account(7, writable, name = "reserve_stake_account", desc = "Reserve stake account, to deposit SOL")]
+    #[account(8, writable, signer, name = "lamports_from", desc = "Account providing the lamports to be deposited into the pool")]
+    #[account(9, writable, name = "vault", desc = "Vault account to receive pool tokens")]
+    #[account(10, writable, name = "manager_fee_account", desc = "Account to receive pool fee tokens")]
+    #[account(11, writable, name = "referrer_pool_tokens_account", desc = "Account to receive a portion of pool fee tokens as referral fees")]
+    #[account(12, writable, name = "pool_mint", desc = "Pool token mint account")]
+    #[account(13, name = "token_program", desc = "Pool token program id")]
+    #[account(14, name = "system_program", desc = "System program id")]
+    #[account(15, optional, name = "fee_exemption", desc = "FeeExemption PDA for the owner")]
+    DepositSolWithSlippage(DepositSolWithSlippageArgs),
+
+    ///   Grant a depositor a reduced initial fee rate for DepositReceipts created through the
+    ///   StakePoolDepositStakeAuthority. The lower of the FeeExemption's and the
+    ///   StakePoolDepositStakeAuthority's rate is applied.
+    ///
+    ///   0. `[w,s]` Payer for the FeeExemption account
+    ///   1. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   2. `[s]` Authority of the StakePoolDepositStakeAuthority
+    ///   3. `[]` Depositor (owner of future DepositReceipts) to exempt
+    ///   4. `[w]` FeeExemption PDA to be created
+    ///   5. `[]` System program id
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(2, signer, name = "authority", desc = "Authority of the deposit authority")]
+    #[account(3, name = "depositor", desc = "Depositor to exempt")]
+    #[account(4, writable, name = "fee_exemption", desc = "PDA storing the fee exemption")]
+    #[account(5, name = "system_program", desc = "System program")]
+    AddFeeExemption(AddFeeExemptionArgs),
+
+    ///   Revoke a depositor's FeeExemption. Existing DepositReceipts are unaffected.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority of the StakePoolDepositStakeAuthority
+    ///   2. `[w]` FeeExemption PDA to be closed
+    ///   3. `[w]` Destination for the FeeExemption's lamports
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    #[account(2, writable, name = "fee_exemption", desc = "PDA storing the fee exemption")]
+    #[account(3, writable, name = "destination", desc = "Destination for the rent lamports")]
+    RemoveFeeExemption,
+
+    ///   Propose a new authority for the StakePoolDepositStakeAuthority. The current authority
+    ///   keeps control until the new authority signs `AcceptAuthority`. Replaces any pending proposal.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority
+    ///   2. `[]` Proposed new authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    #[account(2, name = "new_authority", desc = "Proposed new authority")]
+    ProposeAuthority,
+
+    ///   Accept a pending authority transfer, making the signer the StakePoolDepositStakeAuthority's
+    ///   authority.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Pending authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "pending_authority", desc = "Proposed new authority")]
+    AcceptAuthority,
+
+    ///   Cancel a pending authority transfer.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    CancelAuthorityTransfer,
+
+    ///   Apply the StakePoolDepositStakeAuthority's queued fee parameters once their `effective_at`
+    ///   has passed. Permissionless.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    ApplyPendingParameters,
+
+    ///   Replace the StakePoolDepositStakeAuthority's fee recipients. Each recipient receives its
+    ///   share of every fee and the `fee_wallet` receives the remainder. Takes effect immediately
+    ///   as it does not change the fee paid by depositors.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    SetFeeRecipients(SetFeeRecipientsArgs),
+
+    ///   Send the StakePoolDepositStakeAuthority's accrued fees from the vault to the `fee_wallet`
+    ///   and fee recipients, split as in SetFeeRecipients. Permissionless.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[w]` Vault token account
+    ///   2. `[w]` Fee wallet token account
+    ///   3. `[]` Pool token mint
+    ///   4. `[]` Token program id
+    ///   5-7. `[w]` Token account of each of the StakePoolDepositStakeAuthority's fee recipients, in order
+    ///   Followed by the extra accounts required by the pool mint's transfer hook, if any
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, writable, name = "vault", desc = "Vault token account")]
+    #[account(2, writable, name = "fee_wallet", desc = "Fee wallet token account")]
+    #[account(3, name = "pool_mint", desc = "Pool token mint")]
+    #[account(4, name = "token_program", desc = "Token program")]
+    #[account(5, optional, writable, name = "fee_recipient_0", desc = "Token account of the first fee recipient")]
+    #[account(6, optional, writable, name = "fee_recipient_1", desc = "Token account of the second fee recipient")]
+    #[account(7, optional, writable, name = "fee_recipient_2", desc = "Token account of the third fee recipient")]
+    SweepFees,
+
+    ///   Close the StakePoolDepositStakeAuthority and its vault, sending their lamports to the
+    ///   destination. The vault must be empty, and every DepositReceipt created and FeeExemption
+    ///   granted through the StakePoolDepositStakeAuthority must be closed. It must no longer be the StakePool's
+    ///   `stake_deposit_authority` nor be recorded by the StakePool's StakePoolRegistry.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA to be closed
+    ///   1. `[s]` Authority
+    ///   2. `[w]` Vault token account to be closed
+    ///   3. `[w]` Destination for the rent lamports
+    ///   4. `[]` Token program id
+    ///   5. `[]` StakePool
+    ///   6. `[]` StakePoolRegistry PDA of the StakePool, which may not be initialized
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    #[account(2, writable, name = "vault", desc = "Vault token account")]
+    #[account(3, writable, name = "destination", desc = "Destination for the rent lamports")]
+    #[account(4, name = "token_program", desc = "Token program")]
+    #[account(5, name = "stake_pool", desc = "Stake Pool")]
+    #[account(6, name = "stake_pool_registry", desc = "PDA recording the stake pool's deposit authorities")]
+    CloseStakePoolDepositStakeAuthority,
+
+    ///   Transfer the vault's pool tokens beyond the StakePoolDepositStakeAuthority's
+    ///   `total_outstanding_lst` and `accrued_fees` (e.g. tokens sent to the vault directly) to
+    ///   the destination.
+    ///
+    ///   0. `[]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority
+    ///   2. `[w]` Vault token account
+    ///   3. `[w]` Destination token account
+    ///   4. `[]` Pool token mint
+    ///   5. `[]` Token program id
+    ///   6.. `[]` Extra accounts required by the pool mint's transfer hook, if any
+    #[account(0, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    #[account(2, writable, name = "vault", desc = "Vault token account")]
+    #[account(3, writable, name = "destination", desc = "Destination token account")]
+    #[account(4, name = "pool_mint", desc = "Pool token mint")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    RecoverExcessVaultTokens,
+
+    ///   Pause or unpause deposits and claims during the cool down. Takes effect immediately.
+    ///   Claims after the cool down cannot be paused so depositors' pool tokens are never frozen.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   1. `[s]` Authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority of the deposit authority")]
+    SetPauseState(SetPauseStateArgs),
+
+    ///   Claim all of the "pool" tokens held for a DepositReceipt and withdraw the amount left
+    ///   after fees from the StakePool as a stake account authorized to the owner. Fees are
+    ///   charged and accrue like those of ClaimPoolTokens, and the DepositReceipt is closed.
+    ///
+    ///   0. `[w]` DepositReceipt PDA
+    ///   1. `[w,s]` owner of the DepositReceipt, authority of the withdrawn stake account
+    ///   2. `[w]` vault token account to burn pool tokens from
+    ///   3. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   4. `[w]` Pool token mint
+    ///   5. `[]` Token program id
+    ///   6. `[]` System program id
+    ///   7. `[w]` Rent payer of the DepositReceipt (the owner for DepositReceipts without one)
+    ///   8. `[]` Stake pool program id
+    ///   9. `[w]` Stake pool
+    ///   10. `[w]` Validator stake list storage account
+    ///   11. `[]` Stake pool withdraw authority
+    ///   12. `[w]` Validator or reserve stake account to split
+    ///   13. `[w]` Uninitialized stake account to receive the withdrawal
+    ///   14. `[w]` Account to receive pool fee tokens
+    ///   15. `[]` Sysvar clock account
+    ///   16. `[]` Stake program id
+    #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(1, writable, signer, name = "owner", desc = "Owner of the receipt")]
+    #[account(2, writable, name = "vault", desc = "Vault token account")]
+    #[account(3, writable, name = "deposit_authority", desc = "Deposit authority PDA")]
+    #[account(4, writable, name = "pool_mint", desc = "Pool token mint")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, writable, name = "rent_payer", desc = "Rent payer of the receipt, refunded when it is closed")]
+    #[account(8, name = "stake_pool_program", desc = "Stake pool program id")]
+    #[account(9, writable, name = "stake_pool", desc = "StakePool to withdraw from")]
+    #[account(10, writable, name = "validator_stake_list", desc = "Validator stake list storage account")]
+    #[account(11, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
+    #[account(12, writable, name = "stake_to_split", desc = "Validator or reserve stake account to split")]
+    #[account(13, writable, name = "stake_to_receive", desc = "Uninitialized stake account to receive the withdrawal")]
+    #[account(14, writable, name = "manager_fee_account", desc = "Account to receive pool fee tokens")]
+    #[account(15, name = "clock", desc = "Sysvar clock account")]
+    #[account(16, name = "stake_program", desc = "Stake program id")]
+    ClaimAndWithdrawStake(ClaimAndWithdrawArgs),
+
+    ///   Claim all of the "pool" tokens held for a DepositReceipt and withdraw the amount left
+    ///   after fees from the StakePool's reserve as SOL sent to the owner. Fees are charged and
+    ///   accrue like those of ClaimPoolTokens, and the DepositReceipt is closed.
+    ///
+    ///   0. `[w]` DepositReceipt PDA
+    ///   1. `[w,s]` owner of the DepositReceipt, receiving the withdrawn lamports
+    ///   2. `[w]` vault token account to burn pool tokens from
+    ///   3. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   4. `[w]` Pool token mint
+    ///   5. `[]` Token program id
+    ///   6. `[]` System program id
+    ///   7. `[w]` Rent payer of the DepositReceipt (the owner for DepositReceipts without one)
+    ///   8. `[]` Stake pool program id
+    ///   9. `[w]` Stake pool
+    ///   10. `[]` Stake pool withdraw authority
+    ///   11. `[w]` Reserve stake account
+    ///   12. `[w]` Account to receive pool fee tokens
+    ///   13. `[]` Sysvar clock account
+    ///   14. `[]` Sysvar stake history account
+    ///   15. `[]` Stake program id
+    ///   16. `[s]` (Optional) Stake pool sol withdraw authority
+    #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(1, writable, signer, name = "owner", desc = "Owner of the receipt")]
+    #[account(2, writable, name = "vault", desc = "Vault token account")]
+    #[account(3, writable, name = "deposit_authority", desc = "Deposit authority PDA")]
+    #[account(4, writable, name = "pool_mint", desc = "Pool token mint")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, writable, name = "rent_payer", desc = "Rent payer of the receipt, refunded when it is closed")]
+    #[account(8, name = "stake_pool_program", desc = "Stake pool program id")]
+    #[account(9, writable, name = "stake_pool", desc = "StakePool to withdraw from")]
+    #[account(10, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
+    #[account(11, writable, name = "reserve_stake_account", desc = "Reserve stake account")]
+    #[account(12, writable, name = "manager_fee_account", desc = "Account to receive pool fee tokens")]
+    #[account(13, name = "clock", desc = "Sysvar clock account")]
+    #[account(14, name = "stake_history", desc = "Sysvar stake history account")]
+    #[account(15, name = "stake_program", desc = "Stake program id")]
+    #[account(16, optional, signer, name = "sol_withdraw_authority", desc = "Stake pool sol withdraw authority")]
+    ClaimAndWithdrawSol(ClaimAndWithdrawArgs),
+
+    ///   Mint a 1-of-1 Token-2022 token representing the DepositReceipt to the owner's associated
+    ///   token account. From then on the DepositReceipt can only be claimed with
+    ///   ClaimTokenizedDepositReceipt by whoever holds the token. DepositStakeTokenized mints the
+    ///   token as part of the deposit instead. Only allowed when the StakePoolDepositStakeAuthority's
+    ///   `ReceiptTransferPolicy` is `Allow`, since the token can be transferred freely.
+    ///
+    ///   0. `[w]` DepositReceipt PDA
+    ///   1. `[w,s]` owner of the DepositReceipt, funding the receipt mint and token account
+    ///   2. `[w]` Receipt mint PDA to create
+    ///   3. `[w]` Owner's associated token account for the receipt mint
+    ///   4. `[]` Token-2022 program id
+    ///   5. `[]` Associated Token program id
+    ///   6. `[]` System program id
+    ///   7. `[]` StakePoolDepositStakeAuthority PDA of the DepositReceipt
+    #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(1, writable, signer, name = "owner", desc = "Owner of the receipt")]
+    #[account(2, writable, name = "receipt_mint", desc = "Receipt mint PDA to create")]
+    #[account(3, writable, name = "receipt_token_account", desc = "Owner's associated token account for the receipt mint")]
+    #[account(4, name = "receipt_token_program", desc = "Token-2022 program")]
+    #[account(5, name = "associated_token_program", desc = "Associated Token program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    TokenizeDepositReceipt,
+
+    ///   Claim all of the "pool" tokens held for a tokenized DepositReceipt by burning its receipt
+    ///   token. Fees are charged and accrue like those of ClaimPoolTokens, the receipt mint is
+    ///   closed to the holder, and the DepositReceipt is closed to its rent payer. Unless the current
+    ///   ReceiptTransferPolicy is `Allow`, claims are rejected until the cool down ends.
+    ///
+    ///   0. `[w]` DepositReceipt PDA
+    ///   1. `[w,s]` holder of the receipt token
+    ///   2. `[w]` holder's token account for the receipt mint
+    ///   3. `[w]` Receipt mint
+    ///   4. `[w]` vault token account to send tokens from
+    ///   5. `[w]` destination token account
+    ///   6. `[w]` StakePoolDepositStakeAuthority PDA
+    ///   7. `[]` Pool token mint
+    ///   8. `[]` Token program id of the pool token mint
+    ///   9. `[]` Token-2022 program id
+    ///   10. `[]` System program id
+    ///   11. `[w]` Rent payer of the DepositReceipt
+    ///   12.. `[]` Extra accounts required by the pool mint's transfer hook, if any
+    #[account(0, writable, name = "deposit_receipt", desc = "PDA storing deposit receipt")]
+    #[account(1, writable, signer, name = "holder", desc = "Holder of the receipt token")]
+    #[account(2, writable, name = "receipt_token_account", desc = "Holder's token account for the receipt mint")]
+    #[account(3, writable, name = "receipt_mint", desc = "Receipt mint")]
+    #[account(4, writable, name = "vault", desc = "Vault token account")]
+    #[account(5, writable, name = "destination", desc = "Destination token account")]
+    #[account(6, writable, name = "deposit_authority", desc = "Deposit authority PDA")]
+    #[account(7, name = "pool_mint", desc = "Pool token mint")]
+    #[account(8, name = "token_program", desc = "Token program")]
+    #[account(9, name = "receipt_token_program", desc = "Token-2022 program")]
+    #[account(10, name = "system_program", desc = "System program")]
+    #[account(11, writable, name = "rent_payer", desc = "Rent payer of the receipt, refunded when it is closed")]
+    ClaimTokenizedDepositReceipt,
+
+    ///   Permissionless. Upgrade a StakePoolDepositStakeAuthority or DepositReceipt to the current
+    ///   layout version. Accounts with an older, shorter layout are reallocated, with the payer
+    ///   funding the additional rent, and the new fields start zeroed.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority or DepositReceipt to migrate
+    ///   1. `[w,s]` Payer of any additional rent
+    ///   2. `[]` System program id
+    #[account(0, writable, name = "account", desc = "StakePoolDepositStakeAuthority or DepositReceipt to migrate")]
+    #[account(1, writable, signer, name = "payer", desc = "Payer of any additional rent")]
+    #[account(2, name = "system_program", desc = "System program")]
+    MigrateAccount,
+
+    ///   StakePool manager only. Record a StakePoolDepositStakeAuthority in the StakePool's
+    ///   StakePoolRegistry, creating the registry if it does not exist. The registry's active
+    ///   StakePoolDepositStakeAuthority is updated from the StakePool's `stake_deposit_authority`,
+    ///   so registering an already registered account only refreshes it.
+    ///
+    ///   0. `[w,s]` Payer of the StakePoolRegistry's rent
+    ///   1. `[w]` StakePoolRegistry PDA
+    ///   2. `[]` StakePoolDepositStakeAuthority
+    ///   3. `[]` StakePool
+    ///   4. `[s]` StakePool manager
+    ///   5. `[]` System program id
+    #[account(0, writable, signer, name = "payer", desc = "Payer of the StakePoolRegistry's rent")]
+    #[account(1, writable, name = "stake_pool_registry", desc = "PDA indexing the StakePool's StakePoolDepositStakeAuthority accounts")]
+    #[account(2, name = "deposit_stake_authority", desc = "StakePoolDepositStakeAuthority to register")]
+    #[account(3, name = "stake_pool", desc = "StakePool the StakePoolDepositStakeAuthority was created for")]
+    #[account(4, signer, name = "manager", desc = "StakePool manager")]
+    #[account(5, name = "system_program", desc = "System program")]
+    RegisterStakePoolDepositStakeAuthority,
+
+    ///   StakePool manager only. Remove a StakePoolDepositStakeAuthority from the StakePool's
+    ///   StakePoolRegistry. The StakePool's current `stake_deposit_authority` cannot be removed.
+    ///   The StakePoolDepositStakeAuthority may already be closed.
+    ///
+    ///   0. `[w]` StakePoolRegistry PDA
+    ///   1. `[]` StakePoolDepositStakeAuthority
+    ///   2. `[]` StakePool
+    ///   3. `[s]` StakePool manager
+    #[account(0, writable, name = "stake_pool_registry", desc = "PDA indexing the StakePool's StakePoolDepositStakeAuthority accounts")]
+    #[account(1, name = "deposit_stake_authority", desc = "StakePoolDepositStakeAuthority to deregister")]
+    #[account(2, name = "stake_pool", desc = "StakePool the StakePoolRegistry belongs to")]
+    #[account(3, signer, name = "manager", desc = "StakePool manager")]
+    DeregisterStakePoolDepositStakeAuthority,
+
+    ///   Deposit some stake into the pool like DepositStake, and mint the new DepositReceipt's
+    ///   1-of-1 receipt token to the owner's associated token account like TokenizeDepositReceipt.
+    ///   The payer funds the receipt mint and token account. Only allowed when the
+    ///   StakePoolDepositStakeAuthority's `ReceiptTransferPolicy` is `Allow`.
+    ///
+    ///   0. `[w,s]` payer of the new account rent
+    ///   1. `[]` stake pool program id
+    ///   2. `[w]` DepositReceipt to be created
+    ///   3. `[w]` Stake pool
+    ///   4. `[w]` Validator stake list storage account
+    ///   5. `[w]` Stake pool deposit authority (aka the StakePoolDepositStakeAuthority PDA)
+    ///   6. `[s]` Base for PDA seed
+    ///   7. `[]` Stake pool withdraw authority
+    ///   8. `[w]` Stake account to join the pool
+    ///   9. `[w]` Validator stake account for the stake account to be merged with
+    ///   10. `[w]` Reserve stake account, to withdraw rent exempt reserve
+    ///   11. `[w]` Vault account to receive pool tokens
+    ///   12. `[w]` Account to receive pool fee tokens
+    ///   13. `[w]` Account to receive a portion of pool fee tokens as referral fees
+    ///   14. `[w]` Pool token mint account
+    ///   15. '[]' Sysvar clock account
+    ///   16. '[]' Sysvar stake history account
+    ///   17. `[]` Pool token program id
+    ///   18. `[]` Stake program id
+    ///   19. `[]` System program id
+    ///   20. `[w]` Receipt mint PDA to create
+    ///   21. `[w]` Owner's associated token account for the receipt mint
+    ///   22. `[]` DepositReceipt owner. Must also sign when the StakePoolDepositStakeAuthority has
+    ///       `require_owner_signature` set.
+    ///   23. `[]` Token-2022 program id
+    ///   24. `[]` Associated Token program id
+    ///   25. `[]` (Optional) FeeExemption PDA for the owner, only honored when the owner signs
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, name = "stake_pool_program", desc = "Stake pool program id")]
+    #[account(2, writable, name = "deposit_receipt", desc = "PDA to store deposit receipt")]
+    #[account(3, writable, name = "stake_pool", desc = "StakePool to deposit into")]
+    #[account(4, writable, name = "validator_stake_list", desc = "Validator stake list storage account")]
+    #[account(5, writable, name = "deposit_stake_authority", desc = "StakePool stake_deposit_authority")]
+    #[account(6, signer, name = "base", desc = "Base for PDA seed")]
+    #[account(7, name = "stake_pool_withdraw_authority", desc = "Stake pool withdraw authority")]
+    #[account(8, writable, name = "stake", desc = "Stake account to join the pool")]
+    #[account(9, writable, name = "validator_stake_account", desc = "Validator stake account for the stake account to be merged with")]
+    #[account(10, writable, name = "reserve_stake_account", desc = "Reserve stake account, to withdraw rent exempt reserve")]
+    #[account(11, writable, name = "vault", desc = "Vault account to receive pool tokens")]
+    #[account(12, writable, name = "manager_fee_account", desc = "Account to receive pool fee tokens")]
+    #[account(13, writable, name = "referrer_pool_tokens_account", desc = "Account to receive a portion of pool fee tokens as referral fees")]
+    #[account(14, writable, name = "pool_mint", desc = "Pool token mint account")]
+    #[account(15, name = "clock", desc = "Sysvar clock account")]
+    #[account(16, name = "stake_history", desc = "Sysvar stake history account")]
+    #[account(17, name = "token_program", desc = "Pool token program id")]
+    #[account(18, name = "stake_program", desc = "Stake program id")]
+    #[account(19, name = "system_program", desc = "System program id")]
+    #[account(20, writable, name = "receipt_mint", desc = "Receipt mint PDA to create")]
+    #[account(21, writable, name = "receipt_token_account", desc = "Owner's associated token account for the receipt mint")]
+    #[account(22, name = "owner", desc = "DepositReceipt owner")]
+    #[account(23, name = "receipt_token_program", desc = "Token-2022 program")]
+    #[account(24, name = "associated_token_program", desc = "Associated Token program")]
+    #[account(25, optional, name = "fee_exemption", desc = "FeeExemption PDA for the owner")]
+    DepositStakeTokenized(DepositStakeArgs),
+
+    ///   Initializes the StakePoolDepositStakeAuthority for the given StakePool with the given
+    ///   fee curve.
+    ///
+    ///   0. `[w,s]` Payer that will fund the StakePoolDepositStakeAuthority account.
+    ///   1. `[w]` New StakePoolDepositStakeAuthority to create.
+    ///   2. `[w]` New ATA owned by the `StakePoolDepositStakeAuthority` to create.
+    ///   3. `[]` Authority
+    ///   4. `[s]` Base for PDA seed
+    ///   5. `[]` StakePool
+    ///   6. `[]` StakePool's Pool Mint
+    ///   7. `[]` StakePool Program ID
+    ///   8. `[]` Token program
+    ///   9. `[]` Associated Token program
+    ///   10. `[]` System program
+    #[account(0, writable, signer, name = "payer", desc = "Funding account")]
+    #[account(1, writable, name = "deposit_stake_authority")]
+    #[account(2, writable, name = "vault_ata", desc = "New ATA owned by the StakePoolDepositStakeAuthority")]
+    #[account(3, name = "authority", desc = "Authority")]
+    #[account(4, signer, name = "base", desc = "Base for PDA seed")]
+    #[account(5, name = "stake_pool", desc = "StakePool")]
+    #[account(6, name = "stake_pool_mint", desc = "StakePool's Pool Mint")]
+    #[account(7, name = "stake_pool_program", desc = "StakePool Program ID")]
+    #[account(8, name = "token_program", desc = "Token program")]
+    #[account(9, name = "associated_token_program", desc = "Associated Token program")]
+    #[account(10, name = "system_program", desc = "System program")]
+    InitStakePoolDepositStakeAuthorityV2(InitStakePoolDepositStakeAuthorityV2Args),
+
+    ///   Queues changes to the StakePoolDepositStakeAuthority's fee parameters for the given
+    ///   StakePool. The changes are applied with `ApplyPendingParameters` once `effective_at` has
+    ///   passed, and replace any previously queued changes. Deposit limits, the keeper tip, the
+    ///   owner signature requirement, and the receipt transfer policy take effect immediately. The
+    ///   authority itself is changed with `ProposeAuthority` and `AcceptAuthority`.
+    ///
+    ///   0. `[w]` StakePoolDepositStakeAuthority PDA to be updated
+    ///   1. `[s]` Authority
+    #[account(0, writable, name = "deposit_stake_authority", desc = "PDA storing deposit authority data")]
+    #[account(1, signer, name = "authority", desc = "Authority that can update the deposit authority")]
+    UpdateStakePoolDepositStakeAuthorityV2(UpdateStakePoolDepositStakeAuthorityV2Args),
+
+    ///   Permissionless. Update the StakePoolRegistry's active StakePoolDepositStakeAuthority from
+    ///   the StakePool's current `stake_deposit_authority`, which the StakePool manager can change
+    ///   without this program. Default if that authority is not registered.
+    ///
+    ///   0. `[w]` StakePoolRegistry PDA
+    ///   1. `[]` StakePool
+    #[account(0, writable, name = "stake_pool_registry", desc = "PDA indexing the StakePool's StakePoolDepositStakeAuthority accounts")]
+    #[account(1, name = "stake_pool", desc = "StakePool the StakePoolRegistry belongs to")]
+    RefreshStakePoolRegistry,
 }
 
 pub const STAKE_POOL_DEPOSIT_STAKE_AUTHORITY: &[u8] = b"deposit_stake_authority";
 pub const DEPOSIT_RECEIPT: &[u8] = b"deposit_receipt";
+pub const FEE_EXEMPTION: &[u8] = b"fee_exemption";
+pub const RECEIPT_MINT: &[u8] = b"receipt_mint";
+pub const STAKE_POOL_REGISTRY: &[u8] = b"stake_pool_registry";
 
 /// Derive the StakePoolDepositStakeAuthority pubkey for a given program
 pub fn derive_stake_pool_deposit_stake_authority(
@@ -243,6 +968,28 @@ pub fn derive_stake_deposit_receipt(
     )
 }
 
+/// Derive the FeeExemption pubkey for a given program
+pub fn derive_fee_exemption(
+    program_id: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    depositor: &Pubkey
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FEE_EXEMPTION, &deposit_stake_authority.to_bytes(), &depositor.to_bytes()],
+        program_id
+    )
+}
+
+/// Derive the receipt mint pubkey of a tokenized DepositReceipt for a given program
+pub fn derive_receipt_mint(program_id: &Pubkey, deposit_receipt: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_MINT, &deposit_receipt.to_bytes()], program_id)
+}
+
+/// Derive the StakePoolRegistry pubkey for a given program
+pub fn derive_stake_pool_registry(program_id: &Pubkey, stake_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_POOL_REGISTRY, &stake_pool.to_bytes()], program_id)
+}
+
 /// Creates instruction to set up the StakePoolDepositStakeAuthority to be used in the
 pub fn create_init_deposit_stake_authority_instruction(
     program_id: &Pubkey,
@@ -254,6 +1001,7 @@ pub fn create_init_deposit_stake_authority_instruction(
     fee_wallet: &Pubkey,
     cool_down_seconds: u64,
     initial_fee_bps: u32,
+    fee_curve: FeeCurveConfig,
     authority: &Pubkey,
     base: &Pubkey
 ) -> Instruction {
@@ -262,11 +1010,16 @@ pub fn create_init_deposit_stake_authority_instruction(
         stake_pool,
         base
     );
-    let vault_ata = get_associated_token_address(&deposit_stake_authority_pubkey, stake_pool_mint);
-    let args = InitStakePoolDepositStakeAuthorityArgs {
+    let vault_ata = get_associated_token_address_with_program_id(
+        &deposit_stake_authority_pubkey,
+        stake_pool_mint,
+        token_program_id
+    );
+    let args = InitStakePoolDepositStakeAuthorityV2Args {
         fee_wallet: *fee_wallet,
         initial_fee_bps,
         cool_down_seconds,
+        fee_curve,
     };
     let accounts = vec![
         AccountMeta::new(*payer, true),
@@ -285,7 +1038,7 @@ pub fn create_init_deposit_stake_authority_instruction(
         program_id: *program_id,
         accounts,
         data: borsh
-            ::to_vec(&StakeDepositInterceptorInstruction::InitStakePoolDepositStakeAuthority(args))
+            ::to_vec(&StakeDepositInterceptorInstruction::InitStakePoolDepositStakeAuthorityV2(args))
             .unwrap(),
     }
 }
@@ -295,34 +1048,46 @@ pub fn create_update_deposit_stake_authority_instruction(
     stake_pool: &Pubkey,
     authority: &Pubkey,
     base: &Pubkey,
-    new_authority: Option<Pubkey>,
     fee_wallet: Option<Pubkey>,
     cool_down_seconds: Option<u64>,
-    initial_fee_bps: Option<u32>
+    initial_fee_bps: Option<u32>,
+    fee_curve: Option<FeeCurveConfig>,
+    effective_at: Option<i64>,
+    max_deposit_lst_per_receipt: Option<u64>,
+    max_total_outstanding_lst: Option<u64>,
+    min_deposit_lst: Option<u64>,
+    keeper_tip_lamports: Option<u64>,
+    require_owner_signature: Option<bool>,
+    receipt_transfer_policy: Option<ReceiptTransferPolicy>
 ) -> Instruction {
     let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
         program_id,
         stake_pool,
         base
     );
-    let args = UpdateStakePoolDepositStakeAuthorityArgs {
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
         fee_wallet,
         initial_fee_bps,
         cool_down_seconds,
+        fee_curve,
+        effective_at,
+        max_deposit_lst_per_receipt,
+        max_total_outstanding_lst,
+        min_deposit_lst,
+        keeper_tip_lamports,
+        require_owner_signature,
+        receipt_transfer_policy,
     };
-    let mut accounts = vec![
+    let accounts = vec![
         AccountMeta::new(deposit_stake_authority_pubkey, false),
         AccountMeta::new_readonly(*authority, true)
     ];
-    if let Some(new_authority) = new_authority {
-        accounts.push(AccountMeta::new_readonly(new_authority, false));
-    }
     Instruction {
         program_id: *program_id,
         accounts,
         data: borsh
             ::to_vec(
-                &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthority(args)
+                &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args)
             )
             .unwrap(),
     }
@@ -360,8 +1125,8 @@ fn deposit_stake_internal(
         AccountMeta::new(deposit_receipt_pubkey, false),
         AccountMeta::new(*stake_pool, false),
         AccountMeta::new(*validator_list_storage, false),
-        // This is our PDA that will signed the CPI
-        AccountMeta::new_readonly(*stake_pool_deposit_authority, false),
+        // This is our PDA that will signed the CPI. Writable to count the new DepositReceipt.
+        AccountMeta::new(*stake_pool_deposit_authority, false),
         AccountMeta::new_readonly(*base, true)
     ];
     // NOTE: Assumes the withdrawer and staker authorities are the same (i.e. `deposit_stake_withdraw_authority`).
@@ -399,6 +1164,16 @@ fn deposit_stake_internal(
             AccountMeta::new_readonly(*token_program_id, false),
             AccountMeta::new_readonly(stake::program::id(), false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(
+                derive_fee_exemption(
+                    program_id,
+                    stake_pool_deposit_authority,
+                    deposit_stake_withdraw_authority
+                ).0,
+                false
+            ),
+            // The owner already signs the authorize instructions above
+            AccountMeta::new_readonly(*deposit_stake_withdraw_authority, true),
         ]
     );
     instructions.push(
@@ -531,17 +1306,95 @@ pub fn create_deposit_stake_with_slippage_instruction(
     )
 }
 
-/// Creates the Instruction to change the current owner of the DepositReceipt.
-pub fn create_change_deposit_receipt_owner(
+/// Creates instructions required to deposit into a stake pool, given a stake
+/// account owned by the user, and mint the DepositReceipt's receipt token to the user.
+pub fn create_deposit_stake_tokenized_instruction(
     program_id: &Pubkey,
-    deposit_receipt_address: &Pubkey,
-    owner: &Pubkey,
-    new_owner: &Pubkey
-) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new(*deposit_receipt_address, false),
+    payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    deposit_stake_address: &Pubkey,
+    deposit_stake_withdraw_authority: &Pubkey,
+    validator_stake_account: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    deposit_receipt_base: &Pubkey,
+    deposit_authority_base: &Pubkey
+) -> Vec<Instruction> {
+    let mut instructions = create_deposit_stake_instruction(
+        program_id,
+        payer,
+        stake_pool_program_id,
+        stake_pool,
+        validator_list_storage,
+        stake_pool_withdraw_authority,
+        deposit_stake_address,
+        deposit_stake_withdraw_authority,
+        validator_stake_account,
+        reserve_stake_account,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        deposit_receipt_base,
+        deposit_authority_base
+    );
+    let (deposit_receipt_pubkey, _bump_seed) = derive_stake_deposit_receipt(
+        program_id,
+        stake_pool,
+        deposit_receipt_base
+    );
+    let (receipt_mint, _bump_seed) = derive_receipt_mint(program_id, &deposit_receipt_pubkey);
+    let receipt_token_account = get_associated_token_address_with_program_id(
+        deposit_stake_withdraw_authority,
+        &receipt_mint,
+        &spl_token_2022::id()
+    );
+    let deposit_instruction = instructions.last_mut().unwrap();
+    // Receipt token accounts follow the System program, ahead of the optional FeeExemption.
+    // The owner already signs the authorize instructions.
+    deposit_instruction.accounts.splice(
+        20..20,
+        [
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(receipt_token_account, false),
+            AccountMeta::new_readonly(*deposit_stake_withdraw_authority, true),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ]
+    );
+    // The owner is now passed ahead of the FeeExemption
+    deposit_instruction.accounts.pop();
+    deposit_instruction.data = borsh
+        ::to_vec(
+            &StakeDepositInterceptorInstruction::DepositStakeTokenized(DepositStakeArgs {
+                owner: *deposit_stake_withdraw_authority,
+            })
+        )
+        .unwrap();
+    instructions
+}
+
+/// Creates the Instruction to change the current owner of the DepositReceipt.
+pub fn create_change_deposit_receipt_owner(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+    deposit_stake_authority: &Pubkey
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*deposit_receipt_address, false),
         AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new_readonly(*new_owner, false)
+        AccountMeta::new_readonly(*new_owner, false),
+        AccountMeta::new_readonly(*deposit_stake_authority, false)
     ];
     Instruction {
         program_id: *program_id,
@@ -552,34 +1405,983 @@ pub fn create_change_deposit_receipt_owner(
     }
 }
 
-/// Creates a ClaimPoolTokens instruction to transfer the held "pool" tokens to
-/// destination token account. Also closes the DepositReceipt and refunds the owner.
-pub fn create_claim_pool_tokens_instruction(
+fn claim_pool_tokens_internal(
     program_id: &Pubkey,
     deposit_receipt_address: &Pubkey,
     owner: &Pubkey,
     vault_token_account: &Pubkey,
     destination_token_account: &Pubkey,
-    fee_token_account: &Pubkey,
     deposit_stake_authority: &Pubkey,
     pool_mint: &Pubkey,
     token_program: &Pubkey,
-    after_cool_down: bool
+    rent_payer: &Pubkey,
+    after_cool_down: bool,
+    amount: Option<u64>
 ) -> Instruction {
     let accounts = vec![
         AccountMeta::new(*deposit_receipt_address, false),
         AccountMeta::new(*owner, !after_cool_down),
         AccountMeta::new(*vault_token_account, false),
         AccountMeta::new(*destination_token_account, false),
-        AccountMeta::new(*fee_token_account, false),
-        AccountMeta::new_readonly(*deposit_stake_authority, false),
+        AccountMeta::new(*deposit_stake_authority, false),
         AccountMeta::new_readonly(*pool_mint, false),
         AccountMeta::new_readonly(*token_program, false),
-        AccountMeta::new_readonly(system_program::id(), false)
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_payer, false)
+    ];
+    let data = if let Some(amount) = amount {
+        borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::ClaimPoolTokensPartial(
+                    ClaimPoolTokensPartialArgs { amount }
+                )
+            )
+            .unwrap()
+    } else {
+        borsh::to_vec(&StakeDepositInterceptorInstruction::ClaimPoolTokens).unwrap()
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a ClaimPoolTokens instruction to transfer the held "pool" tokens to
+/// destination token account. Also closes the DepositReceipt and refunds its rent payer.
+/// A keeper collects the keeper tip of a permissionless claim by appending itself as a signer
+/// after any transfer hook accounts.
+pub fn create_claim_pool_tokens_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program: &Pubkey,
+    rent_payer: &Pubkey,
+    after_cool_down: bool
+) -> Instruction {
+    claim_pool_tokens_internal(
+        program_id,
+        deposit_receipt_address,
+        owner,
+        vault_token_account,
+        destination_token_account,
+        deposit_stake_authority,
+        pool_mint,
+        token_program,
+        rent_payer,
+        after_cool_down,
+        None
+    )
+}
+
+/// Creates a ClaimPoolTokensPartial instruction to transfer `amount` of the held "pool"
+/// tokens (minus fees) to the destination token account. The DepositReceipt is only
+/// closed once its full `lst_amount` has been claimed.
+pub fn create_claim_pool_tokens_partial_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program: &Pubkey,
+    rent_payer: &Pubkey,
+    after_cool_down: bool,
+    amount: u64
+) -> Instruction {
+    claim_pool_tokens_internal(
+        program_id,
+        deposit_receipt_address,
+        owner,
+        vault_token_account,
+        destination_token_account,
+        deposit_stake_authority,
+        pool_mint,
+        token_program,
+        rent_payer,
+        after_cool_down,
+        Some(amount)
+    )
+}
+
+/// Creates a SplitDepositReceipt instruction to move `lst_amount` from an existing
+/// DepositReceipt to a new DepositReceipt derived from `base`.
+pub fn create_split_deposit_receipt_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    stake_pool: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    base: &Pubkey,
+    lst_amount: u64
+) -> Instruction {
+    let (new_deposit_receipt_address, _bump_seed) = derive_stake_deposit_receipt(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*deposit_receipt_address, false),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(new_deposit_receipt_address, false),
+        AccountMeta::new_readonly(*base, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*deposit_stake_authority, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::SplitDepositReceipt(SplitDepositReceiptArgs {
+                    lst_amount,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a MergeDepositReceipts instruction to fold the `source` DepositReceipt into
+/// the `destination` DepositReceipt and close the `source`.
+pub fn create_merge_deposit_receipts_instruction(
+    program_id: &Pubkey,
+    destination_deposit_receipt_address: &Pubkey,
+    source_deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    source_rent_payer: &Pubkey
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*destination_deposit_receipt_address, false),
+        AccountMeta::new(*source_deposit_receipt_address, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new(*source_rent_payer, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::MergeDepositReceipts).unwrap(),
+    }
+}
+
+fn deposit_sol_internal(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_deposit_authority: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_from: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    base: &Pubkey,
+    lamports: u64,
+    minimum_pool_tokens_out: Option<u64>
+) -> Instruction {
+    let (deposit_receipt_pubkey, _bump_seed) = derive_stake_deposit_receipt(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*stake_pool_program_id, false),
+        AccountMeta::new(deposit_receipt_pubkey, false),
+        AccountMeta::new(*stake_pool, false),
+        // This is our PDA that will signed the CPI. Writable to count the new DepositReceipt.
+        AccountMeta::new(*stake_pool_deposit_authority, false),
+        AccountMeta::new_readonly(*base, true),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*lamports_from, true),
+        AccountMeta::new(*pool_tokens_to, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*referrer_pool_tokens_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(
+            derive_fee_exemption(program_id, stake_pool_deposit_authority, lamports_from).0,
+            false
+        )
     ];
+    let data = if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+        let args = DepositSolWithSlippageArgs {
+            owner: *lamports_from,
+            lamports,
+            minimum_pool_tokens_out,
+        };
+        borsh::to_vec(&StakeDepositInterceptorInstruction::DepositSolWithSlippage(args)).unwrap()
+    } else {
+        let args = DepositSolArgs {
+            owner: *lamports_from,
+            lamports,
+        };
+        borsh::to_vec(&StakeDepositInterceptorInstruction::DepositSol(args)).unwrap()
+    };
     Instruction {
         program_id: *program_id,
         accounts,
-        data: borsh::to_vec(&StakeDepositInterceptorInstruction::ClaimPoolTokens).unwrap(),
+        data,
+    }
+}
+
+/// Creates the instruction required to deposit SOL into a stake pool. The
+/// DepositReceipt is owned by `lamports_from`.
+pub fn create_deposit_sol_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_from: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    deposit_receipt_base: &Pubkey,
+    deposit_authority_base: &Pubkey,
+    lamports: u64
+) -> Instruction {
+    // The StakePool's sol deposit authority is assumed to be the PDA owned by
+    // the stake-deposit-interceptor program
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        deposit_authority_base
+    );
+    deposit_sol_internal(
+        program_id,
+        payer,
+        stake_pool_program_id,
+        stake_pool,
+        &deposit_stake_authority_pubkey,
+        stake_pool_withdraw_authority,
+        reserve_stake_account,
+        lamports_from,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        deposit_receipt_base,
+        lamports,
+        None
+    )
+}
+
+/// Creates the instruction required to deposit SOL into a stake pool. StakePool
+/// program verifies the minimum tokens are minted.
+pub fn create_deposit_sol_with_slippage_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_from: &Pubkey,
+    pool_tokens_to: &Pubkey,
+    manager_fee_account: &Pubkey,
+    referrer_pool_tokens_account: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    deposit_receipt_base: &Pubkey,
+    deposit_authority_base: &Pubkey,
+    lamports: u64,
+    minimum_pool_tokens_out: u64
+) -> Instruction {
+    // The StakePool's sol deposit authority is assumed to be the PDA owned by
+    // the stake-deposit-interceptor program
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        deposit_authority_base
+    );
+    deposit_sol_internal(
+        program_id,
+        payer,
+        stake_pool_program_id,
+        stake_pool,
+        &deposit_stake_authority_pubkey,
+        stake_pool_withdraw_authority,
+        reserve_stake_account,
+        lamports_from,
+        pool_tokens_to,
+        manager_fee_account,
+        referrer_pool_tokens_account,
+        pool_mint,
+        token_program_id,
+        deposit_receipt_base,
+        lamports,
+        Some(minimum_pool_tokens_out)
+    )
+}
+
+/// Creates an AddFeeExemption instruction to grant `depositor` a reduced initial fee rate
+/// on the given StakePoolDepositStakeAuthority.
+pub fn create_add_fee_exemption_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    initial_fee_bps: u32
+) -> Instruction {
+    let (fee_exemption, _bump_seed) = derive_fee_exemption(
+        program_id,
+        deposit_stake_authority,
+        depositor
+    );
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*depositor, false),
+        AccountMeta::new(fee_exemption, false),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::AddFeeExemption(AddFeeExemptionArgs {
+                    initial_fee_bps,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a RemoveFeeExemption instruction to revoke the FeeExemption of `depositor`
+/// and refund its lamports to `destination`.
+pub fn create_remove_fee_exemption_instruction(
+    program_id: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    destination: &Pubkey
+) -> Instruction {
+    let (fee_exemption, _bump_seed) = derive_fee_exemption(
+        program_id,
+        deposit_stake_authority,
+        depositor
+    );
+    let accounts = vec![
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(fee_exemption, false),
+        AccountMeta::new(*destination, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::RemoveFeeExemption).unwrap(),
+    }
+}
+
+/// Creates a ProposeAuthority instruction to start transferring the StakePoolDepositStakeAuthority
+/// to `new_authority`.
+pub fn create_propose_authority_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey,
+    new_authority: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*new_authority, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::ProposeAuthority).unwrap(),
+    }
+}
+
+/// Creates an AcceptAuthority instruction for the `pending_authority` to take over the
+/// StakePoolDepositStakeAuthority.
+pub fn create_accept_authority_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    base: &Pubkey,
+    pending_authority: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*pending_authority, true)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::AcceptAuthority).unwrap(),
+    }
+}
+
+/// Creates a CancelAuthorityTransfer instruction to clear a pending authority transfer.
+pub fn create_cancel_authority_transfer_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::CancelAuthorityTransfer)
+            .unwrap(),
+    }
+}
+
+/// Creates an ApplyPendingParameters instruction to put the StakePoolDepositStakeAuthority's
+/// queued fee parameters into effect.
+pub fn create_apply_pending_parameters_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    base: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(deposit_stake_authority_pubkey, false)],
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::ApplyPendingParameters)
+            .unwrap(),
+    }
+}
+
+/// Creates a SetFeeRecipients instruction to share fees between the `fee_wallet` and up to
+/// `MAX_FEE_RECIPIENTS` other wallets.
+pub fn create_set_fee_recipients_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey,
+    fee_recipients: Vec<FeeRecipient>
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::SetFeeRecipients(SetFeeRecipientsArgs {
+                    fee_recipients,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a SweepFees instruction to send the accrued fees held by the vault to the
+/// `fee_wallet`'s and each fee recipient's token account.
+pub fn create_sweep_fees_instruction(
+    program_id: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    vault_token_account: &Pubkey,
+    fee_token_account: &Pubkey,
+    fee_recipient_token_accounts: &[Pubkey],
+    pool_mint: &Pubkey,
+    token_program: &Pubkey
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new(*vault_token_account, false),
+        AccountMeta::new(*fee_token_account, false),
+        AccountMeta::new_readonly(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program, false)
+    ];
+    accounts.extend(
+        fee_recipient_token_accounts
+            .iter()
+            .map(|token_account| AccountMeta::new(*token_account, false))
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::SweepFees).unwrap(),
+    }
+}
+
+/// Creates a CloseStakePoolDepositStakeAuthority instruction to close the
+/// StakePoolDepositStakeAuthority and its vault once nothing is owed to depositors.
+pub fn create_close_stake_pool_deposit_stake_authority_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    pool_mint: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let vault_ata = get_associated_token_address_with_program_id(
+        &deposit_stake_authority_pubkey,
+        pool_mint,
+        token_program
+    );
+    let (stake_pool_registry_pubkey, _bump_seed) = derive_stake_pool_registry(
+        program_id,
+        stake_pool
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(vault_ata, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(stake_pool_registry_pubkey, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::CloseStakePoolDepositStakeAuthority)
+            .unwrap(),
+    }
+}
+
+/// Creates a RecoverExcessVaultTokens instruction to transfer the vault's pool tokens that are
+/// not owed to depositors or fee recipients to the destination token account.
+pub fn create_recover_excess_vault_tokens_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    pool_mint: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey,
+    destination_token_account: &Pubkey,
+    token_program: &Pubkey
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let vault_ata = get_associated_token_address_with_program_id(
+        &deposit_stake_authority_pubkey,
+        pool_mint,
+        token_program
+    );
+    let accounts = vec![
+        AccountMeta::new_readonly(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(vault_ata, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::RecoverExcessVaultTokens)
+            .unwrap(),
+    }
+}
+
+/// Creates a SetPauseState instruction to pause or unpause deposits and claims during the
+/// cool down.
+pub fn create_set_pause_state_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    authority: &Pubkey,
+    base: &Pubkey,
+    deposits_paused: bool,
+    early_claims_paused: bool
+) -> Instruction {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        program_id,
+        stake_pool,
+        base
+    );
+    let accounts = vec![
+        AccountMeta::new(deposit_stake_authority_pubkey, false),
+        AccountMeta::new_readonly(*authority, true)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::SetPauseState(SetPauseStateArgs {
+                    deposits_paused,
+                    early_claims_paused,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a ClaimAndWithdrawStake instruction to claim a DepositReceipt and withdraw the "pool"
+/// tokens left after fees from the StakePool into `stake_to_receive`, authorized to the owner.
+pub fn create_claim_and_withdraw_stake_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program: &Pubkey,
+    rent_payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    validator_list_storage: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    stake_to_split: &Pubkey,
+    stake_to_receive: &Pubkey,
+    manager_fee_account: &Pubkey,
+    minimum_lamports_out: Option<u64>
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*deposit_receipt_address, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*vault_token_account, false),
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_payer, false),
+        AccountMeta::new_readonly(*stake_pool_program_id, false),
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new(*validator_list_storage, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*stake_to_split, false),
+        AccountMeta::new(*stake_to_receive, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(stake::program::id(), false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::ClaimAndWithdrawStake(ClaimAndWithdrawArgs {
+                    minimum_lamports_out,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a ClaimAndWithdrawSol instruction to claim a DepositReceipt and withdraw the "pool"
+/// tokens left after fees from the StakePool's reserve as SOL sent to the owner.
+pub fn create_claim_and_withdraw_sol_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    vault_token_account: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program: &Pubkey,
+    rent_payer: &Pubkey,
+    stake_pool_program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    manager_fee_account: &Pubkey,
+    sol_withdraw_authority: Option<&Pubkey>,
+    minimum_lamports_out: Option<u64>
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*deposit_receipt_address, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*vault_token_account, false),
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_payer, false),
+        AccountMeta::new_readonly(*stake_pool_program_id, false),
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(stake::program::id(), false)
+    ];
+    if let Some(sol_withdraw_authority) = sol_withdraw_authority {
+        accounts.push(AccountMeta::new_readonly(*sol_withdraw_authority, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(
+                &StakeDepositInterceptorInstruction::ClaimAndWithdrawSol(ClaimAndWithdrawArgs {
+                    minimum_lamports_out,
+                })
+            )
+            .unwrap(),
+    }
+}
+
+/// Creates a TokenizeDepositReceipt instruction to mint the DepositReceipt's 1-of-1 receipt token
+/// to the owner's associated token account.
+pub fn create_tokenize_deposit_receipt_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    owner: &Pubkey,
+    deposit_stake_authority: &Pubkey
+) -> Instruction {
+    let (receipt_mint, _bump_seed) = derive_receipt_mint(program_id, deposit_receipt_address);
+    let receipt_token_account = get_associated_token_address_with_program_id(
+        owner,
+        &receipt_mint,
+        &spl_token_2022::id()
+    );
+    let accounts = vec![
+        AccountMeta::new(*deposit_receipt_address, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(receipt_mint, false),
+        AccountMeta::new(receipt_token_account, false),
+        AccountMeta::new_readonly(spl_token_2022::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*deposit_stake_authority, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::TokenizeDepositReceipt).unwrap(),
+    }
+}
+
+/// Creates a ClaimTokenizedDepositReceipt instruction to burn the holder's receipt token and
+/// transfer the held "pool" tokens (minus fees) to the destination token account.
+pub fn create_claim_tokenized_deposit_receipt_instruction(
+    program_id: &Pubkey,
+    deposit_receipt_address: &Pubkey,
+    holder: &Pubkey,
+    vault_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    pool_mint: &Pubkey,
+    token_program: &Pubkey,
+    rent_payer: &Pubkey
+) -> Instruction {
+    let (receipt_mint, _bump_seed) = derive_receipt_mint(program_id, deposit_receipt_address);
+    let receipt_token_account = get_associated_token_address_with_program_id(
+        holder,
+        &receipt_mint,
+        &spl_token_2022::id()
+    );
+    let accounts = vec![
+        AccountMeta::new(*deposit_receipt_address, false),
+        AccountMeta::new(*holder, true),
+        AccountMeta::new(receipt_token_account, false),
+        AccountMeta::new(receipt_mint, false),
+        AccountMeta::new(*vault_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new(*deposit_stake_authority, false),
+        AccountMeta::new_readonly(*pool_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(spl_token_2022::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_payer, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::ClaimTokenizedDepositReceipt)
+            .unwrap(),
+    }
+}
+
+/// Creates a MigrateAccount instruction to upgrade a StakePoolDepositStakeAuthority or
+/// DepositReceipt to the current layout version.
+pub fn create_migrate_account_instruction(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    payer: &Pubkey
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*account, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::MigrateAccount)
+            .unwrap(),
+    }
+}
+
+/// Creates a RegisterStakePoolDepositStakeAuthority instruction to record a
+/// StakePoolDepositStakeAuthority in the StakePool's StakePoolRegistry.
+pub fn create_register_stake_pool_deposit_stake_authority_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    stake_pool: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    manager: &Pubkey
+) -> Instruction {
+    let (stake_pool_registry, _bump_seed) = derive_stake_pool_registry(program_id, stake_pool);
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(stake_pool_registry, false),
+        AccountMeta::new_readonly(*deposit_stake_authority, false),
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::RegisterStakePoolDepositStakeAuthority)
+            .unwrap(),
+    }
+}
+
+/// Creates a DeregisterStakePoolDepositStakeAuthority instruction to remove a
+/// StakePoolDepositStakeAuthority from the StakePool's StakePoolRegistry.
+pub fn create_deregister_stake_pool_deposit_stake_authority_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    manager: &Pubkey
+) -> Instruction {
+    let (stake_pool_registry, _bump_seed) = derive_stake_pool_registry(program_id, stake_pool);
+    let accounts = vec![
+        AccountMeta::new(stake_pool_registry, false),
+        AccountMeta::new_readonly(*deposit_stake_authority, false),
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh
+            ::to_vec(&StakeDepositInterceptorInstruction::DeregisterStakePoolDepositStakeAuthority)
+            .unwrap(),
+    }
+}
+
+/// Creates a RefreshStakePoolRegistry instruction to re-read the StakePool's
+/// `stake_deposit_authority` into its StakePoolRegistry.
+pub fn create_refresh_stake_pool_registry_instruction(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey
+) -> Instruction {
+    let (stake_pool_registry, _bump_seed) = derive_stake_pool_registry(program_id, stake_pool);
+    let accounts = vec![
+        AccountMeta::new(stake_pool_registry, false),
+        AccountMeta::new_readonly(*stake_pool, false)
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakeDepositInterceptorInstruction::RefreshStakePoolRegistry).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_init_args_round_trip() {
+        // InitStakePoolDepositStakeAuthority is the 1st variant with a pubkey, a u64 and a u32
+        let fee_wallet = Pubkey::new_unique();
+        let mut data = vec![0];
+        data.extend_from_slice(&fee_wallet.to_bytes());
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&20u32.to_le_bytes());
+
+        let instruction = StakeDepositInterceptorInstruction::try_from_slice(&data).unwrap();
+        assert_eq!(
+            instruction,
+            StakeDepositInterceptorInstruction::InitStakePoolDepositStakeAuthority(
+                InitStakePoolDepositStakeAuthorityArgs {
+                    fee_wallet,
+                    cool_down_seconds: 100,
+                    initial_fee_bps: 20,
+                }
+            )
+        );
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), data);
+    }
+
+    #[test]
+    fn test_released_update_args_round_trip() {
+        // UpdateStakePoolDepositStakeAuthority is the 2nd variant with an optional pubkey, u64
+        // and u32
+        let fee_wallet = Pubkey::new_unique();
+        let mut data = vec![1, 1];
+        data.extend_from_slice(&fee_wallet.to_bytes());
+        data.push(0);
+        data.push(1);
+        data.extend_from_slice(&20u32.to_le_bytes());
+
+        let instruction = StakeDepositInterceptorInstruction::try_from_slice(&data).unwrap();
+        assert_eq!(
+            instruction,
+            StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthority(
+                UpdateStakePoolDepositStakeAuthorityArgs {
+                    fee_wallet: Some(fee_wallet),
+                    cool_down_seconds: None,
+                    initial_fee_bps: Some(20),
+                }
+            )
+        );
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), data);
     }
 }
@@ -1,6 +1,7 @@
 use solana_program::declare_id;
 
 pub mod error;
+pub mod events;
 pub mod instruction;
 pub mod processor;
 pub mod macros;
@@ -20,4 +20,27 @@ macro_rules! deposit_receipt_signer_seeds {
             &[$deposit_receipt.bump_seed],
         ]
     };
-}
\ No newline at end of file
+}
+
+#[macro_export]
+macro_rules! fee_exemption_signer_seeds {
+    ($fee_exemption:expr) => {
+        &[
+            FEE_EXEMPTION,
+            $fee_exemption.stake_pool_deposit_stake_authority.as_ref(),
+            $fee_exemption.depositor.as_ref(),
+            &[$fee_exemption.bump_seed],
+        ]
+    };
+}
+
+#[macro_export]
+macro_rules! stake_pool_registry_signer_seeds {
+    ($stake_pool_registry:expr) => {
+        &[
+            STAKE_POOL_REGISTRY,
+            $stake_pool_registry.stake_pool.as_ref(),
+            &[$stake_pool_registry.bump_seed],
+        ]
+    };
+}
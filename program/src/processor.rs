@@ -1,4 +1,7 @@
-use std::mem;
+use std::{
+    cell::{Ref, RefMut},
+    mem,
+};
 
 use borsh::BorshDeserialize;
 use jito_bytemuck::{AccountDeserialize, Discriminator};
@@ -11,25 +14,40 @@ use solana_program::{
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction, system_program,
     sysvar::Sysvar,
 };
-use spl_associated_token_account::get_associated_token_address;
-use spl_token_2022::state::{Account, Mint};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_pod::primitives::{PodU32, PodU64};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
 
 use crate::{
     deposit_receipt_signer_seeds, deposit_stake_authority_signer_seeds,
     error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    fee_exemption_signer_seeds,
     instruction::{
-        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority, DepositStakeArgs,
-        InitStakePoolDepositStakeAuthorityArgs, StakeDepositInterceptorInstruction,
-        UpdateStakePoolDepositStakeAuthorityArgs, DEPOSIT_RECEIPT,
-        STAKE_POOL_DEPOSIT_STAKE_AUTHORITY,
+        derive_fee_exemption, derive_receipt_mint, derive_stake_deposit_receipt,
+        derive_stake_pool_deposit_stake_authority, derive_stake_pool_registry, AddFeeExemptionArgs,
+        ClaimAndWithdrawArgs, DepositSolArgs, DepositStakeArgs,
+        InitStakePoolDepositStakeAuthorityV2Args, SetFeeRecipientsArgs, SetPauseStateArgs,
+        SplitDepositReceiptArgs, StakeDepositInterceptorInstruction,
+        UpdateStakePoolDepositStakeAuthorityV2Args, DEPOSIT_RECEIPT, FEE_EXEMPTION, RECEIPT_MINT,
+        STAKE_POOL_DEPOSIT_STAKE_AUTHORITY, STAKE_POOL_REGISTRY,
+    },
+    stake_pool_registry_signer_seeds,
+    state::{
+        DepositReceipt, FeeCurve, FeeCurveType, FeeExemption, ReceiptTransferPolicy,
+        StakePoolDepositStakeAuthority, StakePoolRegistry,
     },
-    state::{DepositReceipt, StakePoolDepositStakeAuthority},
 };
 
 pub struct Processor;
@@ -40,7 +58,7 @@ impl Processor {
     pub fn process_init_stake_pool_deposit_stake_authority(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        init_deposit_stake_authority_args: InitStakePoolDepositStakeAuthorityArgs,
+        init_deposit_stake_authority_args: InitStakePoolDepositStakeAuthorityV2Args,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let payer_info = next_account_info(account_info_iter)?;
@@ -75,6 +93,9 @@ impl Processor {
             return Err(StakeDepositInterceptorError::InitialFeeRateMaxExceeded.into());
         }
 
+        // Validate: FeeCurve parameters are well formed
+        let fee_curve = FeeCurve::from_config(&init_deposit_stake_authority_args.fee_curve)?;
+
         // Validate: StakePool must be owned by the correct program
         if stake_pool_info.owner != stake_pool_program_info.key {
             return Err(StakeDepositInterceptorError::InvalidStakePool.into());
@@ -117,15 +138,18 @@ impl Processor {
         create_pda_account(
             payer_info,
             &rent,
-            8 + mem::size_of::<StakePoolDepositStakeAuthority>(),
+            StakePoolDepositStakeAuthority::LEN,
             program_id,
             system_program_info,
             deposit_stake_authority_info,
             &pda_seeds,
         )?;
 
-        let vault_ata =
-            get_associated_token_address(&deposit_stake_authority_pda, &stake_pool.pool_mint);
+        let vault_ata = get_associated_token_address_with_program_id(
+            &deposit_stake_authority_pda,
+            &stake_pool.pool_mint,
+            token_program_info.key,
+        );
 
         // Validate: Vault must be the ATA for the StakePoolDepositStakeAuthority PDA
         if vault_ata != *vault_ata_info.key {
@@ -154,7 +178,7 @@ impl Processor {
         let mut deposit_stake_authority_data =
             deposit_stake_authority_info.try_borrow_mut_data()?;
         deposit_stake_authority_data[0] = StakePoolDepositStakeAuthority::DISCRIMINATOR;
-        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
             &mut deposit_stake_authority_data,
         )
         .unwrap();
@@ -171,25 +195,38 @@ impl Processor {
             init_deposit_stake_authority_args.cool_down_seconds.into();
         deposit_stake_authority.inital_fee_bps =
             init_deposit_stake_authority_args.initial_fee_bps.into();
+        deposit_stake_authority.fee_curve = fee_curve;
         deposit_stake_authority.bump_seed = bump_seed;
+        deposit_stake_authority.outstanding_lst_tracked = 1;
+        deposit_stake_authority.version = StakePoolDepositStakeAuthority::CURRENT_VERSION;
+
+        StakeDepositInterceptorEvent::InitStakePoolDepositStakeAuthority {
+            deposit_stake_authority: deposit_stake_authority_pda,
+            stake_pool: deposit_stake_authority.stake_pool,
+            authority: deposit_stake_authority.authority,
+            fee_wallet: deposit_stake_authority.fee_wallet,
+            cool_down_seconds: deposit_stake_authority.cool_down_seconds.into(),
+            initial_fee_bps: deposit_stake_authority.inital_fee_bps.into(),
+            fee_curve: deposit_stake_authority.fee_curve.config(),
+        }
+        .emit();
 
         Ok(())
     }
 
-    /// Update `StakePoolDepositStakeAuthority` authority, fee_wallet, cool_down_seconds, and/or initial_fee_bps.
-    /// ONLY accessible by the currnet authority.
+    /// Queue changes to `StakePoolDepositStakeAuthority` fee_wallet, cool_down_seconds, initial_fee_bps,
+    /// and/or fee_curve. Deposit limits and the keeper tip are updated immediately. ONLY accessible by the current authority.
     pub fn process_update_deposit_stake_authority(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        update_deposit_stake_authority_args: UpdateStakePoolDepositStakeAuthorityArgs,
+        update_deposit_stake_authority_args: UpdateStakePoolDepositStakeAuthorityV2Args,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let deposit_stake_authority_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
-        let new_authority_info = next_account_info(account_info_iter).ok();
 
-        // Validate: program owns `StakePoolDepositStakeAuthority`
-        check_account_owner(deposit_stake_authority_info, program_id)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
 
         // Validate: deposit_stake_authority must be writable
         if !deposit_stake_authority_info.is_writable {
@@ -203,7 +240,7 @@ impl Processor {
 
         let mut deposit_stake_authority_data =
             deposit_stake_authority_info.try_borrow_mut_data()?;
-        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
             &mut deposit_stake_authority_data,
         )
         .unwrap();
@@ -219,34 +256,231 @@ impl Processor {
             return Err(StakeDepositInterceptorError::InvalidAuthority.into());
         }
 
-        if let Some(new_authority) = new_authority_info {
-            deposit_stake_authority.authority = *new_authority.key;
+        // Fee parameters are queued behind the update delay, everything else applies immediately
+        let has_fee_changes = update_deposit_stake_authority_args.fee_wallet.is_some()
+            || update_deposit_stake_authority_args
+                .cool_down_seconds
+                .is_some()
+            || update_deposit_stake_authority_args
+                .initial_fee_bps
+                .is_some()
+            || update_deposit_stake_authority_args.fee_curve.is_some();
+
+        // Deposit limits do not change the fees owed by depositors and take effect immediately
+        if update_deposit_stake_authority_args
+            .max_deposit_lst_per_receipt
+            .is_some()
+            || update_deposit_stake_authority_args
+                .max_total_outstanding_lst
+                .is_some()
+        {
+            if let Some(max_deposit_lst_per_receipt) =
+                update_deposit_stake_authority_args.max_deposit_lst_per_receipt
+            {
+                deposit_stake_authority.max_deposit_lst_per_receipt =
+                    max_deposit_lst_per_receipt.into();
+            }
+            if let Some(max_total_outstanding_lst) =
+                update_deposit_stake_authority_args.max_total_outstanding_lst
+            {
+                deposit_stake_authority.max_total_outstanding_lst =
+                    max_total_outstanding_lst.into();
+            }
+
+            StakeDepositInterceptorEvent::SetDepositCaps {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                max_deposit_lst_per_receipt: deposit_stake_authority
+                    .max_deposit_lst_per_receipt
+                    .into(),
+                max_total_outstanding_lst: deposit_stake_authority.max_total_outstanding_lst.into(),
+            }
+            .emit();
+        }
+        if let Some(min_deposit_lst) = update_deposit_stake_authority_args.min_deposit_lst {
+            deposit_stake_authority.min_deposit_lst = min_deposit_lst.into();
+
+            StakeDepositInterceptorEvent::SetMinDepositLst {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                min_deposit_lst,
+            }
+            .emit();
+        }
+
+        // The keeper tip is paid out of DepositReceipt rent rather than pool tokens, so it does
+        // not change the fees owed by depositors either
+        if let Some(keeper_tip_lamports) = update_deposit_stake_authority_args.keeper_tip_lamports {
+            // Validate: the keeper tip cannot exceed its share of a new DepositReceipt's rent
+            let max_keeper_tip_lamports = StakePoolDepositStakeAuthority::max_keeper_tip(
+                Rent::get()?.minimum_balance(DepositReceipt::LEN),
+            );
+            if keeper_tip_lamports > max_keeper_tip_lamports {
+                return Err(StakeDepositInterceptorError::InvalidKeeperTip.into());
+            }
+            deposit_stake_authority.keeper_tip_lamports = keeper_tip_lamports.into();
+
+            StakeDepositInterceptorEvent::SetKeeperTip {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                keeper_tip_lamports,
+            }
+            .emit();
+        }
+
+        // Requiring the owner's signature only restricts who can create DepositReceipts
+        if let Some(require_owner_signature) =
+            update_deposit_stake_authority_args.require_owner_signature
+        {
+            deposit_stake_authority.require_owner_signature = require_owner_signature.into();
+
+            StakeDepositInterceptorEvent::SetRequireOwnerSignature {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                require_owner_signature,
+            }
+            .emit();
+        }
+
+        // The transfer policy only restricts changes of DepositReceipt owners
+        if let Some(receipt_transfer_policy) =
+            update_deposit_stake_authority_args.receipt_transfer_policy
+        {
+            deposit_stake_authority.receipt_transfer_policy = receipt_transfer_policy as u8;
+
+            StakeDepositInterceptorEvent::SetReceiptTransferPolicy {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                receipt_transfer_policy,
+            }
+            .emit();
+        }
+
+        // Leave any queued fee parameters untouched when no fee parameter changes
+        if !has_fee_changes {
+            return Ok(());
+        }
+
+        let clock = Clock::get()?;
+        let min_effective_at = clock
+            .unix_timestamp
+            .checked_add(StakePoolDepositStakeAuthority::MIN_PARAMETER_UPDATE_DELAY_SECONDS)
+            .expect("overflow");
+        let effective_at = update_deposit_stake_authority_args
+            .effective_at
+            .unwrap_or(min_effective_at);
+        // Validate: changes cannot take effect before the minimum delay
+        if effective_at < min_effective_at {
+            return Err(StakeDepositInterceptorError::ParameterUpdateDelayTooShort.into());
         }
 
+        // Queue the current values with the requested changes applied on top
+        let mut pending_cool_down_seconds = deposit_stake_authority.cool_down_seconds;
+        let mut pending_initial_fee_bps = deposit_stake_authority.inital_fee_bps;
+        let mut pending_fee_wallet = deposit_stake_authority.fee_wallet;
+        let mut pending_fee_curve = deposit_stake_authority.fee_curve;
+
         if let Some(cool_down_seconds) = update_deposit_stake_authority_args.cool_down_seconds {
-            deposit_stake_authority.cool_down_seconds = cool_down_seconds.into();
+            pending_cool_down_seconds = cool_down_seconds.into();
         }
         if let Some(initial_fee_bps) = update_deposit_stake_authority_args.initial_fee_bps {
             // Validate: `initial_fee_bps` cannot exceed 100%
             if initial_fee_bps.gt(&DepositReceipt::FEE_BPS_DENOMINATOR) {
                 return Err(StakeDepositInterceptorError::InitialFeeRateMaxExceeded.into());
             }
-            deposit_stake_authority.inital_fee_bps = initial_fee_bps.into();
+            pending_initial_fee_bps = initial_fee_bps.into();
         }
         if let Some(fee_wallet) = update_deposit_stake_authority_args.fee_wallet {
-            deposit_stake_authority.fee_wallet = fee_wallet;
+            pending_fee_wallet = fee_wallet;
+        }
+        if let Some(fee_curve) = update_deposit_stake_authority_args.fee_curve {
+            // Validate: FeeCurve parameters are well formed
+            pending_fee_curve = FeeCurve::from_config(&fee_curve)?;
+        }
+
+        deposit_stake_authority.pending_parameters_effective_at =
+            effective_at.unsigned_abs().into();
+        deposit_stake_authority.pending_cool_down_seconds = pending_cool_down_seconds;
+        deposit_stake_authority.pending_initial_fee_bps = pending_initial_fee_bps;
+        deposit_stake_authority.pending_fee_wallet = pending_fee_wallet;
+        deposit_stake_authority.pending_fee_curve = pending_fee_curve;
+
+        StakeDepositInterceptorEvent::UpdateStakePoolDepositStakeAuthority {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            fee_wallet: pending_fee_wallet,
+            cool_down_seconds: pending_cool_down_seconds.into(),
+            initial_fee_bps: pending_initial_fee_bps.into(),
+            fee_curve: pending_fee_curve.config(),
+            effective_at,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Apply the `StakePoolDepositStakeAuthority`'s queued fee parameters once their
+    /// `effective_at` has passed. Permissionless.
+    pub fn process_apply_pending_parameters(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: there are queued parameters
+        if !deposit_stake_authority.has_pending_parameters() {
+            return Err(StakeDepositInterceptorError::NoPendingParameters.into());
+        }
+
+        // Validate: the update delay has passed
+        let clock = Clock::get()?;
+        let effective_at: u64 = deposit_stake_authority
+            .pending_parameters_effective_at
+            .into();
+        if clock.unix_timestamp.unsigned_abs() < effective_at {
+            return Err(StakeDepositInterceptorError::PendingParametersNotEffective.into());
+        }
+
+        deposit_stake_authority.apply_pending_parameters();
+
+        StakeDepositInterceptorEvent::ApplyPendingParameters {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            fee_wallet: deposit_stake_authority.fee_wallet,
+            cool_down_seconds: deposit_stake_authority.cool_down_seconds.into(),
+            initial_fee_bps: deposit_stake_authority.inital_fee_bps.into(),
+            fee_curve: deposit_stake_authority.fee_curve.config(),
         }
+        .emit();
 
         Ok(())
     }
 
     /// Invoke the provided stake-pool program's DepositStake (or DepositStakeWithSlippage), but use
     /// the vault account from the `StakePoolDepositStakeAuthority` to custody the "pool" tokens.
+    /// With `tokenize` set, the new DepositReceipt's receipt token is minted to the owner as
+    /// TokenizeDepositReceipt would.
     pub fn process_deposit_stake(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         deposit_stake_args: DepositStakeArgs,
         minimum_pool_tokens_out: Option<u64>,
+        tokenize: bool,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let payer_info = next_account_info(account_info_iter)?;
@@ -269,11 +503,30 @@ impl Processor {
         let token_program_info = next_account_info(account_info_iter)?;
         let stake_program_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
+        // The owner is found by key among the trailing accounts, so it does not depend on whether
+        // the optional FeeExemption precedes it
+        let trailing_accounts = account_info_iter.as_slice();
+        let receipt_token_infos = if tokenize {
+            Some((
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            ))
+        } else {
+            None
+        };
+        let fee_exemption_info = next_account_info(account_info_iter).ok();
 
         // Validate: System program is correct native program
         check_system_program(system_program_info.key)?;
-        // Validate `StakePoolDepositStakeAuthority` is owned by current program.
-        check_account_owner(deposit_stake_authority_info, program_id)?;
+        // Validate `StakePoolDepositStakeAuthority` is owned by current program with a known layout.
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+        // Validate: deposit_stake_authority must be writable to count the DepositReceipt
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
         // Validate: DepositReceipt should be owned by system program and not initialized
         check_system_account(deposit_receipt_info, true)?;
 
@@ -286,7 +539,7 @@ impl Processor {
 
         let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
         let deposit_stake_authority =
-            StakePoolDepositStakeAuthority::try_from_slice_unchecked(&deposit_stake_authority_data)
+            StakePoolDepositStakeAuthority::try_from_account_data(&deposit_stake_authority_data)
                 .unwrap();
 
         // Validate StakePoolDepositStakeAuthority PDA is correct
@@ -295,6 +548,33 @@ impl Processor {
             deposit_stake_authority_info.key,
             &deposit_stake_authority,
         )?;
+        // Validate: deposits must not be paused
+        if deposit_stake_authority.deposits_paused() {
+            return Err(StakeDepositInterceptorError::Paused.into());
+        }
+        let owner_signed = trailing_accounts
+            .iter()
+            .any(|owner_info| owner_info.is_signer && owner_info.key == &deposit_stake_args.owner);
+        // Validate: the owner must sign when required, otherwise whoever relays a stake account
+        // that was already authorized to the StakePoolDepositStakeAuthority could name any owner
+        if deposit_stake_authority.owner_signature_required() && !owner_signed {
+            return Err(StakeDepositInterceptorError::OwnerSignatureRequired.into());
+        }
+        if let Some((_, _, owner_info, receipt_token_program_info, _)) = receipt_token_infos {
+            // Validate: the receipt token is minted to the DepositReceipt's owner
+            if owner_info.key != &deposit_stake_args.owner {
+                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            }
+            // Validate: receipt mints are Token-2022 mints so they can be closed once burned
+            if receipt_token_program_info.key != &spl_token_2022::id() {
+                return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+            }
+            // Validate: the receipt token can be transferred freely, bypassing any restriction
+            // on changing the DepositReceipt's owner
+            if deposit_stake_authority.receipt_transfer_policy()? != ReceiptTransferPolicy::Allow {
+                return Err(StakeDepositInterceptorError::ReceiptTransferForbidden.into());
+            }
+        }
         // Validate Vault token account to receive pool tokens is coorect.
         if pool_tokens_vault_info.key != &deposit_stake_authority.vault {
             return Err(StakeDepositInterceptorError::InvalidVault.into());
@@ -310,7 +590,7 @@ impl Processor {
             return Err(StakeDepositInterceptorError::InvalidStakePool.into());
         }
 
-        let vault_token_account_before = Account::unpack(&pool_tokens_vault_info.data.borrow())?;
+        let vault_token_account_before = unpack_token_account(pool_tokens_vault_info)?;
 
         // CPI to SPL stake-pool program to invoke DepositStake with the `StakePoolDepositStakeAuthority` as the
         // `stake_deposit_authority`.
@@ -335,56 +615,210 @@ impl Processor {
             minimum_pool_tokens_out,
         )?;
 
-        let vault_token_account_after = Account::unpack(&pool_tokens_vault_info.data.borrow())?;
+        let vault_token_account_after = unpack_token_account(pool_tokens_vault_info)?;
         let pool_tokens_minted = vault_token_account_after
             .amount
             .checked_sub(vault_token_account_before.amount)
             .expect("overflow");
 
+        // Validate: deposit must stay within the deposit limits
+        deposit_stake_authority.check_deposit_limits(pool_tokens_minted)?;
+
+        // Apply the owner's FeeExemption, if any, to the DepositReceipt's initial fee rate
+        let initial_fee_bps = fee_exemption_initial_fee_bps(
+            program_id,
+            fee_exemption_info,
+            deposit_stake_authority_info,
+            &deposit_stake_authority,
+            &deposit_stake_args.owner,
+            owner_signed,
+        )?;
+
         // Create the DepositReceipt
+        create_deposit_receipt(
+            program_id,
+            payer_info,
+            deposit_receipt_info,
+            stake_pool_info,
+            base_info,
+            deposit_stake_authority_info,
+            system_program_info,
+            &deposit_stake_authority,
+            &deposit_stake_args.owner,
+            initial_fee_bps,
+            pool_tokens_minted,
+        )?;
 
-        let rent = Rent::get()?;
-        let clock = Clock::get()?;
+        // Count the new DepositReceipt and its pool tokens
+        {
+            let mut deposit_stake_authority: RefMut<StakePoolDepositStakeAuthority> =
+                borrow_mut_after_cpi(deposit_stake_authority_info, deposit_stake_authority_data)?;
+            deposit_stake_authority.open_deposit_receipt();
+            deposit_stake_authority.add_outstanding_lst(pool_tokens_minted);
+        }
+
+        if let Some((
+            receipt_mint_info,
+            receipt_token_account_info,
+            owner_info,
+            receipt_token_program_info,
+            _associated_token_program_info,
+        )) = receipt_token_infos
+        {
+            mint_receipt_token(
+                program_id,
+                payer_info,
+                owner_info,
+                deposit_receipt_info,
+                receipt_mint_info,
+                receipt_token_account_info,
+                receipt_token_program_info,
+                system_program_info,
+            )?;
+        }
 
-        let (deposit_receipt_pda, bump_seed) =
-            derive_stake_deposit_receipt(program_id, stake_pool_info.key, base_info.key);
+        Ok(())
+    }
 
-        // Validate: DepositReceipt should be canonical PDA
-        if deposit_receipt_pda != *deposit_receipt_info.key {
-            return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+    /// Invoke the StakePool DepositSol instruction with the StakePoolDepositStakeAuthority as the
+    /// `sol_deposit_authority`. The minted "pool" tokens are held in the vault and a DepositReceipt
+    /// is created with the same time decayed fee as a DepositStake.
+    pub fn process_deposit_sol(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        deposit_sol_args: DepositSolArgs,
+        minimum_pool_tokens_out: Option<u64>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let stake_pool_program_info = next_account_info(account_info_iter)?;
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let base_info = next_account_info(account_info_iter)?;
+        let withdraw_authority_info = next_account_info(account_info_iter)?;
+        let reserve_stake_account_info = next_account_info(account_info_iter)?;
+        let lamports_from_info = next_account_info(account_info_iter)?;
+        let pool_tokens_vault_info = next_account_info(account_info_iter)?;
+        let manager_fee_info = next_account_info(account_info_iter)?;
+        let referrer_fee_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let fee_exemption_info = next_account_info(account_info_iter).ok();
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+        // Validate `StakePoolDepositStakeAuthority` is owned by current program with a known layout.
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+        // Validate: deposit_stake_authority must be writable to count the DepositReceipt
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
         }
+        // Validate: DepositReceipt should be owned by system program and not initialized
+        check_system_account(deposit_receipt_info, true)?;
 
-        let pda_seeds = [
-            DEPOSIT_RECEIPT,
-            &stake_pool_info.key.to_bytes(),
-            &base_info.key.to_bytes(),
-            &[bump_seed],
-        ];
-        // Create and initialize the DepositReceipt account
-        create_pda_account(
-            payer_info,
-            &rent,
-            8 + mem::size_of::<DepositReceipt>(),
+        // Validate: base signed the TX
+        if !base_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        // NOTE: we assume that stake-pool program makes all of the assertions that the SPL stake-pool program does.
+
+        let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+        let deposit_stake_authority =
+            StakePoolDepositStakeAuthority::try_from_account_data(&deposit_stake_authority_data)
+                .unwrap();
+
+        // Validate StakePoolDepositStakeAuthority PDA is correct
+        check_deposit_stake_authority_address(
             program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+        // Validate: deposits must not be paused
+        if deposit_stake_authority.deposits_paused() {
+            return Err(StakeDepositInterceptorError::Paused.into());
+        }
+        // Validate Vault token account to receive pool tokens is coorect.
+        if pool_tokens_vault_info.key != &deposit_stake_authority.vault {
+            return Err(StakeDepositInterceptorError::InvalidVault.into());
+        }
+
+        // Validate: stake-pool program must match the program used to set up the authority
+        if &deposit_stake_authority.stake_pool_program_id != stake_pool_program_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolProgram.into());
+        }
+
+        // Validate: StakePool must match the `StakePoolDepositStakeAuthority` StakePool
+        if &deposit_stake_authority.stake_pool != stake_pool_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+        }
+
+        let vault_token_account_before = unpack_token_account(pool_tokens_vault_info)?;
+
+        // CPI to SPL stake-pool program to invoke DepositSol with the `StakePoolDepositStakeAuthority` as the
+        // `sol_deposit_authority`.
+        deposit_sol_cpi(
+            stake_pool_program_info,
+            stake_pool_info,
+            withdraw_authority_info,
+            reserve_stake_account_info,
+            lamports_from_info,
+            pool_tokens_vault_info,
+            manager_fee_info,
+            referrer_fee_info,
+            pool_mint_info,
             system_program_info,
+            token_program_info,
+            deposit_stake_authority_info,
+            &deposit_stake_authority,
+            deposit_sol_args.lamports,
+            minimum_pool_tokens_out,
+        )?;
+
+        let vault_token_account_after = unpack_token_account(pool_tokens_vault_info)?;
+        let pool_tokens_minted = vault_token_account_after
+            .amount
+            .checked_sub(vault_token_account_before.amount)
+            .expect("overflow");
+
+        // Validate: deposit must stay within the deposit limits
+        deposit_stake_authority.check_deposit_limits(pool_tokens_minted)?;
+
+        // Apply the owner's FeeExemption, if any, to the DepositReceipt's initial fee rate
+        let initial_fee_bps = fee_exemption_initial_fee_bps(
+            program_id,
+            fee_exemption_info,
+            deposit_stake_authority_info,
+            &deposit_stake_authority,
+            &deposit_sol_args.owner,
+            // The owner signs as the payer or the source of the lamports
+            [payer_info, lamports_from_info].iter().any(|owner_info| {
+                owner_info.is_signer && owner_info.key == &deposit_sol_args.owner
+            }),
+        )?;
+
+        // Create the DepositReceipt
+        create_deposit_receipt(
+            program_id,
+            payer_info,
             deposit_receipt_info,
-            &pda_seeds,
+            stake_pool_info,
+            base_info,
+            deposit_stake_authority_info,
+            system_program_info,
+            &deposit_stake_authority,
+            &deposit_sol_args.owner,
+            initial_fee_bps,
+            pool_tokens_minted,
         )?;
 
-        let mut deposit_receipt_data = deposit_receipt_info.try_borrow_mut_data()?;
-        deposit_receipt_data[0] = DepositReceipt::DISCRIMINATOR;
-        let deposit_receipt =
-            DepositReceipt::try_from_slice_unchecked_mut(&mut deposit_receipt_data).unwrap();
-
-        deposit_receipt.base = *base_info.key;
-        deposit_receipt.owner = deposit_stake_args.owner;
-        deposit_receipt.stake_pool = *stake_pool_info.key;
-        deposit_receipt.stake_pool_deposit_stake_authority = *deposit_stake_authority_info.key;
-        deposit_receipt.deposit_time = clock.unix_timestamp.unsigned_abs().into();
-        deposit_receipt.lst_amount = pool_tokens_minted.into();
-        deposit_receipt.cool_down_seconds = deposit_stake_authority.cool_down_seconds;
-        deposit_receipt.initial_fee_bps = deposit_stake_authority.inital_fee_bps;
-        deposit_receipt.bump_seed = bump_seed;
+        // Count the new DepositReceipt and its pool tokens
+        let mut deposit_stake_authority: RefMut<StakePoolDepositStakeAuthority> =
+            borrow_mut_after_cpi(deposit_stake_authority_info, deposit_stake_authority_data)?;
+        deposit_stake_authority.open_deposit_receipt();
+        deposit_stake_authority.add_outstanding_lst(pool_tokens_minted);
 
         Ok(())
     }
@@ -399,9 +833,12 @@ impl Processor {
         let deposit_receipt_info = next_account_info(account_info_iter)?;
         let owner_info = next_account_info(account_info_iter)?;
         let new_owner_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
 
-        // Validate: program owns `DepositReceipt`
-        check_account_owner(deposit_receipt_info, program_id)?;
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
 
         // Validate: owner must be a signer
         if !owner_info.is_signer {
@@ -410,157 +847,2280 @@ impl Processor {
 
         let mut deposit_receipt_data = deposit_receipt_info.try_borrow_mut_data()?;
         let deposit_receipt =
-            DepositReceipt::try_from_slice_unchecked_mut(&mut deposit_receipt_data).unwrap();
+            DepositReceipt::try_from_account_data_mut(&mut deposit_receipt_data).unwrap();
 
         // Validate: DepositReceipt address must match expected PDA
         check_deposit_receipt_address(program_id, deposit_receipt_info.key, &deposit_receipt)?;
 
+        // Validate: tokenized DepositReceipts are held through their receipt token
+        if deposit_receipt.is_tokenized() {
+            return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+        }
+
         // Validate: owner should match that of the DepositReceipt
         if owner_info.key != &deposit_receipt.owner {
             return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
         }
 
+        // Validate: StakePoolDepositStakeAuthority must match the same during creation of DepositReceipt
+        if deposit_stake_authority_info.key != &deposit_receipt.stake_pool_deposit_stake_authority {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into());
+        }
+
+        let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+        let deposit_stake_authority =
+            StakePoolDepositStakeAuthority::try_from_account_data(&deposit_stake_authority_data)
+                .unwrap();
+
+        let clock = Clock::get()?;
+        match deposit_stake_authority.receipt_transfer_policy()? {
+            ReceiptTransferPolicy::Allow => {}
+            ReceiptTransferPolicy::ForbidDuringCoolDown => {
+                let cool_down_end_time: i64 = u64::from(deposit_receipt.deposit_time)
+                    .checked_add(deposit_receipt.cool_down_seconds.into())
+                    .expect("overflow")
+                    .try_into()
+                    .expect("overflow");
+                // Validate: owner cannot change until the fee has fully decayed
+                if cool_down_end_time > clock.unix_timestamp {
+                    return Err(StakeDepositInterceptorError::ReceiptTransferForbidden.into());
+                }
+            }
+            ReceiptTransferPolicy::ResetDepositTime => {
+                // The new owner owes fees as if they had just deposited
+                deposit_receipt.deposit_time = u64::try_from(clock.unix_timestamp)
+                    .expect("overflow")
+                    .into();
+
+                StakeDepositInterceptorEvent::ResetDepositTime {
+                    deposit_receipt: *deposit_receipt_info.key,
+                    deposit_time: deposit_receipt.deposit_time.into(),
+                }
+                .emit();
+            }
+        }
+
         // Update owner to new_owner
         deposit_receipt.owner = *new_owner_info.key;
 
+        StakeDepositInterceptorEvent::ChangeDepositReceiptOwner {
+            deposit_receipt: *deposit_receipt_info.key,
+            previous_owner: *owner_info.key,
+            owner: deposit_receipt.owner,
+        }
+        .emit();
+
         Ok(())
     }
 
-    /// Transfers "pool" tokens to a token account owned by the DepositReceipt `owner`.
-    /// If this instruction is invoked during the `cool_down_seconds`, then fees will be
-    /// sent to a token account owned by the `fee_wallet`. ONLY the DepositReceipt `owner`
-    /// may invoke this instruction during the `cool_down_seconds`. Once the `cool_down_seconds`
-    /// has ended, the instruction is permissionless and no fees are subtracted from the
-    /// depositors original amount of "pool" tokens.
-    pub fn process_claim_pool_tokens(
+    /// Carve a new DepositReceipt with `lst_amount` out of an existing DepositReceipt. The new
+    /// DepositReceipt keeps the owner, `deposit_time`, `cool_down_seconds`, and `initial_fee_bps`
+    /// of the original so the fee schedule is unchanged.
+    pub fn process_split_deposit_receipt(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
+        split_deposit_receipt_args: SplitDepositReceiptArgs,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
         let deposit_receipt_info = next_account_info(account_info_iter)?;
         let owner_info = next_account_info(account_info_iter)?;
-        let vault_token_account_info = next_account_info(account_info_iter)?;
-        let destination_token_account_info = next_account_info(account_info_iter)?;
-        let fee_token_account_info = next_account_info(account_info_iter)?;
-        let deposit_stake_authority_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
-        let pool_mint_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
-        let token_program_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
-        let system_program_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
+        let new_deposit_receipt_info = next_account_info(account_info_iter)?;
+        let base_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
 
         // Validate: System program is correct native program
         check_system_program(system_program_info.key)?;
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+        // Validate: new DepositReceipt should be owned by system program and not initialized
+        check_system_account(new_deposit_receipt_info, true)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
 
-        // Validate: program owns `StakePoolDepositStakeAuthority`
-        check_account_owner(deposit_stake_authority_info, program_id)?;
+        // Validate: owner must be a signer
+        if !owner_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
 
-        // Validate: program owns `DepositReceipt`
-        check_account_owner(deposit_receipt_info, program_id)?;
+        // Validate: base signed the TX
+        if !base_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_receipt_data = deposit_receipt_info.try_borrow_mut_data()?;
+        let deposit_receipt =
+            DepositReceipt::try_from_account_data_mut(&mut deposit_receipt_data).unwrap();
+
+        // Validate: DepositReceipt address must match expected PDA
+        check_deposit_receipt_address(program_id, deposit_receipt_info.key, &deposit_receipt)?;
+
+        // Validate: tokenized DepositReceipts are held through their receipt token
+        if deposit_receipt.is_tokenized() {
+            return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+        }
+
+        // Validate: owner should match that of the DepositReceipt
+        if owner_info.key != &deposit_receipt.owner {
+            return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+        }
+
+        // Validate: StakePoolDepositStakeAuthority must be the one that created the DepositReceipt
+        if deposit_stake_authority_info.key != &deposit_receipt.stake_pool_deposit_stake_authority {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into());
+        }
+
+        let lst_amount = u64::from(deposit_receipt.lst_amount);
+        let split_amount = split_deposit_receipt_args.lst_amount;
+        // Validate: split amount must be non-zero and leave some amount on the original DepositReceipt
+        if split_amount == 0 || split_amount >= lst_amount {
+            return Err(StakeDepositInterceptorError::InvalidSplitAmount.into());
+        }
+
+        let stake_pool = deposit_receipt.stake_pool;
+        let (new_deposit_receipt_pda, bump_seed) =
+            derive_stake_deposit_receipt(program_id, &stake_pool, base_info.key);
+
+        // Validate: new DepositReceipt should be canonical PDA
+        if new_deposit_receipt_pda != *new_deposit_receipt_info.key {
+            return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+        }
+
+        let rent = Rent::get()?;
+        let pda_seeds = [
+            DEPOSIT_RECEIPT,
+            &stake_pool.to_bytes(),
+            &base_info.key.to_bytes(),
+            &[bump_seed],
+        ];
+        // Create and initialize the new DepositReceipt account
+        create_pda_account(
+            payer_info,
+            &rent,
+            DepositReceipt::LEN,
+            program_id,
+            system_program_info,
+            new_deposit_receipt_info,
+            &pda_seeds,
+        )?;
+
+        let mut new_deposit_receipt_data = new_deposit_receipt_info.try_borrow_mut_data()?;
+        new_deposit_receipt_data[0] = DepositReceipt::DISCRIMINATOR;
+        let new_deposit_receipt =
+            DepositReceipt::try_from_account_data_mut(&mut new_deposit_receipt_data).unwrap();
+
+        // Copy the fee schedule and ownership from the original DepositReceipt
+        *new_deposit_receipt = *deposit_receipt;
+        new_deposit_receipt.base = *base_info.key;
+        new_deposit_receipt.lst_amount = split_amount.into();
+        new_deposit_receipt.bump_seed = bump_seed;
+        new_deposit_receipt.rent_payer = *payer_info.key;
+        new_deposit_receipt.version = DepositReceipt::CURRENT_VERSION;
+
+        deposit_receipt.lst_amount = lst_amount
+            .checked_sub(split_amount)
+            .expect("overflow")
+            .into();
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap()
+        .open_deposit_receipt();
+
+        StakeDepositInterceptorEvent::SplitDepositReceipt {
+            deposit_receipt: *deposit_receipt_info.key,
+            new_deposit_receipt: *new_deposit_receipt_info.key,
+            lst_amount: split_amount,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Merge the `source` DepositReceipt into the `destination` DepositReceipt and close the
+    /// `source`. Both DepositReceipts must share the same owner, StakePoolDepositStakeAuthority,
+    /// and fee parameters. The resulting `deposit_time` is the average of both deposit times
+    /// weighted by `lst_amount`, after moving a deposit time whose cool down has ended up to the
+    /// latest such time so an expired DepositReceipt cannot shorten the other's cool down. This
+    /// keeps the total outstanding fee unchanged (up to rounding in the protocol's favor) for a
+    /// Linear fee curve only. DepositReceipts with any other curve can only be merged when their
+    /// remaining fee schedules are identical.
+    pub fn process_merge_deposit_receipts(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let destination_deposit_receipt_info = next_account_info(account_info_iter)?;
+        let source_deposit_receipt_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let source_rent_payer_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns both `DepositReceipt`s with a known layout
+        check_deposit_receipt_account(destination_deposit_receipt_info, program_id)?;
+        check_deposit_receipt_account(source_deposit_receipt_info, program_id)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: owner must be a signer
+        if !owner_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        // Validate: cannot merge a DepositReceipt into itself
+        if destination_deposit_receipt_info.key == source_deposit_receipt_info.key {
+            return Err(StakeDepositInterceptorError::InvalidDepositReceipt.into());
+        }
+
+        {
+            let mut destination_deposit_receipt_data =
+                destination_deposit_receipt_info.try_borrow_mut_data()?;
+            let destination_deposit_receipt =
+                DepositReceipt::try_from_account_data_mut(&mut destination_deposit_receipt_data)
+                    .unwrap();
+            let source_deposit_receipt_data = source_deposit_receipt_info.try_borrow_data()?;
+            let source_deposit_receipt =
+                DepositReceipt::try_from_account_data(&source_deposit_receipt_data).unwrap();
+
+            // Validate: DepositReceipt addresses must match expected PDAs
+            check_deposit_receipt_address(
+                program_id,
+                destination_deposit_receipt_info.key,
+                &destination_deposit_receipt,
+            )?;
+            check_deposit_receipt_address(
+                program_id,
+                source_deposit_receipt_info.key,
+                &source_deposit_receipt,
+            )?;
+
+            // Validate: owner should match that of both DepositReceipts
+            if owner_info.key != &destination_deposit_receipt.owner
+                || owner_info.key != &source_deposit_receipt.owner
+            {
+                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            }
+
+            // Validate: tokenized DepositReceipts are held through their receipt token
+            if destination_deposit_receipt.is_tokenized() || source_deposit_receipt.is_tokenized() {
+                return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+            }
+
+            // Validate: DepositReceipts must originate from the same StakePoolDepositStakeAuthority
+            // and share the same fee parameters
+            if destination_deposit_receipt.stake_pool_deposit_stake_authority
+                != source_deposit_receipt.stake_pool_deposit_stake_authority
+                || destination_deposit_receipt.stake_pool != source_deposit_receipt.stake_pool
+                || destination_deposit_receipt.cool_down_seconds
+                    != source_deposit_receipt.cool_down_seconds
+                || destination_deposit_receipt.initial_fee_bps
+                    != source_deposit_receipt.initial_fee_bps
+                || destination_deposit_receipt.fee_curve != source_deposit_receipt.fee_curve
+            {
+                return Err(StakeDepositInterceptorError::IncompatibleDepositReceipts.into());
+            }
+
+            // Validate: StakePoolDepositStakeAuthority must be the one that created the DepositReceipts
+            if deposit_stake_authority_info.key
+                != &destination_deposit_receipt.stake_pool_deposit_stake_authority
+            {
+                return Err(
+                    StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into(),
+                );
+            }
+
+            // Validate: source DepositReceipt's rent is refunded to whoever paid it
+            if source_rent_payer_info.key != &source_deposit_receipt.rent_refund_destination() {
+                return Err(StakeDepositInterceptorError::InvalidRentPayer.into());
+            }
+
+            // Expired deposit times only count as ending their cool down now, otherwise their
+            // age would offset the fee still owed by the other DepositReceipt
+            let clock = Clock::get()?;
+            let destination_deposit_time =
+                destination_deposit_receipt.effective_deposit_time(clock.unix_timestamp);
+            let source_deposit_time =
+                source_deposit_receipt.effective_deposit_time(clock.unix_timestamp);
+
+            // Validate: a weighted deposit time only preserves the fee of a Linear curve. The
+            // fee of any other curve is not linear in time, so their schedules must match.
+            if destination_deposit_receipt.fee_curve.curve_type() != FeeCurveType::Linear
+                && destination_deposit_time != source_deposit_time
+            {
+                return Err(StakeDepositInterceptorError::IncompatibleDepositReceipts.into());
+            }
+
+            let destination_amount = u64::from(destination_deposit_receipt.lst_amount);
+            let source_amount = u64::from(source_deposit_receipt.lst_amount);
+            let total_amount = destination_amount
+                .checked_add(source_amount)
+                .expect("overflow");
+
+            if total_amount > 0 {
+                // Round up so merging can never shorten the fee schedule
+                let weighted_deposit_time = u128::from(destination_deposit_time)
+                    .checked_mul(destination_amount as u128)
+                    .expect("overflow")
+                    .checked_add(
+                        u128::from(source_deposit_time)
+                            .checked_mul(source_amount as u128)
+                            .expect("overflow"),
+                    )
+                    .expect("overflow")
+                    .div_ceil(total_amount as u128);
+                destination_deposit_receipt.deposit_time =
+                    u64::try_from(weighted_deposit_time).unwrap().into();
+            }
+            destination_deposit_receipt.lst_amount = total_amount.into();
+
+            StakeDepositInterceptorEvent::MergeDepositReceipts {
+                destination_deposit_receipt: *destination_deposit_receipt_info.key,
+                source_deposit_receipt: *source_deposit_receipt_info.key,
+                lst_amount: total_amount,
+                deposit_time: destination_deposit_receipt.deposit_time.into(),
+            }
+            .emit();
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap()
+        .close_deposit_receipt();
+        drop(deposit_stake_authority_data);
+
+        // Close the source DepositReceipt account
+        close_account(source_deposit_receipt_info, source_rent_payer_info)?;
+
+        Ok(())
+    }
+
+    /// Transfers "pool" tokens to a token account owned by the DepositReceipt `owner`, or to
+    /// any token account of the pool mint when the `owner` signs. If this instruction is invoked during the `cool_down_seconds`, then fees will be
+    /// sent to a token account owned by the `fee_wallet`. ONLY the DepositReceipt `owner`
+    /// may invoke this instruction during the `cool_down_seconds`. Once the `cool_down_seconds`
+    /// has ended, the instruction is permissionless and no fees are subtracted from the
+    /// depositors original amount of "pool" tokens.
+    ///
+    /// When an `amount` is given (i.e. ClaimPoolTokensPartial), only that amount is claimed and
+    /// fees are only charged on it. The DepositReceipt stays open until its `lst_amount` is zero.
+    pub fn process_claim_pool_tokens(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: Option<u64>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let destination_token_account_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
+        let pool_mint_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
+        let token_program_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
+        let system_program_info: &AccountInfo<'_> = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+        // The optional keeper follows the transfer hook accounts and only counts when it signs.
+        // It is also passed to the transfer hook, which ignores accounts it does not need.
+        let keeper_info = account_info_iter
+            .as_slice()
+            .last()
+            .filter(|keeper_info| keeper_info.is_signer);
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable to accrue fees
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+
+        let (close_deposit_receipt, keeper_tip_lamports) = {
+            let clock = Clock::get()?;
+
+            let mut deposit_receipt_data = deposit_receipt_info.try_borrow_mut_data()?;
+            let deposit_receipt =
+                DepositReceipt::try_from_account_data_mut(&mut deposit_receipt_data).unwrap();
+
+            let cool_down_end_time: i64 = u64::from(deposit_receipt.deposit_time)
+                .checked_add(deposit_receipt.cool_down_seconds.into())
+                .expect("overflow")
+                .try_into()
+                .expect("overflow");
+
+            // Validate: Owner must be signer during cool down to prevent unintended fee payment
+            if cool_down_end_time > clock.unix_timestamp && !owner_info.is_signer {
+                return Err(StakeDepositInterceptorError::ActiveCooldown.into());
+            }
+
+            // Validate: Owner must match that of DepositReceipt
+            if &deposit_receipt.owner != owner_info.key {
+                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            }
+
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data(
+                &deposit_stake_authority_data,
+            )
+            .unwrap();
+
+            // Validate: the accounts shared with ClaimTokenizedDepositReceipt
+            let destination_token_account = check_claim_accounts(
+                program_id,
+                deposit_receipt_info,
+                &deposit_receipt,
+                deposit_stake_authority_info,
+                &deposit_stake_authority,
+                vault_token_account_info,
+                destination_token_account_info,
+                pool_mint_info,
+                token_program_info,
+                rent_payer_info,
+                cool_down_end_time > clock.unix_timestamp,
+            )?;
+
+            // Validate: tokenized DepositReceipts are claimed by burning their receipt token
+            if deposit_receipt.is_tokenized() {
+                return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+            }
+
+            // Validate: Permissionless claims must send to a token account owned by the
+            // DepositReceipt `owner`. A signing owner may direct the tokens anywhere.
+            if !owner_info.is_signer && destination_token_account.owner != deposit_receipt.owner {
+                return Err(StakeDepositInterceptorError::InvalidDestinationTokenAccount.into());
+            }
+
+            let lst_amount = u64::from(deposit_receipt.lst_amount);
+            let claim_amount = match amount {
+                Some(amount) => {
+                    // Validate: partial claim must be non-zero and cannot exceed the DepositReceipt's amount
+                    if amount == 0 || amount > lst_amount {
+                        return Err(StakeDepositInterceptorError::InvalidClaimAmount.into());
+                    }
+                    amount
+                }
+                None => lst_amount,
+            };
+
+            let pool_mint = unpack_mint(pool_mint_info)?;
+
+            let fee_amount =
+                deposit_receipt.calculate_fee_amount_for(claim_amount, clock.unix_timestamp);
+
+            let amount = claim_amount.saturating_sub(fee_amount);
+            // The owner bears the Token-2022 transfer fee, which is withheld from the amount
+            // received by the destination
+            let transfer_fee = calculate_transfer_fee(pool_mint_info, amount)?;
+            // Transfer the rest of the tokens to the destination token account. Any remaining
+            // accounts are the extra accounts required by the pool mint's transfer hook.
+            transfer_tokens_cpi(
+                token_program_info.clone(),
+                vault_token_account_info.clone(),
+                pool_mint_info.clone(),
+                destination_token_account_info.clone(),
+                deposit_stake_authority_info.clone(),
+                account_info_iter.as_slice(),
+                amount,
+                pool_mint.decimals,
+                &deposit_stake_authority,
+            )?;
+
+            // Fee tokens stay in the vault until swept
+            let mut deposit_stake_authority: RefMut<StakePoolDepositStakeAuthority> =
+                borrow_mut_after_cpi(deposit_stake_authority_info, deposit_stake_authority_data)?;
+            deposit_stake_authority.accrued_fees = u64::from(deposit_stake_authority.accrued_fees)
+                .checked_add(fee_amount)
+                .expect("overflow")
+                .into();
+
+            let remaining_lst_amount = lst_amount.checked_sub(claim_amount).expect("overflow");
+            deposit_receipt.lst_amount = remaining_lst_amount.into();
+            deposit_stake_authority.remove_outstanding_lst(claim_amount);
+            if remaining_lst_amount == 0 {
+                deposit_stake_authority.close_deposit_receipt();
+            }
+
+            // Tip a signing keeper out of the DepositReceipt's rent when it cranks a
+            // permissionless claim, which is only allowed after the cool down
+            let keeper_tip_lamports =
+                if remaining_lst_amount == 0 && !owner_info.is_signer && keeper_info.is_some() {
+                    deposit_stake_authority.keeper_tip(deposit_receipt_info.lamports())
+                } else {
+                    0
+                };
+
+            StakeDepositInterceptorEvent::ClaimPoolTokens {
+                deposit_receipt: *deposit_receipt_info.key,
+                owner: deposit_receipt.owner,
+                destination: *destination_token_account_info.key,
+                fee_amount,
+                net_amount: amount.checked_sub(transfer_fee).expect("overflow"),
+                remaining_lst_amount,
+            }
+            .emit();
+            if transfer_fee > 0 {
+                StakeDepositInterceptorEvent::ClaimPoolTokensTransferFee {
+                    deposit_receipt: *deposit_receipt_info.key,
+                    transfer_fee,
+                }
+                .emit();
+            }
+
+            (remaining_lst_amount == 0, keeper_tip_lamports)
+        };
+
+        if close_deposit_receipt {
+            if let Some(keeper_info) = keeper_info.filter(|_| keeper_tip_lamports > 0) {
+                **deposit_receipt_info.lamports.borrow_mut() = deposit_receipt_info
+                    .lamports()
+                    .checked_sub(keeper_tip_lamports)
+                    .expect("overflow");
+                **keeper_info.lamports.borrow_mut() = keeper_info
+                    .lamports()
+                    .checked_add(keeper_tip_lamports)
+                    .expect("overflow");
+
+                StakeDepositInterceptorEvent::PayKeeperTip {
+                    deposit_receipt: *deposit_receipt_info.key,
+                    keeper: *keeper_info.key,
+                    keeper_tip_lamports,
+                }
+                .emit();
+            }
+            // Close the DepositReceipt account and refund the rest of the rent to its rent payer
+            close_account(deposit_receipt_info, rent_payer_info)?;
+        }
+
+        Ok(())
+    }
+
+    /// Claim all of the "pool" tokens held for a DepositReceipt on behalf of its signing owner and
+    /// withdraw the amount left after fees from the StakePool as a stake account authorized to the
+    /// owner. The net tokens are burned straight from the vault, with the
+    /// StakePoolDepositStakeAuthority as the `user_transfer_authority`.
+    pub fn process_claim_and_withdraw_stake(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        claim_and_withdraw_args: ClaimAndWithdrawArgs,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+        let stake_pool_program_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let validator_list_storage_info = next_account_info(account_info_iter)?;
+        let withdraw_authority_info = next_account_info(account_info_iter)?;
+        let stake_to_split_info = next_account_info(account_info_iter)?;
+        let stake_to_receive_info = next_account_info(account_info_iter)?;
+        let manager_fee_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+        let stake_program_info = next_account_info(account_info_iter)?;
+
+        Self::process_claim_and_withdraw(
+            program_id,
+            deposit_receipt_info,
+            owner_info,
+            vault_token_account_info,
+            deposit_stake_authority_info,
+            pool_mint_info,
+            token_program_info,
+            system_program_info,
+            rent_payer_info,
+            stake_pool_program_info,
+            stake_pool_info,
+            stake_to_receive_info,
+            |deposit_stake_authority, pool_tokens_in| {
+                // CPI to SPL stake-pool program to invoke WithdrawStake with the owner as the
+                // authority of the new stake account
+                withdraw_stake_cpi(
+                    stake_pool_program_info,
+                    stake_pool_info,
+                    validator_list_storage_info,
+                    withdraw_authority_info,
+                    stake_to_split_info,
+                    stake_to_receive_info,
+                    owner_info,
+                    deposit_stake_authority_info,
+                    vault_token_account_info,
+                    manager_fee_info,
+                    pool_mint_info,
+                    clock_info,
+                    token_program_info,
+                    stake_program_info,
+                    deposit_stake_authority,
+                    pool_tokens_in,
+                    claim_and_withdraw_args.minimum_lamports_out,
+                )
+            },
+        )
+    }
+
+    /// Claim all of the "pool" tokens held for a DepositReceipt on behalf of its signing owner and
+    /// withdraw the amount left after fees from the StakePool's reserve as SOL sent to the owner.
+    pub fn process_claim_and_withdraw_sol(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        claim_and_withdraw_args: ClaimAndWithdrawArgs,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+        let stake_pool_program_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let withdraw_authority_info = next_account_info(account_info_iter)?;
+        let reserve_stake_account_info = next_account_info(account_info_iter)?;
+        let manager_fee_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+        let stake_history_info = next_account_info(account_info_iter)?;
+        let stake_program_info = next_account_info(account_info_iter)?;
+        let sol_withdraw_authority_info = next_account_info(account_info_iter).ok();
+
+        Self::process_claim_and_withdraw(
+            program_id,
+            deposit_receipt_info,
+            owner_info,
+            vault_token_account_info,
+            deposit_stake_authority_info,
+            pool_mint_info,
+            token_program_info,
+            system_program_info,
+            rent_payer_info,
+            stake_pool_program_info,
+            stake_pool_info,
+            owner_info,
+            |deposit_stake_authority, pool_tokens_in| {
+                // CPI to SPL stake-pool program to invoke WithdrawSol with the owner receiving the
+                // lamports
+                withdraw_sol_cpi(
+                    stake_pool_program_info,
+                    stake_pool_info,
+                    withdraw_authority_info,
+                    deposit_stake_authority_info,
+                    vault_token_account_info,
+                    reserve_stake_account_info,
+                    owner_info,
+                    manager_fee_info,
+                    pool_mint_info,
+                    clock_info,
+                    stake_history_info,
+                    stake_program_info,
+                    token_program_info,
+                    sol_withdraw_authority_info,
+                    deposit_stake_authority,
+                    pool_tokens_in,
+                    claim_and_withdraw_args.minimum_lamports_out,
+                )
+            },
+        )
+    }
+
+    /// Validates a claim of the DepositReceipt's full `lst_amount` by its signing owner, invokes
+    /// `withdraw` with the "pool" tokens left after fees, then accrues the fees and closes the
+    /// DepositReceipt. `withdrawn_to_info` is the account receiving the withdrawn lamports.
+    fn process_claim_and_withdraw<'a>(
+        program_id: &Pubkey,
+        deposit_receipt_info: &AccountInfo<'a>,
+        owner_info: &AccountInfo<'a>,
+        vault_token_account_info: &AccountInfo<'a>,
+        deposit_stake_authority_info: &AccountInfo<'a>,
+        pool_mint_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent_payer_info: &AccountInfo<'a>,
+        stake_pool_program_info: &AccountInfo<'a>,
+        stake_pool_info: &AccountInfo<'a>,
+        withdrawn_to_info: &AccountInfo<'a>,
+        withdraw: impl FnOnce(&StakePoolDepositStakeAuthority, u64) -> ProgramResult,
+    ) -> ProgramResult {
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable to accrue fees
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+
+        // Validate: Owner must sign as the beneficiary of the withdrawal
+        if !owner_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        {
+            let clock = Clock::get()?;
+
+            let deposit_receipt_data = deposit_receipt_info.try_borrow_data()?;
+            let deposit_receipt =
+                DepositReceipt::try_from_account_data(&deposit_receipt_data).unwrap();
+
+            // Validate: Owner must match that of DepositReceipt
+            if &deposit_receipt.owner != owner_info.key {
+                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            }
+
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data(
+                &deposit_stake_authority_data,
+            )
+            .unwrap();
+
+            // Validate: StakePoolDepositStakeAuthority PDA is correct
+            check_deposit_stake_authority_address(
+                program_id,
+                deposit_stake_authority_info.key,
+                &deposit_stake_authority,
+            )?;
+
+            // Validate: DepositReceipt address must match expected PDA
+            check_deposit_receipt_address(program_id, deposit_receipt_info.key, &deposit_receipt)?;
+
+            // Validate: tokenized DepositReceipts are claimed by burning their receipt token
+            if deposit_receipt.is_tokenized() {
+                return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+            }
+
+            // Validate: DepositReceipt's rent is refunded to whoever paid it
+            if rent_payer_info.key != &deposit_receipt.rent_refund_destination() {
+                return Err(StakeDepositInterceptorError::InvalidRentPayer.into());
+            }
+
+            // Validate: StakePoolDepositStakeAuthority must match the same during creation of DepositReceipt
+            if deposit_stake_authority_info.key
+                != &deposit_receipt.stake_pool_deposit_stake_authority
+            {
+                return Err(
+                    StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into(),
+                );
+            }
+
+            let cool_down_end_time: i64 = u64::from(deposit_receipt.deposit_time)
+                .checked_add(deposit_receipt.cool_down_seconds.into())
+                .expect("overflow")
+                .try_into()
+                .expect("overflow");
+
+            // Validate: claims during the cool down must not be paused
+            if cool_down_end_time > clock.unix_timestamp
+                && deposit_stake_authority.early_claims_paused()
+            {
+                return Err(StakeDepositInterceptorError::Paused.into());
+            }
+
+            // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
+            if &deposit_stake_authority.vault != vault_token_account_info.key {
+                return Err(StakeDepositInterceptorError::InvalidVault.into());
+            }
+
+            // Validate: Pool mint should match that of the `StakePoolDepositStakeAuthority`, which is the StakePool's mint
+            if &deposit_stake_authority.pool_mint != pool_mint_info.key {
+                return Err(StakeDepositInterceptorError::InvalidPoolMint.into());
+            }
+
+            // Validate: Token program must be the pool mint's token program
+            if pool_mint_info.owner != token_program_info.key {
+                return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+            }
+
+            // Validate: stake-pool program must match the program used to set up the authority
+            if &deposit_stake_authority.stake_pool_program_id != stake_pool_program_info.key {
+                return Err(StakeDepositInterceptorError::InvalidStakePoolProgram.into());
+            }
+
+            // Validate: StakePool must match the `StakePoolDepositStakeAuthority` StakePool
+            if &deposit_stake_authority.stake_pool != stake_pool_info.key {
+                return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+            }
+
+            // NOTE: we assume that stake-pool program makes all of the assertions that the SPL stake-pool program does.
+
+            let lst_amount = u64::from(deposit_receipt.lst_amount);
+            let fee_amount =
+                deposit_receipt.calculate_fee_amount_for(lst_amount, clock.unix_timestamp);
+            let amount = lst_amount.saturating_sub(fee_amount);
+
+            let lamports_before = withdrawn_to_info.lamports();
+            withdraw(&deposit_stake_authority, amount)?;
+            let lamports_withdrawn = withdrawn_to_info
+                .lamports()
+                .checked_sub(lamports_before)
+                .expect("overflow");
+
+            // Fee tokens stay in the vault until swept
+            let mut deposit_stake_authority: RefMut<StakePoolDepositStakeAuthority> =
+                borrow_mut_after_cpi(deposit_stake_authority_info, deposit_stake_authority_data)?;
+            deposit_stake_authority.accrued_fees = u64::from(deposit_stake_authority.accrued_fees)
+                .checked_add(fee_amount)
+                .expect("overflow")
+                .into();
+            deposit_stake_authority.remove_outstanding_lst(lst_amount);
+            deposit_stake_authority.close_deposit_receipt();
+
+            StakeDepositInterceptorEvent::ClaimAndWithdraw {
+                deposit_receipt: *deposit_receipt_info.key,
+                owner: deposit_receipt.owner,
+                withdrawn_to: *withdrawn_to_info.key,
+                fee_amount,
+                pool_tokens_withdrawn: amount,
+                lamports_withdrawn,
+            }
+            .emit();
+        }
+
+        // Close the DepositReceipt account and refund its rent payer
+        close_account(deposit_receipt_info, rent_payer_info)
+    }
+
+    /// Mint a 1-of-1 Token-2022 token representing the DepositReceipt to its owner. The receipt
+    /// mint is a PDA closable by the DepositReceipt and has no mint authority once the token is
+    /// minted. From then on the DepositReceipt can only be claimed by burning the token, so the
+    /// locked position can be traded while its time decayed fee still applies.
+    pub fn process_tokenize_deposit_receipt(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let receipt_mint_info = next_account_info(account_info_iter)?;
+        let receipt_token_account_info = next_account_info(account_info_iter)?;
+        let receipt_token_program_info = next_account_info(account_info_iter)?;
+        let _associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: receipt mints are Token-2022 mints so they can be closed once burned
+        if receipt_token_program_info.key != &spl_token_2022::id() {
+            return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+        }
+
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+
+        // Validate: owner must be a signer
+        if !owner_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        {
+            let deposit_receipt_data = deposit_receipt_info.try_borrow_data()?;
+            let deposit_receipt =
+                DepositReceipt::try_from_account_data(&deposit_receipt_data).unwrap();
+
+            // Validate: DepositReceipt address must match expected PDA
+            check_deposit_receipt_address(program_id, deposit_receipt_info.key, deposit_receipt)?;
+
+            // Validate: owner should match that of the DepositReceipt
+            if owner_info.key != &deposit_receipt.owner {
+                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            }
+
+            // Validate: DepositReceipt can only be tokenized once
+            if deposit_receipt.is_tokenized() {
+                return Err(StakeDepositInterceptorError::DepositReceiptTokenized.into());
+            }
+
+            // Validate: StakePoolDepositStakeAuthority must match the same during creation of DepositReceipt
+            if deposit_stake_authority_info.key
+                != &deposit_receipt.stake_pool_deposit_stake_authority
+            {
+                return Err(
+                    StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into(),
+                );
+            }
+
+            // Validate: the receipt token can be transferred freely, bypassing any restriction on
+            // changing the DepositReceipt's owner
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data(
+                &deposit_stake_authority_data,
+            )
+            .unwrap();
+            if deposit_stake_authority.receipt_transfer_policy()? != ReceiptTransferPolicy::Allow {
+                return Err(StakeDepositInterceptorError::ReceiptTransferForbidden.into());
+            }
+        }
+
+        mint_receipt_token(
+            program_id,
+            owner_info,
+            owner_info,
+            deposit_receipt_info,
+            receipt_mint_info,
+            receipt_token_account_info,
+            receipt_token_program_info,
+            system_program_info,
+        )
+    }
+
+    /// Claim all of the "pool" tokens held for a tokenized DepositReceipt by burning its receipt
+    /// token. Whoever holds the token may claim to any token account of the pool mint, with the
+    /// same fee as ClaimPoolTokens. Unless the ReceiptTransferPolicy in force at the claim is
+    /// Allow, the claim must wait for the cool down to end. The receipt mint's rent goes to the
+    /// holder and the DepositReceipt's rent to its rent payer.
+    pub fn process_claim_tokenized_deposit_receipt(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_receipt_info = next_account_info(account_info_iter)?;
+        let holder_info = next_account_info(account_info_iter)?;
+        let receipt_token_account_info = next_account_info(account_info_iter)?;
+        let receipt_mint_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let destination_token_account_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let receipt_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+
+        // Validate: receipt mints are Token-2022 mints
+        if receipt_token_program_info.key != &spl_token_2022::id() {
+            return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+        }
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable to accrue fees
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: program owns `DepositReceipt` with a known layout
+        check_deposit_receipt_account(deposit_receipt_info, program_id)?;
+
+        // Validate: holder must sign to burn the receipt token
+        if !holder_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        {
+            let clock = Clock::get()?;
+
+            let deposit_receipt_data = deposit_receipt_info.try_borrow_data()?;
+            let deposit_receipt =
+                DepositReceipt::try_from_account_data(&deposit_receipt_data).unwrap();
+
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data(
+                &deposit_stake_authority_data,
+            )
+            .unwrap();
+
+            let cool_down_end_time: i64 = u64::from(deposit_receipt.deposit_time)
+                .checked_add(deposit_receipt.cool_down_seconds.into())
+                .expect("overflow")
+                .try_into()
+                .expect("overflow");
+            let in_cool_down = cool_down_end_time > clock.unix_timestamp;
+
+            // Validate: the accounts shared with ClaimPoolTokens
+            check_claim_accounts(
+                program_id,
+                deposit_receipt_info,
+                deposit_receipt,
+                deposit_stake_authority_info,
+                deposit_stake_authority,
+                vault_token_account_info,
+                destination_token_account_info,
+                pool_mint_info,
+                token_program_info,
+                rent_payer_info,
+                in_cool_down,
+            )?;
+
+            // Validate: receipt mint must be that of the tokenized DepositReceipt
+            if !deposit_receipt.is_tokenized()
+                || receipt_mint_info.key != &deposit_receipt.receipt_mint
+            {
+                return Err(StakeDepositInterceptorError::InvalidReceiptMint.into());
+            }
+
+            // Validate: the receipt token may have changed hands any number of times, even if it
+            // is back with the DepositReceipt's owner, and when it did is unknown. Any
+            // ReceiptTransferPolicy other than Allow therefore forbids claims during the cool down.
+            if deposit_stake_authority.receipt_transfer_policy()? != ReceiptTransferPolicy::Allow
+                && in_cool_down
+            {
+                return Err(StakeDepositInterceptorError::ReceiptTransferForbidden.into());
+            }
+
+            // Burning the receipt token authorizes the claim. The emptied receipt mint is then
+            // closed to the holder.
+            invoke(
+                &spl_token_2022::instruction::burn_checked(
+                    receipt_token_program_info.key,
+                    receipt_token_account_info.key,
+                    receipt_mint_info.key,
+                    holder_info.key,
+                    &[],
+                    1,
+                    0,
+                )?,
+                &[
+                    receipt_token_account_info.clone(),
+                    receipt_mint_info.clone(),
+                    holder_info.clone(),
+                ],
+            )?;
+            invoke_signed(
+                &spl_token_2022::instruction::close_account(
+                    receipt_token_program_info.key,
+                    receipt_mint_info.key,
+                    holder_info.key,
+                    deposit_receipt_info.key,
+                    &[],
+                )?,
+                &[
+                    receipt_mint_info.clone(),
+                    holder_info.clone(),
+                    deposit_receipt_info.clone(),
+                ],
+                &[deposit_receipt_signer_seeds!(deposit_receipt)],
+            )?;
+
+            let lst_amount = u64::from(deposit_receipt.lst_amount);
+            let pool_mint = unpack_mint(pool_mint_info)?;
+
+            let fee_amount =
+                deposit_receipt.calculate_fee_amount_for(lst_amount, clock.unix_timestamp);
+
+            let amount = lst_amount.saturating_sub(fee_amount);
+            // The owner bears the Token-2022 transfer fee, which is withheld from the amount
+            // received by the destination
+            let transfer_fee = calculate_transfer_fee(pool_mint_info, amount)?;
+            // Transfer the rest of the tokens to the destination token account. Any remaining
+            // accounts are the extra accounts required by the pool mint's transfer hook.
+            transfer_tokens_cpi(
+                token_program_info.clone(),
+                vault_token_account_info.clone(),
+                pool_mint_info.clone(),
+                destination_token_account_info.clone(),
+                deposit_stake_authority_info.clone(),
+                account_info_iter.as_slice(),
+                amount,
+                pool_mint.decimals,
+                deposit_stake_authority,
+            )?;
+
+            // Fee tokens stay in the vault until swept
+            let mut deposit_stake_authority: RefMut<StakePoolDepositStakeAuthority> =
+                borrow_mut_after_cpi(deposit_stake_authority_info, deposit_stake_authority_data)?;
+            deposit_stake_authority.accrued_fees = u64::from(deposit_stake_authority.accrued_fees)
+                .checked_add(fee_amount)
+                .expect("overflow")
+                .into();
+            deposit_stake_authority.remove_outstanding_lst(lst_amount);
+            deposit_stake_authority.close_deposit_receipt();
+
+            StakeDepositInterceptorEvent::ClaimPoolTokens {
+                deposit_receipt: *deposit_receipt_info.key,
+                owner: *holder_info.key,
+                destination: *destination_token_account_info.key,
+                fee_amount,
+                net_amount: amount.checked_sub(transfer_fee).expect("overflow"),
+                remaining_lst_amount: 0,
+            }
+            .emit();
+            if transfer_fee > 0 {
+                StakeDepositInterceptorEvent::ClaimPoolTokensTransferFee {
+                    deposit_receipt: *deposit_receipt_info.key,
+                    transfer_fee,
+                }
+                .emit();
+            }
+        }
+
+        // Close the DepositReceipt account and refund its rent payer
+        close_account(deposit_receipt_info, rent_payer_info)
+    }
+
+    /// Upgrade a `StakePoolDepositStakeAuthority` or `DepositReceipt` to the current layout
+    /// version. Permissionless; the payer funds any rent required by a larger layout.
+    pub fn process_migrate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        check_system_program(system_program_info.key)?;
+        check_account_owner(account_info, program_id)?;
+        if !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Resolve the target layout from the account's discriminator
+        let (discriminator, previous_version) = {
+            let data = account_info.try_borrow_data()?;
+            let discriminator = *data.first().ok_or(ProgramError::InvalidAccountData)?;
+            let previous_version = match discriminator {
+                StakePoolDepositStakeAuthority::DISCRIMINATOR => {
+                    StakePoolDepositStakeAuthority::version(&data)
+                }
+                DepositReceipt::DISCRIMINATOR => DepositReceipt::version(&data),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            (discriminator, previous_version)
+        };
+        let (len, current_version) = match discriminator {
+            StakePoolDepositStakeAuthority::DISCRIMINATOR => (
+                StakePoolDepositStakeAuthority::LEN,
+                StakePoolDepositStakeAuthority::CURRENT_VERSION,
+            ),
+            _ => (DepositReceipt::LEN, DepositReceipt::CURRENT_VERSION),
+        };
+        if previous_version > current_version {
+            return Err(StakeDepositInterceptorError::UnsupportedAccountVersion.into());
+        }
+
+        // Accounts already longer than the current layout keep their size
+        if account_info.data_len() < len {
+            // Top up rent for the larger layout before growing the account
+            let required_lamports = Rent::get()?
+                .minimum_balance(len)
+                .saturating_sub(account_info.lamports());
+            if required_lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        payer_info.key,
+                        account_info.key,
+                        required_lamports,
+                    ),
+                    &[
+                        payer_info.clone(),
+                        account_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            account_info.realloc(len, true)?;
+        }
+
+        let mut data = account_info.try_borrow_mut_data()?;
+        match discriminator {
+            StakePoolDepositStakeAuthority::DISCRIMINATOR => {
+                StakePoolDepositStakeAuthority::try_from_account_data_mut(&mut data)?.version =
+                    current_version
+            }
+            _ => DepositReceipt::try_from_account_data_mut(&mut data)?.version = current_version,
+        }
+
+        StakeDepositInterceptorEvent::MigrateAccount {
+            account: *account_info.key,
+            previous_version,
+            version: current_version,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Record a `StakePoolDepositStakeAuthority` in its StakePool's `StakePoolRegistry`, creating
+    /// the registry on first use. ONLY accessible by the StakePool's manager.
+    pub fn process_register_stake_pool_deposit_stake_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let stake_pool_registry_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let manager_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        let rent = Rent::get()?;
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: manager is signer
+        if !manager_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let stake_pool_program_id = {
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data(
+                &deposit_stake_authority_data,
+            )
+            .unwrap();
+
+            check_deposit_stake_authority_address(
+                program_id,
+                deposit_stake_authority_info.key,
+                deposit_stake_authority,
+            )?;
+
+            // Validate: StakePoolDepositStakeAuthority was created for the StakePool
+            if deposit_stake_authority.stake_pool != *stake_pool_info.key {
+                return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+            }
+            deposit_stake_authority.stake_pool_program_id
+        };
+
+        // Validate: StakePool must be owned by the correct program
+        if *stake_pool_info.owner != stake_pool_program_id {
+            return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+        }
+
+        let stake_pool = try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(
+            &stake_pool_info.data.borrow(),
+        )?;
+
+        // Validate: manager matches the StakePool's
+        if stake_pool.manager != *manager_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolManager.into());
+        }
+
+        if stake_pool_registry_info.data_is_empty() {
+            // Validate: StakePoolRegistry should be owned by system program and not initialized
+            check_system_account(stake_pool_registry_info, true)?;
+
+            let (stake_pool_registry_pda, bump_seed) =
+                derive_stake_pool_registry(program_id, stake_pool_info.key);
+
+            // Validate: StakePoolRegistry should be canonical PDA
+            if stake_pool_registry_pda != *stake_pool_registry_info.key {
+                return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+            }
+
+            let pda_seeds = [
+                STAKE_POOL_REGISTRY,
+                &stake_pool_info.key.to_bytes(),
+                &[bump_seed],
+            ];
+            // Create and initialize the StakePoolRegistry account
+            create_pda_account(
+                payer_info,
+                &rent,
+                StakePoolRegistry::LEN,
+                program_id,
+                system_program_info,
+                stake_pool_registry_info,
+                &pda_seeds,
+            )?;
+
+            let mut stake_pool_registry_data = stake_pool_registry_info.try_borrow_mut_data()?;
+            stake_pool_registry_data[0] = StakePoolRegistry::DISCRIMINATOR;
+            let stake_pool_registry =
+                StakePoolRegistry::try_from_slice_unchecked_mut(&mut stake_pool_registry_data)
+                    .unwrap();
+            stake_pool_registry.stake_pool = *stake_pool_info.key;
+            stake_pool_registry.stake_pool_program_id = stake_pool_program_id;
+            stake_pool_registry.bump_seed = bump_seed;
+        } else {
+            // Validate: program owns `StakePoolRegistry`
+            check_account_owner(stake_pool_registry_info, program_id)?;
+        }
+
+        let mut stake_pool_registry_data = stake_pool_registry_info.try_borrow_mut_data()?;
+        let stake_pool_registry =
+            StakePoolRegistry::try_from_slice_unchecked_mut(&mut stake_pool_registry_data)?;
+
+        check_stake_pool_registry_address(
+            program_id,
+            stake_pool_registry_info.key,
+            stake_pool_registry,
+        )?;
+
+        // Validate: StakePoolRegistry belongs to the StakePool
+        if stake_pool_registry.stake_pool != *stake_pool_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolRegistry.into());
+        }
+
+        stake_pool_registry.register(deposit_stake_authority_info.key)?;
+        stake_pool_registry.update_active(&stake_pool.stake_deposit_authority);
+
+        StakeDepositInterceptorEvent::RegisterStakePoolDepositStakeAuthority {
+            stake_pool_registry: *stake_pool_registry_info.key,
+            stake_pool: stake_pool_registry.stake_pool,
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            active_deposit_stake_authority: stake_pool_registry.active_deposit_stake_authority,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Remove a `StakePoolDepositStakeAuthority` from its StakePool's `StakePoolRegistry`. ONLY
+    /// accessible by the StakePool's manager.
+    pub fn process_deregister_stake_pool_deposit_stake_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let stake_pool_registry_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let manager_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolRegistry`
+        check_account_owner(stake_pool_registry_info, program_id)?;
+
+        // Validate: manager is signer
+        if !manager_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut stake_pool_registry_data = stake_pool_registry_info.try_borrow_mut_data()?;
+        let stake_pool_registry =
+            StakePoolRegistry::try_from_slice_unchecked_mut(&mut stake_pool_registry_data)?;
+
+        check_stake_pool_registry_address(
+            program_id,
+            stake_pool_registry_info.key,
+            stake_pool_registry,
+        )?;
+
+        // Validate: StakePool matches the StakePoolRegistry's and is owned by the correct program
+        if stake_pool_registry.stake_pool != *stake_pool_info.key
+            || *stake_pool_info.owner != stake_pool_registry.stake_pool_program_id
+        {
+            return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+        }
+
+        let stake_pool = try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(
+            &stake_pool_info.data.borrow(),
+        )?;
+
+        // Validate: manager matches the StakePool's
+        if stake_pool.manager != *manager_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolManager.into());
+        }
+
+        // Validate: the StakePool's installed authority stays registered
+        if stake_pool.stake_deposit_authority == *deposit_stake_authority_info.key {
+            return Err(StakeDepositInterceptorError::DepositStakeAuthorityActive.into());
+        }
+
+        stake_pool_registry.deregister(deposit_stake_authority_info.key)?;
+        stake_pool_registry.update_active(&stake_pool.stake_deposit_authority);
+
+        StakeDepositInterceptorEvent::DeregisterStakePoolDepositStakeAuthority {
+            stake_pool_registry: *stake_pool_registry_info.key,
+            stake_pool: stake_pool_registry.stake_pool,
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            active_deposit_stake_authority: stake_pool_registry.active_deposit_stake_authority,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Re-read the StakePool's `stake_deposit_authority` into its `StakePoolRegistry`'s
+    /// `active_deposit_stake_authority`, e.g. after the StakePool manager changed it.
+    /// Permissionless.
+    pub fn process_refresh_stake_pool_registry(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let stake_pool_registry_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolRegistry`
+        check_account_owner(stake_pool_registry_info, program_id)?;
+
+        let mut stake_pool_registry_data = stake_pool_registry_info.try_borrow_mut_data()?;
+        let stake_pool_registry =
+            StakePoolRegistry::try_from_slice_unchecked_mut(&mut stake_pool_registry_data)?;
+
+        check_stake_pool_registry_address(
+            program_id,
+            stake_pool_registry_info.key,
+            stake_pool_registry,
+        )?;
+
+        // Validate: StakePool matches the StakePoolRegistry's and is owned by the correct program
+        if stake_pool_registry.stake_pool != *stake_pool_info.key
+            || *stake_pool_info.owner != stake_pool_registry.stake_pool_program_id
+        {
+            return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+        }
+
+        let stake_pool = try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(
+            &stake_pool_info.data.borrow(),
+        )?;
+
+        stake_pool_registry.update_active(&stake_pool.stake_deposit_authority);
+
+        StakeDepositInterceptorEvent::RefreshStakePoolRegistry {
+            stake_pool_registry: *stake_pool_registry_info.key,
+            stake_pool: stake_pool_registry.stake_pool,
+            active_deposit_stake_authority: stake_pool_registry.active_deposit_stake_authority,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Replace the `StakePoolDepositStakeAuthority`'s fee recipients. ONLY accessible by the authority.
+    pub fn process_set_fee_recipients(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        set_fee_recipients_args: SetFeeRecipientsArgs,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
+
+        // Validate: fee recipients are well formed and their shares do not exceed 100%
+        deposit_stake_authority.set_fee_recipients(&set_fee_recipients_args.fee_recipients)?;
+
+        StakeDepositInterceptorEvent::SetFeeRecipients {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            fee_recipients: set_fee_recipients_args.fee_recipients,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Send the accrued fees held by the vault to the `fee_wallet` and fee recipients.
+    /// Permissionless.
+    pub fn process_sweep_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let fee_token_account_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Reset the accrued fees up front and keep a copy to sign the transfer CPIs with, so the
+        // data is not borrowed while they run
+        let (deposit_stake_authority, accrued_fees) = {
+            let mut deposit_stake_authority_data =
+                deposit_stake_authority_info.try_borrow_mut_data()?;
+            let deposit_stake_authority =
+                StakePoolDepositStakeAuthority::try_from_account_data_mut(
+                    &mut deposit_stake_authority_data,
+                )
+                .unwrap();
+
+            check_deposit_stake_authority_address(
+                program_id,
+                deposit_stake_authority_info.key,
+                deposit_stake_authority,
+            )?;
+
+            let accrued_fees = u64::from(deposit_stake_authority.accrued_fees);
+            deposit_stake_authority.accrued_fees = PodU64::from(0);
+            (*deposit_stake_authority, accrued_fees)
+        };
+
+        // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
+        if &deposit_stake_authority.vault != vault_token_account_info.key {
+            return Err(StakeDepositInterceptorError::InvalidVault.into());
+        }
+
+        // Validate: Pool mint should match that of the `StakePoolDepositStakeAuthority`
+        if &deposit_stake_authority.pool_mint != pool_mint_info.key {
+            return Err(StakeDepositInterceptorError::InvalidPoolMint.into());
+        }
+
+        // Validate: Token program must be the pool mint's token program
+        if pool_mint_info.owner != token_program_info.key {
+            return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+        }
+
+        let fee_token_account = unpack_token_account(fee_token_account_info)?;
+
+        // Validate: Fee token account must be owned by `fee_wallet`
+        if fee_token_account.owner != deposit_stake_authority.fee_wallet {
+            return Err(StakeDepositInterceptorError::InvalidFeeTokenAccount.into());
+        }
+
+        let mut fee_recipient_token_account_infos = vec![];
+        for fee_recipient in deposit_stake_authority
+            .fee_recipients
+            .iter()
+            .filter(|fee_recipient| fee_recipient.is_set())
+        {
+            let fee_recipient_token_account_info = next_account_info(account_info_iter)?;
+            let fee_recipient_token_account =
+                unpack_token_account(fee_recipient_token_account_info)?;
+
+            // Validate: Fee recipient token account must be owned by the fee recipient
+            if fee_recipient_token_account.owner != fee_recipient.wallet {
+                return Err(StakeDepositInterceptorError::InvalidFeeTokenAccount.into());
+            }
+            fee_recipient_token_account_infos.push(fee_recipient_token_account_info);
+        }
+        // Any remaining accounts are the extra accounts required by the pool mint's transfer hook
+        let transfer_hook_account_infos = account_info_iter.as_slice();
+
+        let pool_mint = unpack_mint(pool_mint_info)?;
+        let (fee_recipient_amounts, fee_wallet_amount) =
+            deposit_stake_authority.split_fee(accrued_fees);
+        // Each recipient bears the Token-2022 transfer fee on its own share. The fees did not
+        // leave the vault when they were claimed, so this is the only transfer fee they pay.
+        let mut transfer_fee = 0u64;
+        let mut received_fee_recipient_amounts = fee_recipient_amounts;
+
+        // Transfer the remainder to the fee token account
+        let fee_wallet_transfer_fee = calculate_transfer_fee(pool_mint_info, fee_wallet_amount)?;
+        transfer_fee += fee_wallet_transfer_fee;
+        transfer_tokens_cpi(
+            token_program_info.clone(),
+            vault_token_account_info.clone(),
+            pool_mint_info.clone(),
+            fee_token_account_info.clone(),
+            deposit_stake_authority_info.clone(),
+            transfer_hook_account_infos,
+            fee_wallet_amount,
+            pool_mint.decimals,
+            &deposit_stake_authority,
+        )?;
+
+        // Transfer each fee recipient's share to its token account
+        for ((fee_recipient_token_account_info, fee_recipient_amount), received_amount) in
+            fee_recipient_token_account_infos
+                .into_iter()
+                .zip(fee_recipient_amounts)
+                .zip(received_fee_recipient_amounts.iter_mut())
+        {
+            let fee_recipient_transfer_fee =
+                calculate_transfer_fee(pool_mint_info, fee_recipient_amount)?;
+            transfer_fee += fee_recipient_transfer_fee;
+            *received_amount = fee_recipient_amount
+                .checked_sub(fee_recipient_transfer_fee)
+                .expect("overflow");
+            transfer_tokens_cpi(
+                token_program_info.clone(),
+                vault_token_account_info.clone(),
+                pool_mint_info.clone(),
+                fee_recipient_token_account_info.clone(),
+                deposit_stake_authority_info.clone(),
+                transfer_hook_account_infos,
+                fee_recipient_amount,
+                pool_mint.decimals,
+                &deposit_stake_authority,
+            )?;
+        }
+
+        StakeDepositInterceptorEvent::SweepFees {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            fee_wallet_amount: fee_wallet_amount
+                .checked_sub(fee_wallet_transfer_fee)
+                .expect("overflow"),
+            fee_recipient_amounts: received_fee_recipient_amounts.to_vec(),
+        }
+        .emit();
+        if transfer_fee > 0 {
+            StakeDepositInterceptorEvent::SweepFeesTransferFee {
+                deposit_stake_authority: *deposit_stake_authority_info.key,
+                transfer_fee,
+            }
+            .emit();
+        }
+
+        Ok(())
+    }
+
+    /// Close the `StakePoolDepositStakeAuthority` and its vault, sending their lamports to the
+    /// destination. Requires an empty vault and no outstanding DepositReceipts or FeeExemptions.
+    /// ONLY accessible by the authority.
+    pub fn process_close_stake_pool_deposit_stake_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let stake_pool_registry_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let deposit_stake_authority = {
+            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+            *StakePoolDepositStakeAuthority::try_from_account_data(&deposit_stake_authority_data)
+                .unwrap()
+        };
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
+
+        // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
+        if &deposit_stake_authority.vault != vault_token_account_info.key {
+            return Err(StakeDepositInterceptorError::InvalidVault.into());
+        }
+
+        // Validate: Token program must be the vault's token program
+        if vault_token_account_info.owner != token_program_info.key {
+            return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+        }
+
+        // Validate: every DepositReceipt must be closed
+        if u64::from(deposit_stake_authority.outstanding_receipt_count) != 0 {
+            return Err(StakeDepositInterceptorError::OutstandingDepositReceipts.into());
+        }
+
+        // Validate: every FeeExemption must be removed, otherwise it would apply again to a
+        // StakePoolDepositStakeAuthority re-created at the same address
+        if u64::from(deposit_stake_authority.fee_exemption_count) != 0 {
+            return Err(StakeDepositInterceptorError::OutstandingFeeExemptions.into());
+        }
+
+        // Validate: vault must be empty. Also covers DepositReceipts created before the count
+        // was tracked, as their pool tokens are still held by the vault.
+        if unpack_token_account(vault_token_account_info)?.amount != 0 {
+            return Err(StakeDepositInterceptorError::VaultNotEmpty.into());
+        }
+
+        // Validate: StakePool must match that of the `StakePoolDepositStakeAuthority` and be
+        // owned by the correct program
+        if deposit_stake_authority.stake_pool != *stake_pool_info.key
+            || deposit_stake_authority.stake_pool_program_id != *stake_pool_info.owner
+        {
+            return Err(StakeDepositInterceptorError::InvalidStakePool.into());
+        }
+
+        let stake_pool = try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(
+            &stake_pool_info.data.borrow(),
+        )?;
+
+        // Validate: deposits must no longer be routed through the StakePoolDepositStakeAuthority
+        if stake_pool.stake_deposit_authority == *deposit_stake_authority_info.key {
+            return Err(StakeDepositInterceptorError::DepositStakeAuthorityActive.into());
+        }
+
+        // Validate: StakePoolRegistry should be the StakePool's canonical PDA
+        let (stake_pool_registry_pda, _bump_seed) =
+            derive_stake_pool_registry(program_id, stake_pool_info.key);
+        if stake_pool_registry_pda != *stake_pool_registry_info.key {
+            return Err(StakeDepositInterceptorError::InvalidStakePoolRegistry.into());
+        }
+
+        // Validate: the StakePoolRegistry, if any, must no longer record the
+        // StakePoolDepositStakeAuthority
+        if stake_pool_registry_info.owner == program_id {
+            let stake_pool_registry_data = stake_pool_registry_info.try_borrow_data()?;
+            let stake_pool_registry =
+                StakePoolRegistry::try_from_slice_unchecked(&stake_pool_registry_data)?;
+            if stake_pool_registry.is_registered(deposit_stake_authority_info.key) {
+                return Err(StakeDepositInterceptorError::DepositStakeAuthorityRegistered.into());
+            }
+        }
+
+        // Close the vault, which is owned by the StakePoolDepositStakeAuthority
+        let close_vault_ix = spl_token_2022::instruction::close_account(
+            token_program_info.key,
+            vault_token_account_info.key,
+            destination_info.key,
+            deposit_stake_authority_info.key,
+            &[],
+        )?;
+        invoke_signed(
+            &close_vault_ix,
+            &[
+                vault_token_account_info.clone(),
+                destination_info.clone(),
+                deposit_stake_authority_info.clone(),
+            ],
+            &[deposit_stake_authority_signer_seeds!(
+                deposit_stake_authority
+            )],
+        )?;
+
+        close_account(deposit_stake_authority_info, destination_info)?;
+
+        StakeDepositInterceptorEvent::CloseStakePoolDepositStakeAuthority {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            stake_pool: deposit_stake_authority.stake_pool,
+            destination: *destination_info.key,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Transfer the vault's pool tokens that are owed to neither DepositReceipt holders nor fee
+    /// recipients (e.g. tokens sent to the vault directly) to the destination token account.
+    /// ONLY accessible by the authority.
+    pub fn process_recover_excess_vault_tokens(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let vault_token_account_info = next_account_info(account_info_iter)?;
+        let destination_token_account_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
+        let deposit_stake_authority =
+            StakePoolDepositStakeAuthority::try_from_account_data(&deposit_stake_authority_data)
+                .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
+
+        // Validate: `total_outstanding_lst` must include every DepositReceipt, otherwise tokens
+        // owed to depositors could be mistaken for excess
+        if deposit_stake_authority.outstanding_lst_tracked == 0 {
+            return Err(StakeDepositInterceptorError::OutstandingLstNotTracked.into());
+        }
+
+        // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
+        if &deposit_stake_authority.vault != vault_token_account_info.key {
+            return Err(StakeDepositInterceptorError::InvalidVault.into());
+        }
+
+        // Validate: Pool mint should match that of the `StakePoolDepositStakeAuthority`
+        if &deposit_stake_authority.pool_mint != pool_mint_info.key {
+            return Err(StakeDepositInterceptorError::InvalidPoolMint.into());
+        }
+
+        // Validate: Token program must be the pool mint's token program
+        if pool_mint_info.owner != token_program_info.key {
+            return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+        }
+
+        let vault_token_account = unpack_token_account(vault_token_account_info)?;
+        let amount = deposit_stake_authority.excess_vault_amount(vault_token_account.amount);
+        if amount == 0 {
+            return Err(StakeDepositInterceptorError::NoExcessVaultTokens.into());
+        }
+
+        let pool_mint = unpack_mint(pool_mint_info)?;
+        let transfer_fee = calculate_transfer_fee(pool_mint_info, amount)?;
+        // Any remaining accounts are the extra accounts required by the pool mint's transfer hook
+        transfer_tokens_cpi(
+            token_program_info.clone(),
+            vault_token_account_info.clone(),
+            pool_mint_info.clone(),
+            destination_token_account_info.clone(),
+            deposit_stake_authority_info.clone(),
+            account_info_iter.as_slice(),
+            amount,
+            pool_mint.decimals,
+            &deposit_stake_authority,
+        )?;
+
+        StakeDepositInterceptorEvent::RecoverExcessVaultTokens {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            destination: *destination_token_account_info.key,
+            amount,
+            transfer_fee,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Replace the StakePoolDepositStakeAuthority's paused operations. ONLY accessible by the
+    /// authority.
+    pub fn process_set_pause_state(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: SetPauseStateArgs,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
+
+        deposit_stake_authority.set_pause_state(args.deposits_paused, args.early_claims_paused);
+
+        StakeDepositInterceptorEvent::SetPauseState {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            deposits_paused: args.deposits_paused,
+            early_claims_paused: args.early_claims_paused,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Create a FeeExemption granting `depositor` a reduced initial fee rate on DepositReceipts
+    /// created through the StakePoolDepositStakeAuthority. ONLY accessible by the authority.
+    pub fn process_add_fee_exemption(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        add_fee_exemption_args: AddFeeExemptionArgs,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let depositor_info = next_account_info(account_info_iter)?;
+        let fee_exemption_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        let rent = Rent::get()?;
+
+        // Validate: System program is correct native program
+        check_system_program(system_program_info.key)?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+        // Validate: FeeExemption should be owned by system program and not initialized
+        check_system_account(fee_exemption_info, true)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        // Validate: `initial_fee_bps` cannot exceed 100%
+        if add_fee_exemption_args
+            .initial_fee_bps
+            .gt(&DepositReceipt::FEE_BPS_DENOMINATOR)
+        {
+            return Err(StakeDepositInterceptorError::InitialFeeRateMaxExceeded.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
+
+        let (fee_exemption_pda, bump_seed) = derive_fee_exemption(
+            program_id,
+            deposit_stake_authority_info.key,
+            depositor_info.key,
+        );
+
+        // Validate: FeeExemption should be canonical PDA
+        if fee_exemption_pda != *fee_exemption_info.key {
+            return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+        }
+
+        let pda_seeds = [
+            FEE_EXEMPTION,
+            &deposit_stake_authority_info.key.to_bytes(),
+            &depositor_info.key.to_bytes(),
+            &[bump_seed],
+        ];
+        // Create and initialize the FeeExemption account
+        create_pda_account(
+            payer_info,
+            &rent,
+            FeeExemption::LEN,
+            program_id,
+            system_program_info,
+            fee_exemption_info,
+            &pda_seeds,
+        )?;
+
+        let mut fee_exemption_data = fee_exemption_info.try_borrow_mut_data()?;
+        fee_exemption_data[0] = FeeExemption::DISCRIMINATOR;
+        let fee_exemption =
+            FeeExemption::try_from_slice_unchecked_mut(&mut fee_exemption_data).unwrap();
+
+        fee_exemption.stake_pool_deposit_stake_authority = *deposit_stake_authority_info.key;
+        fee_exemption.depositor = *depositor_info.key;
+        fee_exemption.initial_fee_bps = add_fee_exemption_args.initial_fee_bps.into();
+        fee_exemption.bump_seed = bump_seed;
+
+        deposit_stake_authority.add_fee_exemption();
+
+        StakeDepositInterceptorEvent::AddFeeExemption {
+            deposit_stake_authority: fee_exemption.stake_pool_deposit_stake_authority,
+            depositor: fee_exemption.depositor,
+            initial_fee_bps: fee_exemption.initial_fee_bps.into(),
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Close a FeeExemption, returning its lamports to `destination`. DepositReceipts that were
+    /// already created keep their snapshotted fee rate. ONLY accessible by the authority.
+    pub fn process_remove_fee_exemption(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let fee_exemption_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+        // Validate: program owns `FeeExemption`
+        check_account_owner(fee_exemption_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
 
-        {
-            let clock = Clock::get()?;
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            deposit_stake_authority,
+        )?;
 
-            let deposit_receipt_data = deposit_receipt_info.try_borrow_data()?;
-            let deposit_receipt =
-                DepositReceipt::try_from_slice_unchecked(&deposit_receipt_data).unwrap();
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
 
-            let cool_down_end_time: i64 = u64::from(deposit_receipt.deposit_time)
-                .checked_add(deposit_receipt.cool_down_seconds.into())
-                .expect("overflow")
-                .try_into()
-                .expect("overflow");
+        let depositor = {
+            let fee_exemption_data = fee_exemption_info.try_borrow_data()?;
+            let fee_exemption =
+                FeeExemption::try_from_slice_unchecked(&fee_exemption_data).unwrap();
 
-            // Validate: Owner must be signer during cool down to prevent unintended fee payment
-            if cool_down_end_time > clock.unix_timestamp && !owner_info.is_signer {
-                return Err(StakeDepositInterceptorError::ActiveCooldown.into());
-            }
+            check_fee_exemption_address(program_id, fee_exemption_info.key, fee_exemption)?;
 
-            // Validate: Owner must match that of DepositReceipt
-            if &deposit_receipt.owner != owner_info.key {
-                return Err(StakeDepositInterceptorError::InvalidDepositReceiptOwner.into());
+            // Validate: FeeExemption belongs to the StakePoolDepositStakeAuthority
+            if fee_exemption.stake_pool_deposit_stake_authority != *deposit_stake_authority_info.key
+            {
+                return Err(StakeDepositInterceptorError::InvalidFeeExemption.into());
             }
 
-            let deposit_stake_authority_data = deposit_stake_authority_info.try_borrow_data()?;
-            let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked(
-                &deposit_stake_authority_data,
-            )
-            .unwrap();
+            fee_exemption.depositor
+        };
 
-            // Validate: StakePoolDepositStakeAuthority PDA is correct
-            check_deposit_stake_authority_address(
-                program_id,
-                deposit_stake_authority_info.key,
-                &deposit_stake_authority,
-            )?;
+        close_account(fee_exemption_info, destination_info)?;
 
-            // Validate: DepositReceipt address must match expected PDA
-            check_deposit_receipt_address(program_id, deposit_receipt_info.key, &deposit_receipt)?;
+        deposit_stake_authority.remove_fee_exemption();
 
-            // Validate: StakePoolDepositStakeAuthority must match the same during creation of DepositReceipt
-            if deposit_stake_authority_info.key
-                != &deposit_receipt.stake_pool_deposit_stake_authority
-            {
-                return Err(
-                    StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into(),
-                );
-            }
+        StakeDepositInterceptorEvent::RemoveFeeExemption {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            depositor,
+        }
+        .emit();
 
-            // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
-            if &deposit_stake_authority.vault != vault_token_account_info.key {
-                return Err(StakeDepositInterceptorError::InvalidVault.into());
-            }
+        Ok(())
+    }
 
-            // Validate: Pool mint should match that of the `StakePoolDepositStakeAuthority`, which is the StakePool's mint
-            if &deposit_stake_authority.pool_mint != pool_mint_info.key {
-                return Err(StakeDepositInterceptorError::InvalidPoolMint.into());
-            }
+    /// Propose a new authority for the `StakePoolDepositStakeAuthority`. The transfer only takes
+    /// effect once the new authority signs `AcceptAuthority`. ONLY accessible by the current authority.
+    pub fn process_propose_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let new_authority_info = next_account_info(account_info_iter)?;
 
-            let fee_token_account = Account::unpack(&fee_token_account_info.data.borrow())?;
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
 
-            // Validate: Fee token account must be owned by `fee_wallet`
-            if fee_token_account.owner != deposit_stake_authority.fee_wallet {
-                return Err(StakeDepositInterceptorError::InvalidFeeTokenAccount.into());
-            }
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
 
-            let destination_token_account =
-                Account::unpack(&destination_token_account_info.data.borrow())?;
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
 
-            // Validate: Destination token account must be owned by DepositRecipt `owner`
-            if destination_token_account.owner != deposit_receipt.owner {
-                return Err(StakeDepositInterceptorError::InvalidDestinationTokenAccount.into());
-            }
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
 
-            let pool_mint = Mint::unpack(&pool_mint_info.data.borrow())?;
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
 
-            let fee_amount = deposit_receipt.calculate_fee_amount(clock.unix_timestamp);
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
+        }
 
-            // Transfer fee tokens to fee token account
-            transfer_tokens_cpi(
-                token_program_info.clone(),
-                vault_token_account_info.clone(),
-                pool_mint_info.clone(),
-                fee_token_account_info.clone(),
-                deposit_stake_authority_info.clone(),
-                fee_amount,
-                pool_mint.decimals,
-                &deposit_stake_authority,
-            )?;
+        deposit_stake_authority.pending_authority = *new_authority_info.key;
 
-            let amount = u64::from(deposit_receipt.lst_amount).saturating_sub(fee_amount);
-            // Transfer the rest of the tokens to the destination token account
-            transfer_tokens_cpi(
-                token_program_info.clone(),
-                vault_token_account_info.clone(),
-                pool_mint_info.clone(),
-                destination_token_account_info.clone(),
-                deposit_stake_authority_info.clone(),
-                amount,
-                pool_mint.decimals,
-                &deposit_stake_authority,
-            )?;
+        StakeDepositInterceptorEvent::ProposeAuthority {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            authority: deposit_stake_authority.authority,
+            pending_authority: deposit_stake_authority.pending_authority,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Complete a pending authority transfer. ONLY accessible by the pending authority.
+    pub fn process_accept_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let pending_authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: pending authority is signer
+        if !pending_authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: a transfer is pending to the signer
+        if !deposit_stake_authority.has_pending_authority()
+            || deposit_stake_authority.pending_authority != *pending_authority_info.key
+        {
+            return Err(StakeDepositInterceptorError::InvalidPendingAuthority.into());
+        }
+
+        let previous_authority = deposit_stake_authority.authority;
+        deposit_stake_authority.authority = deposit_stake_authority.pending_authority;
+        deposit_stake_authority.pending_authority = Pubkey::default();
+
+        StakeDepositInterceptorEvent::AcceptAuthority {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            previous_authority,
+            authority: deposit_stake_authority.authority,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    /// Clear a pending authority transfer. ONLY accessible by the current authority.
+    pub fn process_cancel_authority_transfer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let deposit_stake_authority_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // Validate: program owns `StakePoolDepositStakeAuthority` with a known layout
+        check_deposit_stake_authority_account(deposit_stake_authority_info, program_id)?;
+
+        // Validate: deposit_stake_authority must be writable
+        if !deposit_stake_authority_info.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Validate: authority is signer
+        if !authority_info.is_signer {
+            return Err(StakeDepositInterceptorError::SignatureMissing.into());
+        }
+
+        let mut deposit_stake_authority_data =
+            deposit_stake_authority_info.try_borrow_mut_data()?;
+        let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_account_data_mut(
+            &mut deposit_stake_authority_data,
+        )
+        .unwrap();
+
+        check_deposit_stake_authority_address(
+            program_id,
+            deposit_stake_authority_info.key,
+            &deposit_stake_authority,
+        )?;
+
+        // Validate: authority matches
+        if deposit_stake_authority.authority != *authority_info.key {
+            return Err(StakeDepositInterceptorError::InvalidAuthority.into());
         }
 
-        // Close the DepositReceipt account
-        close_account(deposit_receipt_info, owner_info)?;
+        let pending_authority = deposit_stake_authority.pending_authority;
+        deposit_stake_authority.pending_authority = Pubkey::default();
+
+        StakeDepositInterceptorEvent::CancelAuthorityTransfer {
+            deposit_stake_authority: *deposit_stake_authority_info.key,
+            pending_authority,
+        }
+        .emit();
 
         Ok(())
     }
@@ -569,13 +3129,17 @@ impl Processor {
         let instruction = StakeDepositInterceptorInstruction::try_from_slice(input)?;
         match instruction {
             StakeDepositInterceptorInstruction::InitStakePoolDepositStakeAuthority(args) => {
-                Self::process_init_stake_pool_deposit_stake_authority(program_id, accounts, args)?;
+                Self::process_init_stake_pool_deposit_stake_authority(
+                    program_id,
+                    accounts,
+                    args.into(),
+                )?;
             }
             StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthority(args) => {
-                Self::process_update_deposit_stake_authority(program_id, accounts, args)?;
+                Self::process_update_deposit_stake_authority(program_id, accounts, args.into())?;
             }
             StakeDepositInterceptorInstruction::DepositStake(args) => {
-                Self::process_deposit_stake(program_id, accounts, args, None)?;
+                Self::process_deposit_stake(program_id, accounts, args, None, false)?;
             }
             StakeDepositInterceptorInstruction::DepositStakeWithSlippage(args) => {
                 let deposit_stake_args = DepositStakeArgs { owner: args.owner };
@@ -584,13 +3148,104 @@ impl Processor {
                     accounts,
                     deposit_stake_args,
                     Some(args.minimum_pool_tokens_out),
+                    false,
                 )?;
             }
             StakeDepositInterceptorInstruction::ChangeDepositReceiptOwner => {
                 Self::process_change_deposit_receipt_owner(program_id, accounts)?;
             }
             StakeDepositInterceptorInstruction::ClaimPoolTokens => {
-                Self::process_claim_pool_tokens(program_id, accounts)?;
+                Self::process_claim_pool_tokens(program_id, accounts, None)?;
+            }
+            StakeDepositInterceptorInstruction::ClaimPoolTokensPartial(args) => {
+                Self::process_claim_pool_tokens(program_id, accounts, Some(args.amount))?;
+            }
+            StakeDepositInterceptorInstruction::SplitDepositReceipt(args) => {
+                Self::process_split_deposit_receipt(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::MergeDepositReceipts => {
+                Self::process_merge_deposit_receipts(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::DepositSol(args) => {
+                Self::process_deposit_sol(program_id, accounts, args, None)?;
+            }
+            StakeDepositInterceptorInstruction::DepositSolWithSlippage(args) => {
+                let deposit_sol_args = DepositSolArgs {
+                    owner: args.owner,
+                    lamports: args.lamports,
+                };
+                Self::process_deposit_sol(
+                    program_id,
+                    accounts,
+                    deposit_sol_args,
+                    Some(args.minimum_pool_tokens_out),
+                )?;
+            }
+            StakeDepositInterceptorInstruction::AddFeeExemption(args) => {
+                Self::process_add_fee_exemption(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::RemoveFeeExemption => {
+                Self::process_remove_fee_exemption(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::ProposeAuthority => {
+                Self::process_propose_authority(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::AcceptAuthority => {
+                Self::process_accept_authority(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::CancelAuthorityTransfer => {
+                Self::process_cancel_authority_transfer(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::ApplyPendingParameters => {
+                Self::process_apply_pending_parameters(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::SetFeeRecipients(args) => {
+                Self::process_set_fee_recipients(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::SweepFees => {
+                Self::process_sweep_fees(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::CloseStakePoolDepositStakeAuthority => {
+                Self::process_close_stake_pool_deposit_stake_authority(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::RecoverExcessVaultTokens => {
+                Self::process_recover_excess_vault_tokens(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::SetPauseState(args) => {
+                Self::process_set_pause_state(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::ClaimAndWithdrawStake(args) => {
+                Self::process_claim_and_withdraw_stake(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::ClaimAndWithdrawSol(args) => {
+                Self::process_claim_and_withdraw_sol(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::TokenizeDepositReceipt => {
+                Self::process_tokenize_deposit_receipt(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::ClaimTokenizedDepositReceipt => {
+                Self::process_claim_tokenized_deposit_receipt(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::MigrateAccount => {
+                Self::process_migrate_account(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::RegisterStakePoolDepositStakeAuthority => {
+                Self::process_register_stake_pool_deposit_stake_authority(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::DeregisterStakePoolDepositStakeAuthority => {
+                Self::process_deregister_stake_pool_deposit_stake_authority(program_id, accounts)?;
+            }
+            StakeDepositInterceptorInstruction::DepositStakeTokenized(args) => {
+                Self::process_deposit_stake(program_id, accounts, args, None, true)?;
+            }
+            StakeDepositInterceptorInstruction::InitStakePoolDepositStakeAuthorityV2(args) => {
+                Self::process_init_stake_pool_deposit_stake_authority(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args) => {
+                Self::process_update_deposit_stake_authority(program_id, accounts, args)?;
+            }
+            StakeDepositInterceptorInstruction::RefreshStakePoolRegistry => {
+                Self::process_refresh_stake_pool_registry(program_id, accounts)?;
             }
         }
         Ok(())
@@ -614,6 +3269,97 @@ fn check_account_owner(
     }
 }
 
+/// Check the StakePoolDepositStakeAuthority is owned by the given program and has a layout
+/// version it knows
+fn check_deposit_stake_authority_account(
+    account_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    check_account_owner(account_info, program_id)?;
+    StakePoolDepositStakeAuthority::check_version(&account_info.try_borrow_data()?)?;
+    Ok(())
+}
+
+/// Check the DepositReceipt is owned by the given program and has a layout version it knows
+fn check_deposit_receipt_account(
+    account_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    check_account_owner(account_info, program_id)?;
+    DepositReceipt::check_version(&account_info.try_borrow_data()?)?;
+    Ok(())
+}
+
+/// Check the accounts shared by ClaimPoolTokens and ClaimTokenizedDepositReceipt against the
+/// DepositReceipt and its StakePoolDepositStakeAuthority, and return the destination token
+/// account.
+#[allow(clippy::too_many_arguments)]
+fn check_claim_accounts(
+    program_id: &Pubkey,
+    deposit_receipt_info: &AccountInfo,
+    deposit_receipt: &DepositReceipt,
+    deposit_stake_authority_info: &AccountInfo,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    vault_token_account_info: &AccountInfo,
+    destination_token_account_info: &AccountInfo,
+    pool_mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+    rent_payer_info: &AccountInfo,
+    in_cool_down: bool,
+) -> Result<Account, ProgramError> {
+    // Validate: StakePoolDepositStakeAuthority PDA is correct
+    check_deposit_stake_authority_address(
+        program_id,
+        deposit_stake_authority_info.key,
+        deposit_stake_authority,
+    )?;
+
+    // Validate: DepositReceipt address must match expected PDA
+    check_deposit_receipt_address(program_id, deposit_receipt_info.key, deposit_receipt)?;
+
+    // Validate: DepositReceipt's rent is refunded to whoever paid it
+    if rent_payer_info.key != &deposit_receipt.rent_refund_destination() {
+        return Err(StakeDepositInterceptorError::InvalidRentPayer.into());
+    }
+
+    // Validate: StakePoolDepositStakeAuthority must match the same during creation of DepositReceipt
+    if deposit_stake_authority_info.key != &deposit_receipt.stake_pool_deposit_stake_authority {
+        return Err(StakeDepositInterceptorError::InvalidStakePoolDepositStakeAuthority.into());
+    }
+
+    // Validate: claims during the cool down must not be paused. Claims after the cool down are
+    // always allowed so pool tokens cannot be frozen.
+    if in_cool_down && deposit_stake_authority.early_claims_paused() {
+        return Err(StakeDepositInterceptorError::Paused.into());
+    }
+
+    // Validate: Vault token account must match that of the `StakePoolDepositStakeAuthority`
+    if &deposit_stake_authority.vault != vault_token_account_info.key {
+        return Err(StakeDepositInterceptorError::InvalidVault.into());
+    }
+
+    // Validate: Pool mint should match that of the `StakePoolDepositStakeAuthority`, which is the StakePool's mint
+    if &deposit_stake_authority.pool_mint != pool_mint_info.key {
+        return Err(StakeDepositInterceptorError::InvalidPoolMint.into());
+    }
+
+    // Validate: Token program must be the pool mint's token program
+    if pool_mint_info.owner != token_program_info.key {
+        return Err(StakeDepositInterceptorError::InvalidTokenProgram.into());
+    }
+
+    let destination_token_account = unpack_token_account(destination_token_account_info)?;
+
+    // Validate: Destination token account must hold the pool mint and cannot be the vault
+    if &destination_token_account.mint != pool_mint_info.key
+        || destination_token_account_info.key == vault_token_account_info.key
+    {
+        return Err(StakeDepositInterceptorError::InvalidDestinationTokenAccount.into());
+    }
+
+    Ok(destination_token_account)
+}
+
 /// Check system program address
 fn check_system_program(program_id: &Pubkey) -> Result<(), ProgramError> {
     if *program_id != system_program::id() {
@@ -706,6 +3452,247 @@ fn create_pda_account<'a>(
     }
 }
 
+/// Create and initialize a DepositReceipt for `pool_tokens_minted` that were deposited in
+/// the vault, snapshotting the StakePoolDepositStakeAuthority's fee parameters with the given
+/// `initial_fee_bps`.
+fn create_deposit_receipt<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    deposit_receipt_info: &AccountInfo<'a>,
+    stake_pool_info: &AccountInfo<'a>,
+    base_info: &AccountInfo<'a>,
+    deposit_stake_authority_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    owner: &Pubkey,
+    initial_fee_bps: PodU32,
+    pool_tokens_minted: u64,
+) -> Result<(), ProgramError> {
+    let rent = Rent::get()?;
+    let clock = Clock::get()?;
+
+    let (deposit_receipt_pda, bump_seed) =
+        derive_stake_deposit_receipt(program_id, stake_pool_info.key, base_info.key);
+
+    // Validate: DepositReceipt should be canonical PDA
+    if deposit_receipt_pda != *deposit_receipt_info.key {
+        return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+    }
+
+    let pda_seeds = [
+        DEPOSIT_RECEIPT,
+        &stake_pool_info.key.to_bytes(),
+        &base_info.key.to_bytes(),
+        &[bump_seed],
+    ];
+    // Create and initialize the DepositReceipt account
+    create_pda_account(
+        payer_info,
+        &rent,
+        DepositReceipt::LEN,
+        program_id,
+        system_program_info,
+        deposit_receipt_info,
+        &pda_seeds,
+    )?;
+
+    let mut deposit_receipt_data = deposit_receipt_info.try_borrow_mut_data()?;
+    deposit_receipt_data[0] = DepositReceipt::DISCRIMINATOR;
+    let deposit_receipt =
+        DepositReceipt::try_from_account_data_mut(&mut deposit_receipt_data).unwrap();
+
+    deposit_receipt.base = *base_info.key;
+    deposit_receipt.owner = *owner;
+    deposit_receipt.stake_pool = *stake_pool_info.key;
+    deposit_receipt.stake_pool_deposit_stake_authority = *deposit_stake_authority_info.key;
+    deposit_receipt.deposit_time = clock.unix_timestamp.unsigned_abs().into();
+    deposit_receipt.lst_amount = pool_tokens_minted.into();
+    deposit_receipt.cool_down_seconds = deposit_stake_authority.cool_down_seconds;
+    deposit_receipt.initial_fee_bps = initial_fee_bps;
+    deposit_receipt.fee_curve = deposit_stake_authority.fee_curve;
+    deposit_receipt.bump_seed = bump_seed;
+    deposit_receipt.rent_payer = *payer_info.key;
+    deposit_receipt.version = DepositReceipt::CURRENT_VERSION;
+
+    StakeDepositInterceptorEvent::Deposit {
+        deposit_receipt: deposit_receipt_pda,
+        deposit_stake_authority: *deposit_stake_authority_info.key,
+        owner: *owner,
+        pool_tokens_minted,
+        deposit_time: deposit_receipt.deposit_time.into(),
+        initial_fee_bps: initial_fee_bps.into(),
+    }
+    .emit();
+
+    Ok(())
+}
+
+/// Returns the initial fee rate for a DepositReceipt owned by `owner`. When a FeeExemption for
+/// the owner is supplied, the lower of its rate and the StakePoolDepositStakeAuthority's is used.
+/// The owner must have signed to use their FeeExemption, since the owner is only an instruction
+/// argument and anyone could otherwise deposit at the reduced rate by naming an exempt owner.
+fn fee_exemption_initial_fee_bps(
+    program_id: &Pubkey,
+    fee_exemption_info: Option<&AccountInfo>,
+    deposit_stake_authority_info: &AccountInfo,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    owner: &Pubkey,
+    owner_signed: bool,
+) -> Result<PodU32, ProgramError> {
+    let fee_exemption_info = match fee_exemption_info {
+        // An uninitialized FeeExemption PDA means the owner has no exemption
+        Some(fee_exemption_info) if fee_exemption_info.owner == program_id => fee_exemption_info,
+        _ => return Ok(deposit_stake_authority.inital_fee_bps),
+    };
+
+    let fee_exemption_data = fee_exemption_info.try_borrow_data()?;
+    if fee_exemption_data.first() != Some(&FeeExemption::DISCRIMINATOR) {
+        return Err(StakeDepositInterceptorError::InvalidFeeExemption.into());
+    }
+    let fee_exemption = FeeExemption::try_from_slice_unchecked(&fee_exemption_data).unwrap();
+
+    // Validate: FeeExemption PDA is correct
+    check_fee_exemption_address(program_id, fee_exemption_info.key, fee_exemption)?;
+
+    // Validate: FeeExemption was granted to the owner by this StakePoolDepositStakeAuthority
+    if fee_exemption.stake_pool_deposit_stake_authority != *deposit_stake_authority_info.key
+        || fee_exemption.depositor != *owner
+    {
+        return Err(StakeDepositInterceptorError::InvalidFeeExemption.into());
+    }
+
+    // Validate: only the exempt owner can deposit at the reduced rate
+    if !owner_signed {
+        return Err(StakeDepositInterceptorError::OwnerSignatureRequired.into());
+    }
+
+    let initial_fee_bps = u32::from(fee_exemption.initial_fee_bps)
+        .min(u32::from(deposit_stake_authority.inital_fee_bps));
+    Ok(initial_fee_bps.into())
+}
+
+/// Create the receipt mint of a DepositReceipt and mint its single token to the owner's
+/// associated token account, funded by `payer_info`. The mint authority is removed afterwards so
+/// the supply stays at 1.
+#[allow(clippy::too_many_arguments)]
+fn mint_receipt_token<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    owner_info: &AccountInfo<'a>,
+    deposit_receipt_info: &AccountInfo<'a>,
+    receipt_mint_info: &AccountInfo<'a>,
+    receipt_token_account_info: &AccountInfo<'a>,
+    receipt_token_program_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let deposit_receipt_data = deposit_receipt_info.try_borrow_data()?;
+    let deposit_receipt = DepositReceipt::try_from_account_data(&deposit_receipt_data).unwrap();
+
+    let (receipt_mint, bump_seed) = derive_receipt_mint(program_id, deposit_receipt_info.key);
+
+    // Validate: receipt mint should be canonical PDA
+    if receipt_mint != *receipt_mint_info.key {
+        return Err(StakeDepositInterceptorError::InvalidSeeds.into());
+    }
+
+    let rent = Rent::get()?;
+    let mint_space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::MintCloseAuthority])?;
+    let pda_seeds = [
+        RECEIPT_MINT,
+        deposit_receipt_info.key.as_ref(),
+        &[bump_seed],
+    ];
+    // Create the receipt mint, closable by the DepositReceipt once the token is burned
+    create_pda_account(
+        payer_info,
+        &rent,
+        mint_space,
+        receipt_token_program_info.key,
+        system_program_info,
+        receipt_mint_info,
+        &pda_seeds,
+    )?;
+    invoke(
+        &spl_token_2022::instruction::initialize_mint_close_authority(
+            receipt_token_program_info.key,
+            receipt_mint_info.key,
+            Some(deposit_receipt_info.key),
+        )?,
+        &[receipt_mint_info.clone()],
+    )?;
+    invoke(
+        &spl_token_2022::instruction::initialize_mint2(
+            receipt_token_program_info.key,
+            receipt_mint_info.key,
+            deposit_receipt_info.key,
+            None,
+            0,
+        )?,
+        &[receipt_mint_info.clone()],
+    )?;
+
+    // Create the owner's ATA and mint the single receipt token to it
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            payer_info.key,
+            owner_info.key,
+            receipt_mint_info.key,
+            receipt_token_program_info.key,
+        ),
+        &[
+            payer_info.clone(),
+            receipt_token_account_info.clone(),
+            owner_info.clone(),
+            receipt_mint_info.clone(),
+            system_program_info.clone(),
+            receipt_token_program_info.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &spl_token_2022::instruction::mint_to(
+            receipt_token_program_info.key,
+            receipt_mint_info.key,
+            receipt_token_account_info.key,
+            deposit_receipt_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            receipt_mint_info.clone(),
+            receipt_token_account_info.clone(),
+            deposit_receipt_info.clone(),
+        ],
+        &[deposit_receipt_signer_seeds!(deposit_receipt)],
+    )?;
+    // Remove the mint authority so the supply stays at 1
+    invoke_signed(
+        &spl_token_2022::instruction::set_authority(
+            receipt_token_program_info.key,
+            receipt_mint_info.key,
+            None,
+            spl_token_2022::instruction::AuthorityType::MintTokens,
+            deposit_receipt_info.key,
+            &[],
+        )?,
+        &[receipt_mint_info.clone(), deposit_receipt_info.clone()],
+        &[deposit_receipt_signer_seeds!(deposit_receipt)],
+    )?;
+
+    let mut deposit_receipt: RefMut<DepositReceipt> =
+        borrow_mut_after_cpi(deposit_receipt_info, deposit_receipt_data)?;
+    deposit_receipt.receipt_mint = receipt_mint;
+
+    StakeDepositInterceptorEvent::TokenizeDepositReceipt {
+        deposit_receipt: *deposit_receipt_info.key,
+        owner: deposit_receipt.owner,
+        receipt_mint,
+    }
+    .emit();
+
+    Ok(())
+}
+
 /// Invokes the `DepositStake` instruction for the given stake-pool program.
 fn deposit_stake_cpi<'a>(
     program_info: &AccountInfo<'a>,
@@ -789,6 +3776,245 @@ fn deposit_stake_cpi<'a>(
     ret
 }
 
+/// Invokes the `DepositSol` instruction for the given stake-pool program.
+fn deposit_sol_cpi<'a>(
+    program_info: &AccountInfo<'a>,
+    stake_pool_info: &AccountInfo<'a>,
+    stake_pool_withdraw_authority_info: &AccountInfo<'a>,
+    reserve_stake_account_info: &AccountInfo<'a>,
+    lamports_from_info: &AccountInfo<'a>,
+    pool_tokens_to_info: &AccountInfo<'a>,
+    manager_fee_account_info: &AccountInfo<'a>,
+    referrer_pool_tokens_account_info: &AccountInfo<'a>,
+    pool_mint_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_id_info: &AccountInfo<'a>,
+    sol_deposit_authority_info: &AccountInfo<'a>,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    lamports_in: u64,
+    minimum_pool_tokens_out: Option<u64>,
+) -> Result<(), ProgramError> {
+    let account_infos = vec![
+        stake_pool_info.clone(),
+        stake_pool_withdraw_authority_info.clone(),
+        reserve_stake_account_info.clone(),
+        lamports_from_info.clone(),
+        pool_tokens_to_info.clone(),
+        manager_fee_account_info.clone(),
+        referrer_pool_tokens_account_info.clone(),
+        pool_mint_info.clone(),
+        system_program_info.clone(),
+        token_program_id_info.clone(),
+        sol_deposit_authority_info.clone(),
+    ];
+    let accounts = vec![
+        AccountMeta::new(*stake_pool_info.key, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority_info.key, false),
+        AccountMeta::new(*reserve_stake_account_info.key, false),
+        AccountMeta::new(*lamports_from_info.key, true),
+        AccountMeta::new(*pool_tokens_to_info.key, false),
+        AccountMeta::new(*manager_fee_account_info.key, false),
+        AccountMeta::new(*referrer_pool_tokens_account_info.key, false),
+        AccountMeta::new(*pool_mint_info.key, false),
+        AccountMeta::new_readonly(*system_program_info.key, false),
+        AccountMeta::new_readonly(*token_program_id_info.key, false),
+        AccountMeta::new_readonly(*sol_deposit_authority_info.key, true),
+    ];
+
+    let data;
+    if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+        data = borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::DepositSolWithSlippage {
+                lamports_in,
+                minimum_pool_tokens_out,
+            },
+        )
+        .unwrap()
+    } else {
+        data = borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::DepositSol(lamports_in),
+        )
+        .unwrap()
+    }
+    let ix = Instruction {
+        program_id: *program_info.key,
+        accounts,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &account_infos,
+        &[deposit_stake_authority_signer_seeds!(
+            deposit_stake_authority
+        )],
+    )
+}
+
+/// Invokes the `WithdrawStake` instruction for the given stake-pool program, burning the pool
+/// tokens from the vault with the StakePoolDepositStakeAuthority as the transfer authority.
+fn withdraw_stake_cpi<'a>(
+    program_info: &AccountInfo<'a>,
+    stake_pool_info: &AccountInfo<'a>,
+    validator_list_storage_info: &AccountInfo<'a>,
+    stake_pool_withdraw_authority_info: &AccountInfo<'a>,
+    stake_to_split_info: &AccountInfo<'a>,
+    stake_to_receive_info: &AccountInfo<'a>,
+    user_stake_authority_info: &AccountInfo<'a>,
+    user_transfer_authority_info: &AccountInfo<'a>,
+    pool_tokens_from_info: &AccountInfo<'a>,
+    manager_fee_account_info: &AccountInfo<'a>,
+    pool_mint_info: &AccountInfo<'a>,
+    sysvar_clock_info: &AccountInfo<'a>,
+    token_program_id_info: &AccountInfo<'a>,
+    stake_program_info: &AccountInfo<'a>,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    pool_tokens_in: u64,
+    minimum_lamports_out: Option<u64>,
+) -> Result<(), ProgramError> {
+    let account_infos = vec![
+        stake_pool_info.clone(),
+        validator_list_storage_info.clone(),
+        stake_pool_withdraw_authority_info.clone(),
+        stake_to_split_info.clone(),
+        stake_to_receive_info.clone(),
+        user_stake_authority_info.clone(),
+        user_transfer_authority_info.clone(),
+        pool_tokens_from_info.clone(),
+        manager_fee_account_info.clone(),
+        pool_mint_info.clone(),
+        sysvar_clock_info.clone(),
+        token_program_id_info.clone(),
+        stake_program_info.clone(),
+    ];
+    let accounts = vec![
+        AccountMeta::new(*stake_pool_info.key, false),
+        AccountMeta::new(*validator_list_storage_info.key, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority_info.key, false),
+        AccountMeta::new(*stake_to_split_info.key, false),
+        AccountMeta::new(*stake_to_receive_info.key, false),
+        AccountMeta::new_readonly(*user_stake_authority_info.key, false),
+        AccountMeta::new_readonly(*user_transfer_authority_info.key, true),
+        AccountMeta::new(*pool_tokens_from_info.key, false),
+        AccountMeta::new(*manager_fee_account_info.key, false),
+        AccountMeta::new(*pool_mint_info.key, false),
+        AccountMeta::new_readonly(*sysvar_clock_info.key, false),
+        AccountMeta::new_readonly(*token_program_id_info.key, false),
+        AccountMeta::new_readonly(*stake_program_info.key, false),
+    ];
+
+    let data = if let Some(minimum_lamports_out) = minimum_lamports_out {
+        borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::WithdrawStakeWithSlippage {
+                pool_tokens_in,
+                minimum_lamports_out,
+            },
+        )
+        .unwrap()
+    } else {
+        borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::WithdrawStake(pool_tokens_in),
+        )
+        .unwrap()
+    };
+    let ix = Instruction {
+        program_id: *program_info.key,
+        accounts,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &account_infos,
+        &[deposit_stake_authority_signer_seeds!(
+            deposit_stake_authority
+        )],
+    )
+}
+
+/// Invokes the `WithdrawSol` instruction for the given stake-pool program, burning the pool
+/// tokens from the vault with the StakePoolDepositStakeAuthority as the transfer authority.
+fn withdraw_sol_cpi<'a>(
+    program_info: &AccountInfo<'a>,
+    stake_pool_info: &AccountInfo<'a>,
+    stake_pool_withdraw_authority_info: &AccountInfo<'a>,
+    user_transfer_authority_info: &AccountInfo<'a>,
+    pool_tokens_from_info: &AccountInfo<'a>,
+    reserve_stake_account_info: &AccountInfo<'a>,
+    lamports_to_info: &AccountInfo<'a>,
+    manager_fee_account_info: &AccountInfo<'a>,
+    pool_mint_info: &AccountInfo<'a>,
+    sysvar_clock_info: &AccountInfo<'a>,
+    sysvar_stake_history: &AccountInfo<'a>,
+    stake_program_info: &AccountInfo<'a>,
+    token_program_id_info: &AccountInfo<'a>,
+    sol_withdraw_authority_info: Option<&AccountInfo<'a>>,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    pool_tokens_in: u64,
+    minimum_lamports_out: Option<u64>,
+) -> Result<(), ProgramError> {
+    let mut account_infos = vec![
+        stake_pool_info.clone(),
+        stake_pool_withdraw_authority_info.clone(),
+        user_transfer_authority_info.clone(),
+        pool_tokens_from_info.clone(),
+        reserve_stake_account_info.clone(),
+        lamports_to_info.clone(),
+        manager_fee_account_info.clone(),
+        pool_mint_info.clone(),
+        sysvar_clock_info.clone(),
+        sysvar_stake_history.clone(),
+        stake_program_info.clone(),
+        token_program_id_info.clone(),
+    ];
+    let mut accounts = vec![
+        AccountMeta::new(*stake_pool_info.key, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority_info.key, false),
+        AccountMeta::new_readonly(*user_transfer_authority_info.key, true),
+        AccountMeta::new(*pool_tokens_from_info.key, false),
+        AccountMeta::new(*reserve_stake_account_info.key, false),
+        AccountMeta::new(*lamports_to_info.key, false),
+        AccountMeta::new(*manager_fee_account_info.key, false),
+        AccountMeta::new(*pool_mint_info.key, false),
+        AccountMeta::new_readonly(*sysvar_clock_info.key, false),
+        AccountMeta::new_readonly(*sysvar_stake_history.key, false),
+        AccountMeta::new_readonly(*stake_program_info.key, false),
+        AccountMeta::new_readonly(*token_program_id_info.key, false),
+    ];
+    if let Some(sol_withdraw_authority_info) = sol_withdraw_authority_info {
+        account_infos.push(sol_withdraw_authority_info.clone());
+        accounts.push(AccountMeta::new_readonly(
+            *sol_withdraw_authority_info.key,
+            true,
+        ));
+    }
+
+    let data = if let Some(minimum_lamports_out) = minimum_lamports_out {
+        borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::WithdrawSolWithSlippage {
+                pool_tokens_in,
+                minimum_lamports_out,
+            },
+        )
+        .unwrap()
+    } else {
+        borsh::to_vec(
+            &spl_stake_pool::instruction::StakePoolInstruction::WithdrawSol(pool_tokens_in),
+        )
+        .unwrap()
+    };
+    let ix = Instruction {
+        program_id: *program_info.key,
+        accounts,
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &account_infos,
+        &[deposit_stake_authority_signer_seeds!(
+            deposit_stake_authority
+        )],
+    )
+}
+
 /// Check the validity of the supplied deposit_stake_authority given the relevant seeds.
 pub fn check_deposit_stake_authority_address(
     program_id: &Pubkey,
@@ -819,6 +4045,36 @@ pub fn check_deposit_receipt_address(
     Ok(())
 }
 
+/// Check the validity of the supplied FeeExemption given the relevant seeds.
+pub fn check_fee_exemption_address(
+    program_id: &Pubkey,
+    fee_exemption_address: &Pubkey,
+    fee_exemption: &FeeExemption,
+) -> Result<(), ProgramError> {
+    let address =
+        Pubkey::create_program_address(fee_exemption_signer_seeds!(fee_exemption), program_id)?;
+    if address != *fee_exemption_address {
+        return Err(StakeDepositInterceptorError::InvalidFeeExemption.into());
+    }
+    Ok(())
+}
+
+/// Check the validity of the supplied StakePoolRegistry given the relevant seeds.
+pub fn check_stake_pool_registry_address(
+    program_id: &Pubkey,
+    stake_pool_registry_address: &Pubkey,
+    stake_pool_registry: &StakePoolRegistry,
+) -> Result<(), ProgramError> {
+    let address = Pubkey::create_program_address(
+        stake_pool_registry_signer_seeds!(stake_pool_registry),
+        program_id,
+    )?;
+    if address != *stake_pool_registry_address {
+        return Err(StakeDepositInterceptorError::InvalidStakePoolRegistry.into());
+    }
+    Ok(())
+}
+
 /// Transfer tokens using SPL Token or Token2022 based on the given token program.
 pub fn transfer_tokens_cpi<'a>(
     token_program: AccountInfo<'a>,
@@ -826,29 +4082,67 @@ pub fn transfer_tokens_cpi<'a>(
     mint: AccountInfo<'a>,
     destination: AccountInfo<'a>,
     authority: AccountInfo<'a>,
+    transfer_hook_accounts: &[AccountInfo<'a>],
     amount: u64,
     decimals: u8,
     deposit_stake_authority: &StakePoolDepositStakeAuthority,
 ) -> Result<(), ProgramError> {
-    let ix = spl_token_2022::instruction::transfer_checked(
+    // Resolves the extra accounts of the mint's transfer hook, if it has one
+    spl_token_2022::onchain::invoke_transfer_checked(
         token_program.key,
-        source.key,
-        mint.key,
-        destination.key,
-        authority.key,
-        &[],
+        source,
+        mint,
+        destination,
+        authority,
+        transfer_hook_accounts,
         amount,
         decimals,
-    )?;
-    invoke_signed(
-        &ix,
-        &[source, mint, destination, authority],
         &[deposit_stake_authority_signer_seeds!(
             deposit_stake_authority
         )],
     )
 }
 
+/// Unpack a SPL Token or Token-2022 token account, ignoring any extensions.
+pub fn unpack_token_account(token_account_info: &AccountInfo) -> Result<Account, ProgramError> {
+    let token_account_data = token_account_info.try_borrow_data()?;
+    Ok(StateWithExtensions::<Account>::unpack(&token_account_data)?.base)
+}
+
+/// Unpack a SPL Token or Token-2022 mint, ignoring any extensions.
+pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    let mint_data = mint_info.try_borrow_data()?;
+    Ok(StateWithExtensions::<Mint>::unpack(&mint_data)?.base)
+}
+
+/// Amount withheld by the mint's Token-2022 `TransferFeeConfig` when transferring `amount`.
+/// Zero for mints without a transfer fee.
+pub fn calculate_transfer_fee(mint_info: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ProgramError::ArithmeticOverflow),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Release the immutable borrow of a PDA's data taken before a CPI it signed and borrow the data
+/// again mutably as `T`. CPIs signed by a PDA need to borrow its AccountInfo themselves, so its
+/// data is only borrowed mutably once they have returned. Data past the current layout of `T`
+/// is ignored.
+fn borrow_mut_after_cpi<'a, T: AccountDeserialize>(
+    account_info: &'a AccountInfo,
+    data: Ref<&mut [u8]>,
+) -> Result<RefMut<'a, T>, ProgramError> {
+    drop(data);
+    let data = account_info.try_borrow_mut_data()?;
+    Ok(RefMut::map(data, |data| {
+        T::try_from_slice_unchecked_mut(&mut data[..8 + mem::size_of::<T>()]).unwrap()
+    }))
+}
+
 /// Close an account and send any leftover lamports to the destination account.
 pub fn close_account<'a>(
     source: &AccountInfo<'a>,
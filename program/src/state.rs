@@ -1,16 +1,256 @@
-use borsh::BorshSerialize;
+use std::mem;
+
+use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, Zeroable};
 use jito_bytemuck::{AccountDeserialize, Discriminator};
-use solana_program::pubkey::Pubkey;
-use spl_pod::primitives::{PodU32, PodU64};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use spl_pod::primitives::{PodU16, PodU32, PodU64};
+
+use crate::error::StakeDepositInterceptorError;
 /// Discriminators for accounts
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StakeDepositInterceptorDiscriminators {
     DepositStakeAuthority = 1,
     DepositReceipt = 2,
+    FeeExemption = 3,
+    StakePoolRegistry = 4,
+}
+
+/// Maximum number of StakePoolDepositStakeAuthority accounts a StakePoolRegistry can record.
+pub const MAX_REGISTERED_DEPOSIT_STAKE_AUTHORITIES: usize = 8;
+
+/// Maximum number of breakpoints a piecewise linear FeeCurve may define.
+pub const MAX_FEE_CURVE_BREAKPOINTS: usize = 4;
+
+/// Denominator for FeeCurve breakpoint values, which are expressed in bps.
+pub const FEE_CURVE_BPS_DENOMINATOR: u16 = 10_000;
+
+/// Maximum number of fee recipients in addition to the `fee_wallet`.
+pub const MAX_FEE_RECIPIENTS: usize = 3;
+
+/// Maximum number of half lives before an exponential FeeCurve is treated as 0.
+const MAX_FEE_CURVE_HALVINGS: u64 = 32;
+
+/// Shapes a FeeCurve can take while decaying from `initial_fee_bps` to 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeCurveType {
+    Linear = 0,
+    Step = 1,
+    Exponential = 2,
+    PiecewiseLinear = 3,
+}
+
+/// A single point of a piecewise linear FeeCurve.
+#[repr(C)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, BorshSerialize, BorshDeserialize,
+)]
+pub struct FeeCurveBreakpoint {
+    /// Time elapsed since deposit, in bps of `cool_down_seconds`
+    pub elapsed_bps: PodU16,
+    /// Fee rate at this point in time, in bps of `initial_fee_bps`
+    pub fee_ratio_bps: PodU16,
+}
+
+impl FeeCurveBreakpoint {
+    pub fn new(elapsed_bps: u16, fee_ratio_bps: u16) -> Self {
+        Self {
+            elapsed_bps: elapsed_bps.into(),
+            fee_ratio_bps: fee_ratio_bps.into(),
+        }
+    }
+}
+
+/// Shape of the fee decay over the cool down period, given as instruction input.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum FeeCurveConfig {
+    /// Fee decays linearly from `initial_fee_bps` to 0
+    Linear,
+    /// Fee drops in `steps` equal decrements, each lasting `cool_down_seconds / steps`
+    Step { steps: u32 },
+    /// Fee halves every `half_life_seconds` and is 0 once the cool down ends
+    Exponential { half_life_seconds: u32 },
+    /// Fee is linearly interpolated between the given breakpoints. The curve implicitly
+    /// starts at 100% of `initial_fee_bps` and ends at 0 when the cool down ends.
+    PiecewiseLinear {
+        breakpoints: Vec<FeeCurveBreakpoint>,
+    },
+}
+
+/// What happens when a DepositReceipt's owner changes, set per StakePoolDepositStakeAuthority.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum ReceiptTransferPolicy {
+    /// Owners can be changed at any time
+    #[default]
+    Allow,
+    /// Owners can only be changed once the cool down has ended
+    ForbidDuringCoolDown,
+    /// Changing the owner restarts the cool down by resetting `deposit_time`
+    ResetDepositTime,
+}
+
+impl TryFrom<u8> for ReceiptTransferPolicy {
+    type Error = StakeDepositInterceptorError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Allow),
+            1 => Ok(Self::ForbidDuringCoolDown),
+            2 => Ok(Self::ResetDepositTime),
+            _ => Err(StakeDepositInterceptorError::InvalidReceiptTransferPolicy),
+        }
+    }
+}
+
+/// Fee decay curve stored on the StakePoolDepositStakeAuthority and snapshotted
+/// into each DepositReceipt. A zeroed FeeCurve is `Linear`.
+#[repr(C)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, BorshSerialize, BorshDeserialize,
+)]
+pub struct FeeCurve {
+    /// FeeCurveType of the curve
+    pub curve_type: u8,
+    /// Number of steps for `Step` or half life in seconds for `Exponential`
+    pub param: PodU32,
+    /// Number of valid `breakpoints` for `PiecewiseLinear`
+    pub breakpoint_count: u8,
+    /// Breakpoints for `PiecewiseLinear`
+    pub breakpoints: [FeeCurveBreakpoint; MAX_FEE_CURVE_BREAKPOINTS],
+}
+
+impl FeeCurve {
+    /// Validate the given FeeCurveConfig and convert it to its stored representation.
+    pub fn from_config(config: &FeeCurveConfig) -> Result<Self, StakeDepositInterceptorError> {
+        let mut fee_curve = Self::default();
+        match config {
+            FeeCurveConfig::Linear => {}
+            FeeCurveConfig::Step { steps } => {
+                if *steps == 0 {
+                    return Err(StakeDepositInterceptorError::InvalidFeeCurve);
+                }
+                fee_curve.curve_type = FeeCurveType::Step as u8;
+                fee_curve.param = (*steps).into();
+            }
+            FeeCurveConfig::Exponential { half_life_seconds } => {
+                if *half_life_seconds == 0 {
+                    return Err(StakeDepositInterceptorError::InvalidFeeCurve);
+                }
+                fee_curve.curve_type = FeeCurveType::Exponential as u8;
+                fee_curve.param = (*half_life_seconds).into();
+            }
+            FeeCurveConfig::PiecewiseLinear { breakpoints } => {
+                if breakpoints.is_empty() || breakpoints.len() > MAX_FEE_CURVE_BREAKPOINTS {
+                    return Err(StakeDepositInterceptorError::InvalidFeeCurve);
+                }
+                // Breakpoints must be strictly inside the cool down period, ordered by time,
+                // and the fee may never increase.
+                let mut previous = FeeCurveBreakpoint::new(0, FEE_CURVE_BPS_DENOMINATOR);
+                for breakpoint in breakpoints.iter() {
+                    let elapsed_bps = u16::from(breakpoint.elapsed_bps);
+                    let fee_ratio_bps = u16::from(breakpoint.fee_ratio_bps);
+                    if elapsed_bps <= u16::from(previous.elapsed_bps)
+                        || elapsed_bps >= FEE_CURVE_BPS_DENOMINATOR
+                        || fee_ratio_bps > u16::from(previous.fee_ratio_bps)
+                    {
+                        return Err(StakeDepositInterceptorError::InvalidFeeCurve);
+                    }
+                    previous = *breakpoint;
+                }
+                fee_curve.curve_type = FeeCurveType::PiecewiseLinear as u8;
+                fee_curve.breakpoint_count = breakpoints.len() as u8;
+                fee_curve.breakpoints[..breakpoints.len()].copy_from_slice(breakpoints);
+            }
+        }
+        Ok(fee_curve)
+    }
+
+    /// Convert the stored FeeCurve back to its FeeCurveConfig.
+    pub fn config(&self) -> FeeCurveConfig {
+        match self.curve_type() {
+            FeeCurveType::Linear => FeeCurveConfig::Linear,
+            FeeCurveType::Step => FeeCurveConfig::Step {
+                steps: self.param.into(),
+            },
+            FeeCurveType::Exponential => FeeCurveConfig::Exponential {
+                half_life_seconds: self.param.into(),
+            },
+            FeeCurveType::PiecewiseLinear => FeeCurveConfig::PiecewiseLinear {
+                breakpoints: self.breakpoints[..self.breakpoint_count as usize].to_vec(),
+            },
+        }
+    }
+
+    /// The FeeCurveType of the curve. Unknown values fall back to `Linear`.
+    pub fn curve_type(&self) -> FeeCurveType {
+        match self.curve_type {
+            1 => FeeCurveType::Step,
+            2 => FeeCurveType::Exponential,
+            3 => FeeCurveType::PiecewiseLinear,
+            _ => FeeCurveType::Linear,
+        }
+    }
+
+    /// Portion of the initial fee rate still owed `elapsed_seconds` into a cool down period of
+    /// `cool_down_seconds`, returned as a (numerator, denominator) pair. The caller must ensure
+    /// `elapsed_seconds < cool_down_seconds`.
+    pub fn fee_ratio(&self, elapsed_seconds: u64, cool_down_seconds: u64) -> (u128, u128) {
+        let cool_down_seconds = cool_down_seconds as u128;
+        let elapsed_seconds = elapsed_seconds as u128;
+        let time_left = cool_down_seconds - elapsed_seconds;
+        match self.curve_type() {
+            FeeCurveType::Linear => (time_left, cool_down_seconds),
+            FeeCurveType::Step => {
+                let steps = u32::from(self.param) as u128;
+                // The fee stays at the rate of the step that started the current interval.
+                let steps_left = time_left
+                    .checked_mul(steps)
+                    .expect("overflow")
+                    .div_ceil(cool_down_seconds);
+                (steps_left, steps)
+            }
+            FeeCurveType::Exponential => {
+                let half_life = u32::from(self.param) as u128;
+                let halvings = elapsed_seconds / half_life;
+                if halvings >= MAX_FEE_CURVE_HALVINGS as u128 {
+                    return (0, 1);
+                }
+                // Linearly interpolate between 2^-k and 2^-(k+1) within the current half life.
+                let remainder = elapsed_seconds % half_life;
+                (half_life * 2 - remainder, (half_life * 2) << halvings)
+            }
+            FeeCurveType::PiecewiseLinear => {
+                let denominator = FEE_CURVE_BPS_DENOMINATOR as u128;
+                let mut start_time = 0u128;
+                let mut start_ratio = denominator;
+                let breakpoints = self.breakpoints[..self.breakpoint_count as usize]
+                    .iter()
+                    .map(|breakpoint| {
+                        (
+                            u16::from(breakpoint.elapsed_bps) as u128 * cool_down_seconds
+                                / denominator,
+                            u16::from(breakpoint.fee_ratio_bps) as u128,
+                        )
+                    })
+                    .chain(std::iter::once((cool_down_seconds, 0)));
+                for (end_time, end_ratio) in breakpoints {
+                    if elapsed_seconds < end_time {
+                        let duration = end_time - start_time;
+                        let numerator = start_ratio * duration
+                            - (start_ratio - end_ratio) * (elapsed_seconds - start_time);
+                        return (numerator, denominator * duration);
+                    }
+                    start_time = end_time;
+                    start_ratio = end_ratio;
+                }
+                (0, 1)
+            }
+        }
+    }
 }
 
-/// Variables to construct linearly decaying fees over some period of time.
+/// Variables to construct time decaying fees over some period of time.
 #[derive(shank::ShankAccount)]
 #[repr(C)]
 #[derive(Clone, Copy, AccountDeserialize, Debug, PartialEq, Pod, Zeroable)]
@@ -35,8 +275,52 @@ pub struct StakePoolDepositStakeAuthority {
     pub fee_wallet: Pubkey,
     /// Bump seed for derivation
     pub bump_seed: u8,
-    // reserved bytes
-    reserved: [u8; 256],
+    /// Curve the fee decays along during `cool_down_seconds`
+    pub fee_curve: FeeCurve,
+    /// Proposed new authority that must sign `AcceptAuthority`. Default when no transfer is pending.
+    pub pending_authority: Pubkey,
+    /// Timestamp after which the pending parameters can be applied. 0 when none are pending.
+    pub pending_parameters_effective_at: PodU64,
+    /// Queued `cool_down_seconds`
+    pub pending_cool_down_seconds: PodU64,
+    /// Queued `inital_fee_bps`
+    pub pending_initial_fee_bps: PodU32,
+    /// Queued `fee_wallet`
+    pub pending_fee_wallet: Pubkey,
+    /// Queued `fee_curve`
+    pub pending_fee_curve: FeeCurve,
+    /// Recipients of a share of the fees. The `fee_wallet` receives whatever is left.
+    pub fee_recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    /// Fees charged on claims that are held by the vault until swept
+    pub accrued_fees: PodU64,
+    /// Number of DepositReceipts created through this account that have not been closed
+    pub outstanding_receipt_count: PodU64,
+    /// Pool tokens held by the vault that are owed to DepositReceipt holders
+    pub total_outstanding_lst: PodU64,
+    /// 1 when `total_outstanding_lst` includes every DepositReceipt, i.e. the account was created
+    /// after it was introduced. Excess vault tokens can only be recovered when set.
+    pub outstanding_lst_tracked: u8,
+    /// Bit flags of the paused operations, see `DEPOSITS_PAUSED` and `EARLY_CLAIMS_PAUSED`
+    pub pause_flags: u8,
+    /// Layout version, see `CURRENT_VERSION`. Version 0 accounts end right before it. Fields
+    /// added by later versions are appended after it.
+    pub version: u8,
+    /// Maximum pool tokens minted by a single deposit. 0 when there is no limit.
+    pub max_deposit_lst_per_receipt: PodU64,
+    /// Maximum `total_outstanding_lst` after a deposit. 0 when there is no limit.
+    pub max_total_outstanding_lst: PodU64,
+    /// Minimum pool tokens minted by a single deposit. 0 when there is no minimum.
+    pub min_deposit_lst: PodU64,
+    /// Lamports of a closed DepositReceipt's rent paid to the keeper that cranks a permissionless
+    /// claim after the cool down. The rest is refunded to the rent payer.
+    pub keeper_tip_lamports: PodU64,
+    /// 1 when the DepositReceipt owner must sign `DepositStake`, so a relayer of an authorized
+    /// stake account cannot name itself as the owner.
+    pub require_owner_signature: u8,
+    /// `ReceiptTransferPolicy` applied when a DepositReceipt's owner changes
+    pub receipt_transfer_policy: u8,
+    /// Number of FeeExemptions granted through this account that have not been removed
+    pub fee_exemption_count: PodU64,
 }
 
 impl Discriminator for StakePoolDepositStakeAuthority {
@@ -44,10 +328,282 @@ impl Discriminator for StakePoolDepositStakeAuthority {
 }
 
 impl StakePoolDepositStakeAuthority {
+    /// Size of the account data of the current layout, including the discriminator
+    pub const LEN: usize = 8 + mem::size_of::<Self>();
+
+    /// Offset of `version` in the account data. Version 0 accounts, which ended with
+    /// `pause_flags`, are exactly this long.
+    pub const VERSION_OFFSET: usize = 501;
+
+    /// Version of the layout written by this program. Accounts with an older version must be
+    /// upgraded with `MigrateAccount`.
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Layout version of the account data.
+    pub fn version(data: &[u8]) -> u8 {
+        data.get(Self::VERSION_OFFSET).copied().unwrap_or(0)
+    }
+
+    /// Check the account data holds the current layout. Data past `LEN` is ignored.
+    pub fn check_version(data: &[u8]) -> Result<(), StakeDepositInterceptorError> {
+        let version = Self::version(data);
+        if version > Self::CURRENT_VERSION {
+            return Err(StakeDepositInterceptorError::UnsupportedAccountVersion);
+        }
+        if version < Self::CURRENT_VERSION || data.len() < Self::LEN {
+            return Err(StakeDepositInterceptorError::AccountMigrationRequired);
+        }
+        Ok(())
+    }
+
+    /// Deserialize the current layout from the account data, ignoring any data past `LEN`.
+    pub fn try_from_account_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::try_from_slice_unchecked(
+            data.get(..Self::LEN)
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )
+    }
+
+    /// Mutable version of `try_from_account_data`.
+    pub fn try_from_account_data_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::try_from_slice_unchecked_mut(
+            data.get_mut(..Self::LEN)
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )
+    }
+
     /// Check whether the StakePoolDepositStakeAuthority account has been initialized
     pub fn is_initialized(&self) -> bool {
         self.authority != Pubkey::default()
     }
+
+    /// Minimum time between queueing fee parameter changes and when they can be applied.
+    pub const MIN_PARAMETER_UPDATE_DELAY_SECONDS: i64 = 86_400;
+
+    /// `pause_flags` bit rejecting DepositStake and DepositSol.
+    pub const DEPOSITS_PAUSED: u8 = 1 << 0;
+
+    /// `pause_flags` bit rejecting claims during the cool down. Claims after the cool down
+    /// can never be paused.
+    pub const EARLY_CLAIMS_PAUSED: u8 = 1 << 1;
+
+    /// Maximum share (in bps) of a DepositReceipt's rent paid as the keeper tip, so the rent payer
+    /// is always refunded at least half of it.
+    pub const MAX_KEEPER_TIP_BPS: u64 = 5_000;
+
+    /// Largest keeper tip allowed for a DepositReceipt holding `receipt_lamports`.
+    pub fn max_keeper_tip(receipt_lamports: u64) -> u64 {
+        // Never more than `receipt_lamports`, so the cast cannot truncate
+        (receipt_lamports as u128 * Self::MAX_KEEPER_TIP_BPS as u128 / 10_000) as u64
+    }
+
+    /// Keeper tip paid when a permissionless claim closes a DepositReceipt holding
+    /// `receipt_lamports`, capped at `MAX_KEEPER_TIP_BPS` of them.
+    pub fn keeper_tip(&self, receipt_lamports: u64) -> u64 {
+        u64::from(self.keeper_tip_lamports).min(Self::max_keeper_tip(receipt_lamports))
+    }
+
+    /// Check whether deposits are paused
+    pub fn deposits_paused(&self) -> bool {
+        self.pause_flags & Self::DEPOSITS_PAUSED != 0
+    }
+
+    /// Check whether the DepositReceipt owner must sign `DepositStake`
+    pub fn owner_signature_required(&self) -> bool {
+        self.require_owner_signature != 0
+    }
+
+    /// Policy applied when a DepositReceipt's owner changes
+    pub fn receipt_transfer_policy(
+        &self,
+    ) -> Result<ReceiptTransferPolicy, StakeDepositInterceptorError> {
+        ReceiptTransferPolicy::try_from(self.receipt_transfer_policy)
+    }
+
+    /// Check whether claims during the cool down are paused
+    pub fn early_claims_paused(&self) -> bool {
+        self.pause_flags & Self::EARLY_CLAIMS_PAUSED != 0
+    }
+
+    /// Replace the paused operations
+    pub fn set_pause_state(&mut self, deposits_paused: bool, early_claims_paused: bool) {
+        self.pause_flags = 0;
+        if deposits_paused {
+            self.pause_flags |= Self::DEPOSITS_PAUSED;
+        }
+        if early_claims_paused {
+            self.pause_flags |= Self::EARLY_CLAIMS_PAUSED;
+        }
+    }
+
+    /// Check whether an authority transfer has been proposed and not yet accepted or cancelled
+    pub fn has_pending_authority(&self) -> bool {
+        self.pending_authority != Pubkey::default()
+    }
+
+    /// Check whether fee parameter changes are queued
+    pub fn has_pending_parameters(&self) -> bool {
+        u64::from(self.pending_parameters_effective_at) != 0
+    }
+
+    /// Replace the fee recipients. Each recipient must have a non-zero share and the shares
+    /// cannot add up to more than 100%.
+    pub fn set_fee_recipients(
+        &mut self,
+        fee_recipients: &[FeeRecipient],
+    ) -> Result<(), StakeDepositInterceptorError> {
+        if fee_recipients.len() > MAX_FEE_RECIPIENTS {
+            return Err(StakeDepositInterceptorError::InvalidFeeRecipients);
+        }
+        let mut total_fee_bps: u32 = 0;
+        for fee_recipient in fee_recipients {
+            let fee_bps = u16::from(fee_recipient.fee_bps);
+            if !fee_recipient.is_set() || fee_bps == 0 {
+                return Err(StakeDepositInterceptorError::InvalidFeeRecipients);
+            }
+            total_fee_bps += u32::from(fee_bps);
+        }
+        if total_fee_bps > u32::from(FEE_CURVE_BPS_DENOMINATOR) {
+            return Err(StakeDepositInterceptorError::InvalidFeeRecipients);
+        }
+
+        self.fee_recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+        self.fee_recipients[..fee_recipients.len()].copy_from_slice(fee_recipients);
+        Ok(())
+    }
+
+    /// Split `fee_amount` between the fee recipients. Returns the share of each entry in
+    /// `fee_recipients` and the remainder owed to the `fee_wallet`.
+    pub fn split_fee(&self, fee_amount: u64) -> ([u64; MAX_FEE_RECIPIENTS], u64) {
+        let mut shares = [0; MAX_FEE_RECIPIENTS];
+        let mut remainder = fee_amount;
+        for (share, fee_recipient) in shares.iter_mut().zip(self.fee_recipients.iter()) {
+            if !fee_recipient.is_set() {
+                continue;
+            }
+            // Rounds down, the rounding dust goes to the `fee_wallet`
+            *share = (u128::from(fee_amount) * u128::from(u16::from(fee_recipient.fee_bps))
+                / u128::from(FEE_CURVE_BPS_DENOMINATOR)) as u64;
+            remainder -= *share;
+        }
+        (shares, remainder)
+    }
+
+    /// Record a DepositReceipt created through this StakePoolDepositStakeAuthority.
+    pub fn open_deposit_receipt(&mut self) {
+        self.outstanding_receipt_count = u64::from(self.outstanding_receipt_count)
+            .checked_add(1)
+            .expect("overflow")
+            .into();
+    }
+
+    /// Record a closed DepositReceipt. Saturates at zero, as DepositReceipts created before the
+    /// count was tracked are not included in it.
+    pub fn close_deposit_receipt(&mut self) {
+        self.outstanding_receipt_count = u64::from(self.outstanding_receipt_count)
+            .saturating_sub(1)
+            .into();
+    }
+
+    /// Record a FeeExemption granted through this StakePoolDepositStakeAuthority.
+    pub fn add_fee_exemption(&mut self) {
+        self.fee_exemption_count = u64::from(self.fee_exemption_count)
+            .checked_add(1)
+            .expect("overflow")
+            .into();
+    }
+
+    /// Record a removed FeeExemption. Saturates at zero, as FeeExemptions granted before the
+    /// count was tracked are not included in it.
+    pub fn remove_fee_exemption(&mut self) {
+        self.fee_exemption_count = u64::from(self.fee_exemption_count).saturating_sub(1).into();
+    }
+
+    /// Record pool tokens deposited into the vault for a DepositReceipt.
+    pub fn add_outstanding_lst(&mut self, lst_amount: u64) {
+        self.total_outstanding_lst = u64::from(self.total_outstanding_lst)
+            .checked_add(lst_amount)
+            .expect("overflow")
+            .into();
+    }
+
+    /// Record pool tokens claimed from a DepositReceipt, including any fee. Saturates at zero
+    /// like `close_deposit_receipt`.
+    pub fn remove_outstanding_lst(&mut self, lst_amount: u64) {
+        self.total_outstanding_lst = u64::from(self.total_outstanding_lst)
+            .saturating_sub(lst_amount)
+            .into();
+    }
+
+    /// Check that a deposit minting `pool_tokens_minted` stays within the deposit limits.
+    pub fn check_deposit_limits(
+        &self,
+        pool_tokens_minted: u64,
+    ) -> Result<(), StakeDepositInterceptorError> {
+        if pool_tokens_minted < u64::from(self.min_deposit_lst) {
+            return Err(StakeDepositInterceptorError::DepositBelowMinimum);
+        }
+        let max_deposit_lst_per_receipt = u64::from(self.max_deposit_lst_per_receipt);
+        if max_deposit_lst_per_receipt != 0 && pool_tokens_minted > max_deposit_lst_per_receipt {
+            return Err(StakeDepositInterceptorError::MaxDepositPerReceiptExceeded);
+        }
+        let max_total_outstanding_lst = u64::from(self.max_total_outstanding_lst);
+        if max_total_outstanding_lst != 0
+            && u64::from(self.total_outstanding_lst).saturating_add(pool_tokens_minted)
+                > max_total_outstanding_lst
+        {
+            return Err(StakeDepositInterceptorError::MaxTotalOutstandingLstExceeded);
+        }
+        Ok(())
+    }
+
+    /// Amount of the vault's pool tokens that is neither owed to DepositReceipt holders nor
+    /// accrued as fees.
+    pub fn excess_vault_amount(&self, vault_amount: u64) -> u64 {
+        vault_amount
+            .saturating_sub(self.total_outstanding_lst.into())
+            .saturating_sub(self.accrued_fees.into())
+    }
+
+    /// Move the queued fee parameters into effect and clear the queue.
+    pub fn apply_pending_parameters(&mut self) {
+        self.cool_down_seconds = self.pending_cool_down_seconds;
+        self.inital_fee_bps = self.pending_initial_fee_bps;
+        self.fee_wallet = self.pending_fee_wallet;
+        self.fee_curve = self.pending_fee_curve;
+        self.pending_parameters_effective_at = PodU64::from(0);
+        self.pending_cool_down_seconds = PodU64::from(0);
+        self.pending_initial_fee_bps = PodU32::from(0);
+        self.pending_fee_wallet = Pubkey::default();
+        self.pending_fee_curve = FeeCurve::default();
+    }
+}
+
+/// A recipient of a share of the fees, in addition to the StakePoolDepositStakeAuthority's
+/// `fee_wallet`.
+#[repr(C)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, BorshSerialize, BorshDeserialize,
+)]
+pub struct FeeRecipient {
+    /// Owner of the token account receiving the share
+    pub wallet: Pubkey,
+    /// Share of every fee, in bps
+    pub fee_bps: PodU16,
+}
+
+impl FeeRecipient {
+    pub fn new(wallet: Pubkey, fee_bps: u16) -> Self {
+        Self {
+            wallet,
+            fee_bps: fee_bps.into(),
+        }
+    }
+
+    /// Check whether the slot holds a recipient
+    pub fn is_set(&self) -> bool {
+        self.wallet != Pubkey::default()
+    }
 }
 
 /// Representation of some amount of claimable LST
@@ -73,8 +629,18 @@ pub struct DepositReceipt {
     pub initial_fee_bps: PodU32,
     /// Bump seed for derivation
     pub bump_seed: u8,
+    /// Fee curve at time of deposit
+    pub fee_curve: FeeCurve,
+    /// Account that paid the rent of the DepositReceipt and is refunded when it is closed.
+    /// Default for DepositReceipts created before it was recorded, which refund the owner.
+    pub rent_payer: Pubkey,
+    /// 1-of-1 token representing the DepositReceipt. When set, claims are authorized by burning
+    /// the token rather than by the `owner`. Default for DepositReceipts that are not tokenized.
+    pub receipt_mint: Pubkey,
+    /// Layout version, see `CURRENT_VERSION`. 0 for accounts created before it was recorded.
+    pub version: u8,
     // reserved bytes
-    reserved: [u8; 256],
+    reserved: [u8; 169],
 }
 
 impl Discriminator for DepositReceipt {
@@ -86,9 +652,80 @@ impl DepositReceipt {
     /// maximum allowed fee as the fee cannot exceed 100%.
     pub const FEE_BPS_DENOMINATOR: u32 = 10_000;
 
+    /// Size of the account data of the current layout, including the discriminator
+    pub const LEN: usize = 8 + mem::size_of::<Self>();
+
+    /// Version of the layout written by this program. Version 0 DepositReceipts have the same
+    /// size and only ever used their reserved bytes as zeroes, so they are still supported.
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Layout version of the account data. 0 when it is too short to record one.
+    pub fn version(data: &[u8]) -> u8 {
+        Self::try_from_account_data(data).map_or(0, |deposit_receipt| deposit_receipt.version)
+    }
+
+    /// Check the account data holds a layout this program knows. Data past `LEN` is ignored.
+    pub fn check_version(data: &[u8]) -> Result<(), StakeDepositInterceptorError> {
+        if Self::version(data) > Self::CURRENT_VERSION {
+            return Err(StakeDepositInterceptorError::UnsupportedAccountVersion);
+        }
+        if data.len() < Self::LEN {
+            return Err(StakeDepositInterceptorError::AccountMigrationRequired);
+        }
+        Ok(())
+    }
+
+    /// Deserialize the current layout from the account data, ignoring any data past `LEN`.
+    pub fn try_from_account_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        Self::try_from_slice_unchecked(
+            data.get(..Self::LEN)
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )
+    }
+
+    /// Mutable version of `try_from_account_data`.
+    pub fn try_from_account_data_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        Self::try_from_slice_unchecked_mut(
+            data.get_mut(..Self::LEN)
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )
+    }
+
+    /// Account that receives the DepositReceipt's lamports when it is closed
+    pub fn rent_refund_destination(&self) -> Pubkey {
+        if self.rent_payer == Pubkey::default() {
+            self.owner
+        } else {
+            self.rent_payer
+        }
+    }
+
+    /// Whether claims are authorized by burning the `receipt_mint` token
+    pub fn is_tokenized(&self) -> bool {
+        self.receipt_mint != Pubkey::default()
+    }
+
+    /// `deposit_time`, or the latest deposit time whose cool down has already ended at
+    /// `current_timestamp` if that is later. Both owe no fee at `current_timestamp`, but only the
+    /// latter can be weighted against another DepositReceipt without lowering its fee.
+    pub fn effective_deposit_time(&self, current_timestamp: i64) -> u64 {
+        u64::from(self.deposit_time).max(
+            current_timestamp
+                .unsigned_abs()
+                .saturating_sub(self.cool_down_seconds.into()),
+        )
+    }
+
     /// Given a current timestamp, calculate the amount of "pool" tokens
     /// are required to be sent to the fee_wallet's token account.
     pub fn calculate_fee_amount(&self, current_timestamp: i64) -> u64 {
+        self.calculate_fee_amount_for(u64::from(self.lst_amount), current_timestamp)
+    }
+
+    /// Given an amount of "pool" tokens being claimed from this DepositReceipt and a
+    /// current timestamp, calculate the amount of "pool" tokens that are required to be
+    /// sent to the fee_wallet's token account.
+    pub fn calculate_fee_amount_for(&self, amount: u64, current_timestamp: i64) -> u64 {
         let cool_down_seconds = u64::from(self.cool_down_seconds);
         let end_cool_down_time = u64::from(self.deposit_time)
             .checked_add(cool_down_seconds)
@@ -99,21 +736,135 @@ impl DepositReceipt {
             return 0;
         }
 
-        let total_amount = u64::from(self.lst_amount);
+        let elapsed_seconds = cool_down_seconds.saturating_sub(cool_down_time_left);
+        let (numerator, denominator) = self.fee_curve.fee_ratio(elapsed_seconds, cool_down_seconds);
         // Denominator will never be 0, div_ceil is safe to use.
-        let denominator = cool_down_seconds
-            .checked_mul(u64::from(Self::FEE_BPS_DENOMINATOR))
+        let denominator = denominator
+            .checked_mul(u128::from(Self::FEE_BPS_DENOMINATOR))
             .expect("overflow");
         let fee_amount = u128::from(u32::from(self.initial_fee_bps))
-            .checked_mul(cool_down_time_left as u128)
+            .checked_mul(numerator)
             .expect("overflow")
-            .checked_mul(total_amount as u128)
+            .checked_mul(amount as u128)
             .expect("overflow")
-            .div_ceil(denominator as u128);
+            .div_ceil(denominator);
         u64::try_from(fee_amount).unwrap()
     }
 }
 
+/// Reduced fee rate granted by a StakePoolDepositStakeAuthority's `authority` to a trusted depositor.
+#[derive(shank::ShankAccount)]
+#[repr(C)]
+#[derive(Clone, Copy, AccountDeserialize, Debug, PartialEq, Pod, Zeroable)]
+pub struct FeeExemption {
+    /// StakePoolDepositStakeAuthority the FeeExemption was granted by
+    pub stake_pool_deposit_stake_authority: Pubkey,
+    /// Owner of new DepositReceipts the reduced fee rate applies to
+    pub depositor: Pubkey,
+    /// Initial fee rate (in bps) applied in place of the StakePoolDepositStakeAuthority's.
+    /// 0 is a full exemption.
+    pub initial_fee_bps: PodU32,
+    /// Bump seed for derivation
+    pub bump_seed: u8,
+    // reserved bytes
+    reserved: [u8; 64],
+}
+
+impl Discriminator for FeeExemption {
+    const DISCRIMINATOR: u8 = StakeDepositInterceptorDiscriminators::FeeExemption as u8;
+}
+
+impl FeeExemption {
+    /// Size of the account data, including the discriminator
+    pub const LEN: usize = 8 + mem::size_of::<Self>();
+}
+
+/// Index of the StakePoolDepositStakeAuthority accounts of a StakePool, maintained by the
+/// StakePool's manager.
+#[derive(shank::ShankAccount)]
+#[repr(C)]
+#[derive(Clone, Copy, AccountDeserialize, Debug, PartialEq, Pod, Zeroable)]
+pub struct StakePoolRegistry {
+    /// StakePool the registered StakePoolDepositStakeAuthority accounts were created for
+    pub stake_pool: Pubkey,
+    /// Program ID for the stake_pool
+    pub stake_pool_program_id: Pubkey,
+    /// Registered StakePoolDepositStakeAuthority installed as the StakePool's
+    /// `stake_deposit_authority` when the registry was last updated, default otherwise
+    pub active_deposit_stake_authority: Pubkey,
+    /// Registered StakePoolDepositStakeAuthority accounts, the first `registered_count` are set
+    pub deposit_stake_authorities: [Pubkey; MAX_REGISTERED_DEPOSIT_STAKE_AUTHORITIES],
+    /// Number of registered StakePoolDepositStakeAuthority accounts
+    pub registered_count: u8,
+    /// Bump seed for derivation
+    pub bump_seed: u8,
+    // reserved bytes
+    reserved: [u8; 64],
+}
+
+impl Discriminator for StakePoolRegistry {
+    const DISCRIMINATOR: u8 = StakeDepositInterceptorDiscriminators::StakePoolRegistry as u8;
+}
+
+impl StakePoolRegistry {
+    /// Size of the account data, including the discriminator
+    pub const LEN: usize = 8 + mem::size_of::<Self>();
+
+    /// Registered StakePoolDepositStakeAuthority accounts, in registration order
+    pub fn registered(&self) -> &[Pubkey] {
+        &self.deposit_stake_authorities[..self.registered_count as usize]
+    }
+
+    /// Whether the StakePoolDepositStakeAuthority is registered
+    pub fn is_registered(&self, deposit_stake_authority: &Pubkey) -> bool {
+        self.registered().contains(deposit_stake_authority)
+    }
+
+    /// Record a StakePoolDepositStakeAuthority. Registering one twice is a no-op.
+    pub fn register(
+        &mut self,
+        deposit_stake_authority: &Pubkey,
+    ) -> Result<(), StakeDepositInterceptorError> {
+        if self.is_registered(deposit_stake_authority) {
+            return Ok(());
+        }
+        let index = self.registered_count as usize;
+        if index >= MAX_REGISTERED_DEPOSIT_STAKE_AUTHORITIES {
+            return Err(StakeDepositInterceptorError::StakePoolRegistryFull);
+        }
+        self.deposit_stake_authorities[index] = *deposit_stake_authority;
+        self.registered_count += 1;
+        Ok(())
+    }
+
+    /// Remove a StakePoolDepositStakeAuthority, keeping the order of the others.
+    pub fn deregister(
+        &mut self,
+        deposit_stake_authority: &Pubkey,
+    ) -> Result<(), StakeDepositInterceptorError> {
+        let index = self
+            .registered()
+            .iter()
+            .position(|registered| registered == deposit_stake_authority)
+            .ok_or(StakeDepositInterceptorError::DepositStakeAuthorityNotRegistered)?;
+        let count = self.registered_count as usize;
+        self.deposit_stake_authorities
+            .copy_within(index + 1..count, index);
+        self.deposit_stake_authorities[count - 1] = Pubkey::default();
+        self.registered_count -= 1;
+        Ok(())
+    }
+
+    /// Mark the StakePool's `stake_deposit_authority` as active if it is registered.
+    pub fn update_active(&mut self, stake_deposit_authority: &Pubkey) {
+        self.active_deposit_stake_authority = if self.is_registered(stake_deposit_authority) {
+            *stake_deposit_authority
+        } else {
+            Pubkey::default()
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,7 +881,11 @@ mod tests {
             cool_down_seconds: PodU64::from(1_000),
             initial_fee_bps: PodU32::from(100),
             bump_seed: 0,
-            reserved: [0u8; 256],
+            fee_curve: FeeCurve::default(),
+            rent_payer: Pubkey::default(),
+            receipt_mint: Pubkey::default(),
+            version: DepositReceipt::CURRENT_VERSION,
+            reserved: [0u8; 169],
         };
 
         // fee rate is initial rate of 100bps = 10_000
@@ -143,8 +898,310 @@ mod tests {
         assert_eq!(deposit_receipt.calculate_fee_amount(2_000), 0);
         assert_eq!(deposit_receipt.calculate_fee_amount(2_001), 0);
 
+        // Fee on a partial amount only accounts for that amount
+        assert_eq!(
+            deposit_receipt.calculate_fee_amount_for(500_000, 1_000),
+            5_000
+        );
+        assert_eq!(
+            deposit_receipt.calculate_fee_amount_for(500_000, 1_500),
+            2_500
+        );
+
         // Fee should be round up to 1
         deposit_receipt.lst_amount = PodU64::from(1);
         assert_eq!(deposit_receipt.calculate_fee_amount(1_000), 1);
     }
+
+    fn deposit_receipt_with_curve(fee_curve: FeeCurveConfig) -> DepositReceipt {
+        DepositReceipt {
+            base: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            stake_pool: Pubkey::new_unique(),
+            stake_pool_deposit_stake_authority: Pubkey::new_unique(),
+            deposit_time: PodU64::from(1_000),
+            lst_amount: PodU64::from(1_000_000),
+            cool_down_seconds: PodU64::from(1_000),
+            initial_fee_bps: PodU32::from(100),
+            bump_seed: 0,
+            fee_curve: FeeCurve::from_config(&fee_curve).unwrap(),
+            rent_payer: Pubkey::default(),
+            receipt_mint: Pubkey::default(),
+            version: DepositReceipt::CURRENT_VERSION,
+            reserved: [0u8; 169],
+        }
+    }
+
+    fn deposit_receipt_data(deposit_receipt: &DepositReceipt) -> Vec<u8> {
+        let mut data = vec![0u8; 8];
+        data[0] = DepositReceipt::DISCRIMINATOR;
+        data.extend_from_slice(bytemuck::bytes_of(deposit_receipt));
+        data
+    }
+
+    #[test]
+    fn test_check_version() {
+        let mut deposit_receipt = deposit_receipt_with_curve(FeeCurveConfig::Linear);
+        let data = deposit_receipt_data(&deposit_receipt);
+        assert_eq!(data.len(), DepositReceipt::LEN);
+        assert_eq!(DepositReceipt::check_version(&data), Ok(()));
+
+        // DepositReceipts created before the version was recorded are still supported
+        deposit_receipt.version = 0;
+        let data = deposit_receipt_data(&deposit_receipt);
+        assert_eq!(DepositReceipt::check_version(&data), Ok(()));
+
+        deposit_receipt.version = DepositReceipt::CURRENT_VERSION + 1;
+        let data = deposit_receipt_data(&deposit_receipt);
+        assert_eq!(
+            DepositReceipt::check_version(&data),
+            Err(StakeDepositInterceptorError::UnsupportedAccountVersion)
+        );
+
+        // Shorter layouts must be migrated first
+        assert_eq!(
+            DepositReceipt::check_version(&data[..DepositReceipt::LEN - 1]),
+            Err(StakeDepositInterceptorError::AccountMigrationRequired)
+        );
+
+        // Data appended by later layouts is ignored
+        deposit_receipt.version = DepositReceipt::CURRENT_VERSION;
+        let mut data = deposit_receipt_data(&deposit_receipt);
+        data.extend_from_slice(&[1u8; 16]);
+        assert_eq!(DepositReceipt::check_version(&data), Ok(()));
+        assert_eq!(
+            DepositReceipt::try_from_account_data(&data).unwrap(),
+            &deposit_receipt
+        );
+    }
+
+    #[test]
+    fn test_deposit_stake_authority_check_version() {
+        let mut deposit_stake_authority = StakePoolDepositStakeAuthority::zeroed();
+        deposit_stake_authority.version = StakePoolDepositStakeAuthority::CURRENT_VERSION;
+        let mut data = vec![0u8; 8];
+        data[0] = StakePoolDepositStakeAuthority::DISCRIMINATOR;
+        data.extend_from_slice(bytemuck::bytes_of(&deposit_stake_authority));
+        assert_eq!(data.len(), StakePoolDepositStakeAuthority::LEN);
+        assert_eq!(
+            StakePoolDepositStakeAuthority::version(&data),
+            StakePoolDepositStakeAuthority::CURRENT_VERSION
+        );
+        assert_eq!(StakePoolDepositStakeAuthority::check_version(&data), Ok(()));
+
+        // Version 0 accounts end right before the version
+        let legacy_data = &data[..StakePoolDepositStakeAuthority::VERSION_OFFSET];
+        assert_eq!(StakePoolDepositStakeAuthority::version(legacy_data), 0);
+        assert_eq!(
+            StakePoolDepositStakeAuthority::check_version(legacy_data),
+            Err(StakeDepositInterceptorError::AccountMigrationRequired)
+        );
+
+        let mut future_data = data.clone();
+        future_data[StakePoolDepositStakeAuthority::VERSION_OFFSET] =
+            StakePoolDepositStakeAuthority::CURRENT_VERSION + 1;
+        future_data.extend_from_slice(&[0u8; 16]);
+        assert_eq!(
+            StakePoolDepositStakeAuthority::check_version(&future_data),
+            Err(StakeDepositInterceptorError::UnsupportedAccountVersion)
+        );
+
+        // Data appended by later layouts is ignored
+        data.extend_from_slice(&[1u8; 16]);
+        assert_eq!(StakePoolDepositStakeAuthority::check_version(&data), Ok(()));
+        assert_eq!(
+            StakePoolDepositStakeAuthority::try_from_account_data(&data)
+                .unwrap()
+                .version,
+            StakePoolDepositStakeAuthority::CURRENT_VERSION
+        );
+    }
+
+    #[test]
+    fn test_calculate_fee_amount_step() {
+        let deposit_receipt = deposit_receipt_with_curve(FeeCurveConfig::Step { steps: 4 });
+
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_000), 10_000);
+        // Fee holds for the whole first step
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_249), 10_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_250), 7_500);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_500), 5_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_999), 2_500);
+        assert_eq!(deposit_receipt.calculate_fee_amount(2_000), 0);
+    }
+
+    #[test]
+    fn test_calculate_fee_amount_exponential() {
+        let deposit_receipt = deposit_receipt_with_curve(FeeCurveConfig::Exponential {
+            half_life_seconds: 250,
+        });
+
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_000), 10_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_250), 5_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_500), 2_500);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_750), 1_250);
+        // Interpolated between half lives
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_125), 7_500);
+        assert_eq!(deposit_receipt.calculate_fee_amount(2_000), 0);
+    }
+
+    #[test]
+    fn test_calculate_fee_amount_piecewise_linear() {
+        let deposit_receipt = deposit_receipt_with_curve(FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![
+                FeeCurveBreakpoint::new(1_000, 2_000),
+                FeeCurveBreakpoint::new(5_000, 2_000),
+            ],
+        });
+
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_000), 10_000);
+        // Steep drop to 20% of the initial fee in the first 10% of the cool down
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_050), 6_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_100), 2_000);
+        // Flat until half of the cool down
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_300), 2_000);
+        // Linear to 0 for the remainder
+        assert_eq!(deposit_receipt.calculate_fee_amount(1_750), 1_000);
+        assert_eq!(deposit_receipt.calculate_fee_amount(2_000), 0);
+    }
+
+    #[test]
+    fn test_split_fee() {
+        let mut deposit_stake_authority = StakePoolDepositStakeAuthority::zeroed();
+        assert_eq!(deposit_stake_authority.split_fee(1_000), ([0, 0, 0], 1_000));
+
+        deposit_stake_authority
+            .set_fee_recipients(&[
+                FeeRecipient::new(Pubkey::new_unique(), 5_000),
+                FeeRecipient::new(Pubkey::new_unique(), 3_333),
+            ])
+            .unwrap();
+        assert_eq!(
+            deposit_stake_authority.split_fee(1_000),
+            ([500, 333, 0], 167)
+        );
+        assert_eq!(deposit_stake_authority.split_fee(1), ([0, 0, 0], 1));
+
+        deposit_stake_authority
+            .set_fee_recipients(&[FeeRecipient::new(Pubkey::new_unique(), 10_000)])
+            .unwrap();
+        assert_eq!(deposit_stake_authority.split_fee(999), ([999, 0, 0], 0));
+    }
+
+    #[test]
+    fn test_set_fee_recipients() {
+        let mut deposit_stake_authority = StakePoolDepositStakeAuthority::zeroed();
+        let recipient = FeeRecipient::new(Pubkey::new_unique(), 1);
+
+        assert_eq!(
+            deposit_stake_authority.set_fee_recipients(&[recipient; MAX_FEE_RECIPIENTS + 1]),
+            Err(StakeDepositInterceptorError::InvalidFeeRecipients)
+        );
+        assert_eq!(
+            deposit_stake_authority
+                .set_fee_recipients(&[FeeRecipient::new(Pubkey::new_unique(), 0)]),
+            Err(StakeDepositInterceptorError::InvalidFeeRecipients)
+        );
+        assert_eq!(
+            deposit_stake_authority.set_fee_recipients(&[FeeRecipient::new(Pubkey::default(), 1)]),
+            Err(StakeDepositInterceptorError::InvalidFeeRecipients)
+        );
+        assert_eq!(
+            deposit_stake_authority.set_fee_recipients(&[
+                FeeRecipient::new(Pubkey::new_unique(), 6_000),
+                FeeRecipient::new(Pubkey::new_unique(), 4_001),
+            ]),
+            Err(StakeDepositInterceptorError::InvalidFeeRecipients)
+        );
+
+        deposit_stake_authority
+            .set_fee_recipients(&[recipient, recipient])
+            .unwrap();
+        assert_eq!(
+            deposit_stake_authority.fee_recipients,
+            [recipient, recipient, FeeRecipient::default()]
+        );
+        // Clearing the recipients sends every fee to the `fee_wallet` again
+        deposit_stake_authority.set_fee_recipients(&[]).unwrap();
+        assert!(deposit_stake_authority
+            .fee_recipients
+            .iter()
+            .all(|fee_recipient| !fee_recipient.is_set()));
+    }
+
+    #[test]
+    fn test_fee_curve_from_config() {
+        assert_eq!(
+            FeeCurve::from_config(&FeeCurveConfig::Linear).unwrap(),
+            FeeCurve::default()
+        );
+        assert!(FeeCurve::from_config(&FeeCurveConfig::Step { steps: 0 }).is_err());
+        assert!(FeeCurve::from_config(&FeeCurveConfig::Exponential {
+            half_life_seconds: 0
+        })
+        .is_err());
+        assert!(FeeCurve::from_config(&FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![]
+        })
+        .is_err());
+        // Fee cannot increase
+        assert!(FeeCurve::from_config(&FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![
+                FeeCurveBreakpoint::new(1_000, 2_000),
+                FeeCurveBreakpoint::new(2_000, 3_000),
+            ]
+        })
+        .is_err());
+        // Breakpoints must be ordered and inside the cool down
+        assert!(FeeCurve::from_config(&FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![FeeCurveBreakpoint::new(10_000, 0)]
+        })
+        .is_err());
+        assert!(FeeCurve::from_config(&FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![
+                FeeCurveBreakpoint::new(2_000, 2_000),
+                FeeCurveBreakpoint::new(2_000, 1_000),
+            ]
+        })
+        .is_err());
+
+        let config = FeeCurveConfig::PiecewiseLinear {
+            breakpoints: vec![FeeCurveBreakpoint::new(5_000, 1_000)],
+        };
+        assert_eq!(FeeCurve::from_config(&config).unwrap().config(), config);
+    }
+
+    #[test]
+    fn test_stake_pool_registry() {
+        let mut registry = StakePoolRegistry::zeroed();
+        let deposit_stake_authorities: Vec<Pubkey> = (0..MAX_REGISTERED_DEPOSIT_STAKE_AUTHORITIES)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        for deposit_stake_authority in deposit_stake_authorities.iter() {
+            registry.register(deposit_stake_authority).unwrap();
+        }
+        // Registering twice does not take another slot
+        registry.register(&deposit_stake_authorities[0]).unwrap();
+        assert_eq!(registry.registered(), deposit_stake_authorities.as_slice());
+        assert_eq!(
+            registry.register(&Pubkey::new_unique()),
+            Err(StakeDepositInterceptorError::StakePoolRegistryFull)
+        );
+
+        registry.update_active(&deposit_stake_authorities[1]);
+        assert_eq!(
+            registry.active_deposit_stake_authority,
+            deposit_stake_authorities[1]
+        );
+
+        registry.deregister(&deposit_stake_authorities[0]).unwrap();
+        assert_eq!(registry.registered(), &deposit_stake_authorities[1..]);
+        assert_eq!(
+            registry.deregister(&deposit_stake_authorities[0]),
+            Err(StakeDepositInterceptorError::DepositStakeAuthorityNotRegistered)
+        );
+
+        registry.update_active(&deposit_stake_authorities[0]);
+        assert_eq!(registry.active_deposit_stake_authority, Pubkey::default());
+    }
 }
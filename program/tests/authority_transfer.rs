@@ -0,0 +1,287 @@
+mod helpers;
+
+use helpers::{
+    assert_transaction_err, create_stake_deposit_authority, get_account_data_deserialized,
+    process_transaction_events, program_test_context_with_stake_pool_state, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked, instruction::InstructionError, pubkey::Pubkey,
+    signature::Keypair, signer::Signer, transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_accept_authority_instruction, create_cancel_authority_transfer_instruction,
+        create_propose_authority_instruction, derive_stake_pool_deposit_stake_authority,
+    },
+    state::StakePoolDepositStakeAuthority,
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Keypair,
+    Keypair,
+    Pubkey,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+
+    let deposit_authority_base = Keypair::new();
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        authority,
+        deposit_authority_base,
+        deposit_stake_authority_pubkey,
+    )
+}
+
+/// Propose `new_authority` as the StakePoolDepositStakeAuthority's authority.
+async fn propose_authority(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    authority: &Keypair,
+    deposit_authority_base: &Keypair,
+    new_authority: &Pubkey,
+) {
+    let ix = create_propose_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+        new_authority,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_propose_and_accept_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        authority,
+        deposit_authority_base,
+        deposit_stake_authority_pubkey,
+    ) = setup().await;
+    let new_authority = Keypair::new();
+
+    propose_authority(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        &new_authority.pubkey(),
+    )
+    .await;
+
+    // Authority is unchanged until the transfer is accepted
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(deposit_stake_authority.authority, authority.pubkey());
+    assert_eq!(
+        deposit_stake_authority.pending_authority,
+        new_authority.pubkey()
+    );
+
+    let ix = create_accept_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+        &new_authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &new_authority],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::AcceptAuthority {
+            deposit_stake_authority: deposit_stake_authority_pubkey,
+            previous_authority: authority.pubkey(),
+            authority: new_authority.pubkey(),
+        }]
+    );
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(deposit_stake_authority.authority, new_authority.pubkey());
+    assert_eq!(deposit_stake_authority.pending_authority, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_cancel_authority_transfer() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        authority,
+        deposit_authority_base,
+        deposit_stake_authority_pubkey,
+    ) = setup().await;
+    let new_authority = Keypair::new();
+
+    propose_authority(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        &new_authority.pubkey(),
+    )
+    .await;
+
+    let ix = create_cancel_authority_transfer_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(deposit_stake_authority.authority, authority.pubkey());
+    assert_eq!(deposit_stake_authority.pending_authority, Pubkey::default());
+
+    // The previously proposed authority can no longer accept
+    let ix = create_accept_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+        &new_authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &new_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidPendingAuthority as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_propose_authority_incorrect_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _authority,
+        deposit_authority_base,
+        _deposit_stake_authority_pubkey,
+    ) = setup().await;
+    let bad_authority = Keypair::new();
+
+    let ix = create_propose_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &bad_authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+        &bad_authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_accept_authority_not_pending_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        authority,
+        deposit_authority_base,
+        _deposit_stake_authority_pubkey,
+    ) = setup().await;
+    let new_authority = Keypair::new();
+
+    propose_authority(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        &new_authority.pubkey(),
+    )
+    .await;
+
+    let bad_authority = Keypair::new();
+    let ix = create_accept_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+        &bad_authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidPendingAuthority as u32),
+    )
+    .await;
+}
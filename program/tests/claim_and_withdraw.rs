@@ -0,0 +1,361 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_deposit_authority, get_account,
+    get_account_data_deserialized, program_test_context_with_stake_pool_state, set_clock_time,
+    update_sol_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self, state::StakeStateV2},
+    system_instruction,
+    transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_claim_and_withdraw_sol_instruction, create_claim_and_withdraw_stake_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+};
+
+/// Set up a StakePool with the StakePoolDepositStakeAuthority as its sol deposit authority and
+/// a DepositReceipt for 1 SOL deposited into the reserve.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Pubkey,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Pubkey,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's sol_deposit_authority to the interceptor program's PDA
+    update_sol_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    let deposit_receipt_base = Keypair::new();
+    let deposit_sol_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+        );
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_sol_instruction],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    )
+}
+
+fn claim_and_withdraw_sol_ix(
+    stake_pool_accounts: &StakePoolAccounts,
+    deposit_stake_authority_pubkey: &Pubkey,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    depositor: &Pubkey,
+    deposit_receipt_pda: &Pubkey,
+) -> Instruction {
+    create_claim_and_withdraw_sol_instruction(
+        &stake_deposit_interceptor::id(),
+        deposit_receipt_pda,
+        depositor,
+        &deposit_stake_authority.vault,
+        deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        depositor,
+        &spl_stake_pool::id(),
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.withdraw_authority,
+        &stake_pool_accounts.reserve_stake_account,
+        &stake_pool_accounts.pool_fee_account,
+        None,
+        None,
+    )
+}
+
+#[tokio::test]
+async fn test_success_claim_and_withdraw_sol() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let deposit_receipt_lamports = get_account(&mut ctx.banks_client, &deposit_receipt_pda)
+        .await
+        .lamports;
+    let depositor_lamports_before = get_account(&mut ctx.banks_client, &depositor.pubkey())
+        .await
+        .lamports;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds) as i64 + 10,
+    )
+    .await;
+    let ix = claim_and_withdraw_sol_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority,
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The pool tokens were worth 1 SOL, withdrawn to the owner along with the receipt's rent
+    let depositor_account = get_account(&mut ctx.banks_client, &depositor.pubkey()).await;
+    assert_eq!(
+        depositor_account.lamports - depositor_lamports_before,
+        LAMPORTS_PER_SOL + deposit_receipt_lamports
+    );
+    let vault_account = get_account(&mut ctx.banks_client, &deposit_stake_authority.vault).await;
+    let vault = spl_token::state::Account::unpack(&vault_account.data).unwrap();
+    assert_eq!(vault.amount, 0);
+    assert!(ctx
+        .banks_client
+        .get_account(deposit_receipt_pda)
+        .await
+        .unwrap()
+        .is_none());
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(u64::from(deposit_stake_authority.total_outstanding_lst), 0);
+    assert_eq!(
+        u64::from(deposit_stake_authority.outstanding_receipt_count),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_success_claim_and_withdraw_stake_during_cool_down() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+
+    // The StakePool has no validators, so the stake is split from the reserve
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let stake_rent = rent.minimum_balance(StakeStateV2::size_of());
+    let stake_to_receive = Keypair::new();
+    let create_stake_ix = system_instruction::create_account(
+        &ctx.payer.pubkey(),
+        &stake_to_receive.pubkey(),
+        stake_rent,
+        StakeStateV2::size_of() as u64,
+        &stake::program::id(),
+    );
+    let ix = create_claim_and_withdraw_stake_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        &spl_stake_pool::id(),
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.validator_list,
+        &stake_pool_accounts.withdraw_authority,
+        &stake_pool_accounts.reserve_stake_account,
+        &stake_to_receive.pubkey(),
+        &stake_pool_accounts.pool_fee_account,
+        None,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[create_stake_ix, ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &depositor, &stake_to_receive],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The owner is the authority of the withdrawn stake, which is worth the net pool tokens
+    let fee_amount = deposit_receipt.calculate_fee_amount(clock_time);
+    assert!(fee_amount > 0);
+    let net_amount = u64::from(deposit_receipt.lst_amount) - fee_amount;
+    let stake_account = get_account(&mut ctx.banks_client, &stake_to_receive.pubkey()).await;
+    assert_eq!(stake_account.lamports, stake_rent + net_amount);
+    let stake_state = try_from_slice_unchecked::<StakeStateV2>(&stake_account.data).unwrap();
+    let meta = stake_state.meta().unwrap();
+    assert_eq!(meta.authorized.staker, depositor.pubkey());
+    assert_eq!(meta.authorized.withdrawer, depositor.pubkey());
+
+    // Fees stay in the vault
+    let vault_account = get_account(&mut ctx.banks_client, &deposit_stake_authority.vault).await;
+    let vault = spl_token::state::Account::unpack(&vault_account.data).unwrap();
+    assert_eq!(vault.amount, fee_amount);
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(u64::from(deposit_stake_authority.accrued_fees), fee_amount);
+    assert_eq!(u64::from(deposit_stake_authority.total_outstanding_lst), 0);
+}
+
+#[tokio::test]
+async fn test_fail_claim_and_withdraw_owner_not_signer() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let mut ix = claim_and_withdraw_sol_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority,
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+    );
+    ix.accounts[1] = AccountMeta::new(depositor.pubkey(), false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::SignatureMissing as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_claim_and_withdraw_invalid_stake_pool_program() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let mut ix = claim_and_withdraw_sol_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority,
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+    );
+    ix.accounts[8] = AccountMeta::new_readonly(spl_token::id(), false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidStakePoolProgram as u32),
+    )
+    .await;
+}
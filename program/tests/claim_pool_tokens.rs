@@ -1,11 +1,11 @@
 mod helpers;
 
-use std::{mem, ops::Add};
+use std::ops::Add;
 
 use helpers::{
     airdrop_lamports, assert_transaction_err, clone_account_to_new_address, create_stake_account,
     create_stake_deposit_authority, create_token_account, create_validator_and_add_to_pool,
-    delegate_stake_account, get_account, get_account_data_deserialized,
+    delegate_stake_account, get_account, get_account_data_deserialized, process_transaction_events,
     program_test_context_with_stake_pool_state, set_clock_time, stake_pool_update_all,
     update_stake_deposit_authority, StakePoolAccounts, ValidatorStakeAccount,
 };
@@ -24,13 +24,14 @@ use solana_sdk::{
     stake::{self},
     transaction::Transaction,
 };
-use spl_associated_token_account::{
-    get_associated_token_address, instruction::create_associated_token_account,
-};
 use spl_token_2022::state::Account;
 use stake_deposit_interceptor::{
     error::StakeDepositInterceptorError,
-    instruction::{derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority},
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+        ClaimPoolTokensPartialArgs, StakeDepositInterceptorInstruction,
+    },
     state::{DepositReceipt, StakePoolDepositStakeAuthority},
 };
 
@@ -215,7 +216,7 @@ async fn test_success_claim_pool_tokens() {
         deposit_authority_base,
         _total_staked_amount,
         depositor_pool_token_account,
-        fee_wallet,
+        _fee_wallet,
     ) = setup().await;
 
     let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
@@ -235,27 +236,131 @@ async fn test_success_claim_pool_tokens() {
     )
     .await;
 
-    let fee_token_account =
-        get_associated_token_address(&fee_wallet.pubkey(), &stake_pool_accounts.pool_mint);
-
-    let create_fee_token_account_ix = create_associated_token_account(
+    let ix = stake_deposit_interceptor::instruction::create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
         &depositor.pubkey(),
-        &fee_wallet.pubkey(),
-        &stake_pool_accounts.pool_mint,
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool.pool_mint,
         &spl_token::id(),
+        &depositor.pubkey(),
+        false,
     );
 
-    let ix = stake_deposit_interceptor::instruction::create_claim_pool_tokens_instruction(
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    let events = process_transaction_events(&mut ctx, tx).await;
+
+    let fee_amount = deposit_receipt.calculate_fee_amount(clock_time);
+    let user_amount = u64::from(deposit_receipt.lst_amount) - fee_amount;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::ClaimPoolTokens {
+            deposit_receipt: deposit_receipt_pda,
+            owner: depositor.pubkey(),
+            destination: depositor_pool_token_account,
+            fee_amount,
+            net_amount: user_amount,
+            remaining_lst_amount: 0,
+        }]
+    );
+
+    // Destination token account should have received pool tokens
+    let destination_token_account_info =
+        get_account(&mut ctx.banks_client, &depositor_pool_token_account).await;
+    let destination_token_account =
+        Account::unpack(&destination_token_account_info.data.as_slice()).unwrap();
+    assert_eq!(destination_token_account.amount, user_amount);
+
+    // Fees should have accrued in the vault
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.accrued_fees),
+        fee_amount
+    );
+    let vault_info = get_account(&mut ctx.banks_client, &deposit_stake_authority.vault).await;
+    let vault = Account::unpack(&vault_info.data.as_slice()).unwrap();
+    assert_eq!(vault.amount, fee_amount);
+    // The closed DepositReceipt is no longer outstanding
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.outstanding_receipt_count),
+        0
+    );
+
+    // DepositReceipt account should have been closed
+    let deposit_receipt_account = ctx
+        .banks_client
+        .get_account(deposit_receipt_pda)
+        .await
+        .unwrap();
+    assert!(deposit_receipt_account.is_none());
+}
+
+#[tokio::test]
+async fn test_success_claim_pool_tokens_partial() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        stake_pool,
+        _validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        depositor_pool_token_account,
+        _fee_wallet,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let claim_amount = lst_amount / 4;
+
+    let ix = stake_deposit_interceptor::instruction::create_claim_pool_tokens_partial_instruction(
         &stake_deposit_interceptor::id(),
         &deposit_receipt_pda,
         &depositor.pubkey(),
         &deposit_stake_authority.vault,
         &depositor_pool_token_account,
-        &fee_token_account,
         &deposit_stake_authority_pubkey,
         &stake_pool.pool_mint,
         &spl_token::id(),
+        &depositor.pubkey(),
         false,
+        claim_amount,
     );
 
     let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
@@ -264,7 +369,7 @@ async fn test_success_claim_pool_tokens() {
     set_clock_time(&mut ctx, clock_time).await;
 
     let tx = Transaction::new_signed_with_payer(
-        &[create_fee_token_account_ix, ix],
+        &[ix],
         Some(&depositor.pubkey()),
         &[&depositor],
         ctx.last_blockhash,
@@ -272,20 +377,85 @@ async fn test_success_claim_pool_tokens() {
 
     ctx.banks_client.process_transaction(tx).await.unwrap();
 
-    let fee_amount = deposit_receipt.calculate_fee_amount(clock_time);
-    let user_amount = u64::from(deposit_receipt.lst_amount) - fee_amount;
+    // Fees are only charged on the claimed amount
+    let fee_amount = deposit_receipt.calculate_fee_amount_for(claim_amount, clock_time);
+    let user_amount = claim_amount - fee_amount;
 
-    // Destination token account should have received pool tokens
     let destination_token_account_info =
         get_account(&mut ctx.banks_client, &depositor_pool_token_account).await;
     let destination_token_account =
         Account::unpack(&destination_token_account_info.data.as_slice()).unwrap();
     assert_eq!(destination_token_account.amount, user_amount);
 
-    // Fees should have been paid
-    let fee_token_account_info = get_account(&mut ctx.banks_client, &fee_token_account).await;
-    let fee_token_account = Account::unpack(&fee_token_account_info.data.as_slice()).unwrap();
-    assert_eq!(fee_token_account.amount, fee_amount,);
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.accrued_fees),
+        fee_amount
+    );
+
+    // DepositReceipt should remain open with the remaining amount
+    let updated_deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_receipt.lst_amount),
+        lst_amount - claim_amount
+    );
+    assert_eq!(
+        updated_deposit_receipt.deposit_time,
+        deposit_receipt.deposit_time
+    );
+}
+
+#[tokio::test]
+async fn test_success_claim_pool_tokens_partial_closes_when_empty() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        _depositor,
+        deposit_receipt_pda,
+        _deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let destination_token_account = instructions[0].accounts[3].pubkey;
+    instructions[0].data =
+        borsh::to_vec(&StakeDepositInterceptorInstruction::ClaimPoolTokensPartial(
+            ClaimPoolTokensPartialArgs { amount: lst_amount },
+        ))
+        .unwrap();
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock_time =
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds).add(10) as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let destination_token_account_info =
+        get_account(&mut ctx.banks_client, &destination_token_account).await;
+    let destination_token_account =
+        Account::unpack(&destination_token_account_info.data.as_slice()).unwrap();
+    assert_eq!(destination_token_account.amount, lst_amount);
 
     // DepositReceipt account should have been closed
     let deposit_receipt_account = ctx
@@ -296,6 +466,44 @@ async fn test_success_claim_pool_tokens() {
     assert!(deposit_receipt_account.is_none());
 }
 
+#[tokio::test]
+async fn test_fail_claim_pool_tokens_partial_invalid_amount() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        depositor,
+        deposit_receipt_pda,
+        _deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    instructions[0].accounts[1] = AccountMeta::new(depositor.pubkey(), true);
+    instructions[0].data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::ClaimPoolTokensPartial(ClaimPoolTokensPartialArgs {
+            amount: u64::from(deposit_receipt.lst_amount) + 1,
+        }),
+    )
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidClaimAmount as u32),
+    )
+    .await;
+}
+
 async fn setup_with_ix() -> (
     ProgramTestContext,
     StakePoolAccounts,
@@ -316,7 +524,7 @@ async fn setup_with_ix() -> (
         deposit_authority_base,
         _total_staked_amount,
         depositor_pool_token_account,
-        fee_wallet,
+        _fee_wallet,
     ) = setup().await;
 
     let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
@@ -330,26 +538,16 @@ async fn setup_with_ix() -> (
         &deposit_receipt_base.pubkey(),
     );
 
-    let fee_token_account =
-        get_associated_token_address(&fee_wallet.pubkey(), &stake_pool_accounts.pool_mint);
-
-    let create_fee_token_account_ix = create_associated_token_account(
-        &depositor.pubkey(),
-        &fee_wallet.pubkey(),
-        &stake_pool_accounts.pool_mint,
-        &spl_token::id(),
-    );
-
     let ix = stake_deposit_interceptor::instruction::create_claim_pool_tokens_instruction(
         &stake_deposit_interceptor::id(),
         &deposit_receipt_pda,
         &depositor.pubkey(),
         &deposit_stake_authority.vault,
         &depositor_pool_token_account,
-        &fee_token_account,
         &deposit_stake_authority_pubkey,
         &stake_pool.pool_mint,
         &spl_token::id(),
+        &depositor.pubkey(),
         true,
     );
     (
@@ -358,7 +556,7 @@ async fn setup_with_ix() -> (
         depositor,
         deposit_receipt_pda,
         deposit_stake_authority_pubkey,
-        vec![create_fee_token_account_ix, ix],
+        vec![ix],
     )
 }
 
@@ -372,9 +570,7 @@ async fn test_success_permissionless_claim() {
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    // update fee token account funder to ctx.payer
-    instructions[0].accounts[0] = AccountMeta::new(ctx.payer.pubkey(), true);
-    let destination_token_account = instructions[1].accounts[3].pubkey;
+    let destination_token_account = instructions[0].accounts[3].pubkey;
     let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
         &mut ctx.banks_client,
         &deposit_receipt_pda,
@@ -413,10 +609,8 @@ async fn test_fail_permissionless_claim_during_cool_down() {
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    // update fee token account funder to ctx.payer
-    instructions[0].accounts[0] = AccountMeta::new(ctx.payer.pubkey(), true);
     // Update instruction to not require owner signature
-    instructions[1].accounts[1] = AccountMeta::new(depositor.pubkey(), false);
+    instructions[0].accounts[1] = AccountMeta::new(depositor.pubkey(), false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -443,7 +637,7 @@ async fn test_fail_invalid_system_program() {
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    instructions[1].accounts[8] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    instructions[0].accounts[7] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -466,7 +660,7 @@ async fn test_fail_invalid_owner() {
         mut instructions,
     ) = setup_with_ix().await;
     let bad_owner = Keypair::new();
-    instructions[1].accounts[1] = AccountMeta::new(bad_owner.pubkey(), true);
+    instructions[0].accounts[1] = AccountMeta::new(bad_owner.pubkey(), true);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -500,7 +694,7 @@ async fn test_fail_invalid_stake_deposit_authority_owner() {
         .await
         .unwrap()
         .unwrap();
-    const ACCOUNT_SIZE: usize = 8 + mem::size_of::<StakePoolDepositStakeAuthority>();
+    const ACCOUNT_SIZE: usize = StakePoolDepositStakeAuthority::LEN;
     let mut bad_account =
         AccountSharedData::new(original.lamports, ACCOUNT_SIZE, &Pubkey::new_unique());
     bad_account.set_data_from_slice(&original.data);
@@ -527,7 +721,7 @@ async fn test_fail_invalid_stake_deposit_authority_address() {
         mut instructions,
     ) = setup_with_ix().await;
     let bad_account = clone_account_to_new_address(&mut ctx, &deposit_stake_authority_pubkey).await;
-    instructions[1].accounts[5] = AccountMeta::new_readonly(bad_account, false);
+    instructions[0].accounts[4] = AccountMeta::new(bad_account, false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -563,7 +757,7 @@ async fn test_fail_invalid_deposit_receipt_owner() {
         .await
         .unwrap()
         .unwrap();
-    const ACCOUNT_SIZE: usize = 8 + mem::size_of::<DepositReceipt>();
+    const ACCOUNT_SIZE: usize = DepositReceipt::LEN;
     let mut bad_account =
         AccountSharedData::new(original.lamports, ACCOUNT_SIZE, &Pubkey::new_unique());
     bad_account.set_data_from_slice(&original.data);
@@ -590,7 +784,7 @@ async fn test_fail_invalid_deposit_receipt_address() {
         mut instructions,
     ) = setup_with_ix().await;
     let bad_account = clone_account_to_new_address(&mut ctx, &deposit_receipt_pda).await;
-    instructions[1].accounts[0] = AccountMeta::new(bad_account, false);
+    instructions[0].accounts[0] = AccountMeta::new(bad_account, false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -624,7 +818,7 @@ async fn test_fail_invalid_deposit_receipt() {
         .await
         .unwrap()
         .unwrap();
-    const ACCOUNT_SIZE: usize = 8 + mem::size_of::<DepositReceipt>();
+    const ACCOUNT_SIZE: usize = DepositReceipt::LEN;
     let bad_deposit_receipt =
         DepositReceipt::try_from_slice_unchecked_mut(&mut original_deposit_receipt.data).unwrap();
     bad_deposit_receipt.stake_pool_deposit_stake_authority = Pubkey::new_unique();
@@ -666,7 +860,7 @@ async fn test_fail_invalid_vault_address() {
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    instructions[1].accounts[2] = AccountMeta::new(Pubkey::new_unique(), false);
+    instructions[0].accounts[2] = AccountMeta::new(Pubkey::new_unique(), false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -684,52 +878,124 @@ async fn test_fail_invalid_vault_address() {
 }
 
 #[tokio::test]
-async fn test_fail_fee_token_account_not_owned_by_fee_wallet() {
+async fn test_fail_stake_deposit_authority_not_writable() {
     let (
         mut ctx,
-        stake_pool_accounts,
+        _stake_pool_accounts,
         depositor,
         _deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    instructions[0].accounts[4] = AccountMeta::new_readonly(deposit_stake_authority_pubkey, false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(&mut ctx, tx, InstructionError::InvalidAccountData).await;
+}
+
+#[tokio::test]
+async fn test_success_owner_claims_to_any_destination() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        depositor,
+        deposit_receipt_pda,
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    let bad_fee_token_account = create_token_account(
-        &mut ctx,
-        &depositor.pubkey(),
-        &stake_pool_accounts.pool_mint,
+    let other_owner = ctx.payer.pubkey();
+    let other_dest_token_account =
+        create_token_account(&mut ctx, &other_owner, &stake_pool_accounts.pool_mint).await;
+    instructions[0].accounts[1] = AccountMeta::new(depositor.pubkey(), true);
+    instructions[0].accounts[3] = AccountMeta::new(other_dest_token_account, false);
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
     )
     .await;
-    instructions[1].accounts[4] = AccountMeta::new(bad_fee_token_account, false);
 
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock_time =
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds).add(10) as i64;
+    set_clock_time(&mut ctx, clock_time).await;
     let tx = Transaction::new_signed_with_payer(
         &instructions,
         Some(&depositor.pubkey()),
         &[&depositor],
         ctx.last_blockhash,
     );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let destination_token_account_info =
+        get_account(&mut ctx.banks_client, &other_dest_token_account).await;
+    let destination_token_account =
+        Account::unpack(&destination_token_account_info.data.as_slice()).unwrap();
+    assert_eq!(
+        destination_token_account.amount,
+        u64::from(deposit_receipt.lst_amount)
+    );
+}
+
+#[tokio::test]
+async fn test_fail_permissionless_claim_destination_not_owned_by_owner() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _depositor,
+        deposit_receipt_pda,
+        _deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    let bad_owner = ctx.payer.pubkey();
+    let bad_dest_token_account =
+        create_token_account(&mut ctx, &bad_owner, &stake_pool_accounts.pool_mint).await;
+    instructions[0].accounts[3] = AccountMeta::new(bad_dest_token_account, false);
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock_time =
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds).add(10) as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
 
     assert_transaction_err(
         &mut ctx,
         tx,
-        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeTokenAccount as u32),
+        InstructionError::Custom(
+            StakeDepositInterceptorError::InvalidDestinationTokenAccount as u32,
+        ),
     )
     .await;
 }
 
 #[tokio::test]
-async fn test_fail_destination_token_account_not_owned_by_owner() {
+async fn test_fail_destination_is_vault() {
     let (
         mut ctx,
-        stake_pool_accounts,
+        _stake_pool_accounts,
         depositor,
         _deposit_receipt_pda,
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    let bad_owner = ctx.payer.pubkey();
-    let bad_dest_token_account =
-        create_token_account(&mut ctx, &bad_owner, &stake_pool_accounts.pool_mint).await;
-    instructions[1].accounts[3] = AccountMeta::new(bad_dest_token_account, false);
+    let vault = instructions[0].accounts[2].pubkey;
+    instructions[0].accounts[1] = AccountMeta::new(depositor.pubkey(), true);
+    instructions[0].accounts[3] = AccountMeta::new(vault, false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -759,7 +1025,7 @@ async fn test_fail_invalid_pool_mint() {
         _deposit_stake_authority_pubkey,
         mut instructions,
     ) = setup_with_ix().await;
-    instructions[1].accounts[6] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    instructions[0].accounts[5] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -775,3 +1041,223 @@ async fn test_fail_invalid_pool_mint() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_success_claim_refunds_rent_payer() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        depositor,
+        deposit_receipt_pda,
+        _deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    let mut deposit_receipt_account = ctx
+        .banks_client
+        .get_account(deposit_receipt_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let deposit_receipt =
+        DepositReceipt::try_from_slice_unchecked_mut(&mut deposit_receipt_account.data).unwrap();
+    // The payer of the deposit is recorded as the rent payer
+    assert_eq!(deposit_receipt.rent_payer, depositor.pubkey());
+    let cool_down_seconds = u64::from(deposit_receipt.cool_down_seconds);
+
+    // Mimic a deposit relayed by a separate fee payer
+    let relayer = Pubkey::new_unique();
+    deposit_receipt.rent_payer = relayer;
+    let receipt_lamports = deposit_receipt_account.lamports;
+    ctx.set_account(
+        &deposit_receipt_pda,
+        &AccountSharedData::from(deposit_receipt_account),
+    );
+    instructions[0].accounts[8] = AccountMeta::new(relayer, false);
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + cool_down_seconds.add(10) as i64,
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The relayer is refunded the DepositReceipt's rent
+    let relayer_account = get_account(&mut ctx.banks_client, &relayer).await;
+    assert_eq!(relayer_account.lamports, receipt_lamports);
+}
+
+#[tokio::test]
+async fn test_fail_invalid_rent_payer() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        depositor,
+        _deposit_receipt_pda,
+        _deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    instructions[0].accounts[1] = AccountMeta::new(depositor.pubkey(), true);
+    instructions[0].accounts[8] = AccountMeta::new(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidRentPayer as u32),
+    )
+    .await;
+}
+
+async fn set_keeper_tip(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    keeper_tip_lamports: u64,
+) {
+    let mut deposit_stake_authority_account =
+        get_account(&mut ctx.banks_client, deposit_stake_authority_pubkey).await;
+    let deposit_stake_authority = StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(
+        &mut deposit_stake_authority_account.data,
+    )
+    .unwrap();
+    deposit_stake_authority.keeper_tip_lamports = keeper_tip_lamports.into();
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(deposit_stake_authority_account),
+    );
+}
+
+#[tokio::test]
+async fn test_success_permissionless_claim_pays_keeper_tip() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        depositor,
+        deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    let keeper_tip_lamports = 100_000;
+    set_keeper_tip(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        keeper_tip_lamports,
+    )
+    .await;
+
+    // The keeper signs as the last account, after any transfer hook accounts
+    let keeper = Keypair::new();
+    airdrop_lamports(&mut ctx, &keeper.pubkey(), LAMPORTS_PER_SOL).await;
+    instructions[0]
+        .accounts
+        .push(AccountMeta::new(keeper.pubkey(), true));
+    let deposit_receipt_account = get_account(&mut ctx.banks_client, &deposit_receipt_pda).await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let depositor_lamports_before = get_account(&mut ctx.banks_client, &depositor.pubkey())
+        .await
+        .lamports;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds).add(10) as i64,
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &keeper],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert!(
+        events.contains(&StakeDepositInterceptorEvent::PayKeeperTip {
+            deposit_receipt: deposit_receipt_pda,
+            keeper: keeper.pubkey(),
+            keeper_tip_lamports,
+        })
+    );
+
+    // The keeper is tipped out of the DepositReceipt's rent and the rent payer gets the rest
+    let keeper_account = get_account(&mut ctx.banks_client, &keeper.pubkey()).await;
+    assert_eq!(
+        keeper_account.lamports,
+        LAMPORTS_PER_SOL + keeper_tip_lamports
+    );
+    let depositor_account = get_account(&mut ctx.banks_client, &depositor.pubkey()).await;
+    assert_eq!(
+        depositor_account.lamports - depositor_lamports_before,
+        deposit_receipt_account.lamports - keeper_tip_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_success_permissionless_claim_without_signing_keeper() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        depositor,
+        deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        mut instructions,
+    ) = setup_with_ix().await;
+    set_keeper_tip(&mut ctx, &deposit_stake_authority_pubkey, 100_000).await;
+
+    // A trailing account that does not sign is not a keeper
+    let keeper = Pubkey::new_unique();
+    instructions[0]
+        .accounts
+        .push(AccountMeta::new(keeper, false));
+    let deposit_receipt_account = get_account(&mut ctx.banks_client, &deposit_receipt_pda).await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let depositor_lamports_before = get_account(&mut ctx.banks_client, &depositor.pubkey())
+        .await
+        .lamports;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds).add(10) as i64,
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The rent payer is refunded all of the DepositReceipt's rent
+    assert!(ctx
+        .banks_client
+        .get_account(keeper)
+        .await
+        .unwrap()
+        .is_none());
+    let depositor_account = get_account(&mut ctx.banks_client, &depositor.pubkey()).await;
+    assert_eq!(
+        depositor_account.lamports - depositor_lamports_before,
+        deposit_receipt_account.lamports
+    );
+}
@@ -0,0 +1,640 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account,
+    get_account_data_deserialized, program_test_context_with_stake_pool_state, set_clock_time,
+    stake_pool_update_all, update_stake_deposit_authority, StakePoolAccounts,
+};
+use jito_bytemuck::AccountDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::AccountSharedData,
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_add_fee_exemption_instruction, create_claim_pool_tokens_instruction,
+        create_close_stake_pool_deposit_stake_authority_instruction,
+        create_register_stake_pool_deposit_stake_authority_instruction,
+        create_remove_fee_exemption_instruction, create_sweep_fees_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+};
+
+/// Deposit and claim during the cool down, leaving the DepositReceipt closed and fees in the
+/// vault. Returns the instructions that sweep the fees out of the vault.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Pubkey,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Vec<Instruction>,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let claim_ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+    );
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let fee_token_account =
+        get_associated_token_address(&fee_wallet.pubkey(), &stake_pool_accounts.pool_mint);
+    let create_fee_token_account_ix = create_associated_token_account(
+        &ctx.payer.pubkey(),
+        &fee_wallet.pubkey(),
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+    );
+    let sweep_fees_ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_account,
+        &[],
+        &stake_pool.pool_mint,
+        &spl_token::id(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        vec![create_fee_token_account_ix, sweep_fees_ix],
+    )
+}
+
+fn close_ix(
+    stake_pool_accounts: &StakePoolAccounts,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    authority: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    create_close_stake_pool_deposit_stake_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.pool_mint,
+        authority,
+        &deposit_stake_authority.base,
+        destination,
+        &spl_token::id(),
+    )
+}
+
+async fn sweep_fees(ctx: &mut ProgramTestContext, sweep_fees_ixs: &[Instruction]) {
+    let tx = Transaction::new_signed_with_payer(
+        sweep_fees_ixs,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+/// Route the StakePool's deposits away from the StakePoolDepositStakeAuthority.
+async fn retire_deposit_stake_authority(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+) {
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        stake_pool_accounts,
+        &Pubkey::new_unique(),
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_close_stake_pool_deposit_stake_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+    retire_deposit_stake_authority(&mut ctx, &stake_pool_accounts).await;
+
+    let rent = ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports
+        + ctx
+            .banks_client
+            .get_account(deposit_stake_authority.vault)
+            .await
+            .unwrap()
+            .unwrap()
+            .lamports;
+
+    let destination = Pubkey::new_unique();
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &destination,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // Both accounts are closed and their rent sent to the destination
+    assert!(ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(ctx
+        .banks_client
+        .get_account(deposit_stake_authority.vault)
+        .await
+        .unwrap()
+        .is_none());
+    let destination_account = ctx
+        .banks_client
+        .get_account(destination)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(destination_account.lamports, rent);
+}
+
+#[tokio::test]
+async fn test_fail_close_vault_not_empty() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        _sweep_fees_ixs,
+    ) = setup().await;
+
+    // The accrued fees are still in the vault
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::VaultNotEmpty as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_close_outstanding_deposit_receipts() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+
+    // Pretend a DepositReceipt is still open
+    let mut account = ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data)
+        .unwrap()
+        .outstanding_receipt_count = 1.into();
+    ctx.set_account(
+        &deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::OutstandingDepositReceipts as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_close_outstanding_fee_exemptions() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+    retire_deposit_stake_authority(&mut ctx, &stake_pool_accounts).await;
+
+    let depositor = Pubkey::new_unique();
+    let add_fee_exemption_ix = create_add_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &authority.pubkey(),
+        &depositor,
+        0,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[add_fee_exemption_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.fee_exemption_count),
+        1
+    );
+
+    // The FeeExemption would otherwise outlive the StakePoolDepositStakeAuthority
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::OutstandingFeeExemptions as u32),
+    )
+    .await;
+
+    // Once the FeeExemption is removed the StakePoolDepositStakeAuthority can be closed
+    let remove_fee_exemption_ix = create_remove_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &authority.pubkey(),
+        &depositor,
+        &ctx.payer.pubkey(),
+    );
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[remove_fee_exemption_ix, ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    assert!(ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_fail_close_invalid_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        _authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+
+    let bad_authority = Keypair::new();
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &bad_authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_close_active_deposit_stake_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+
+    // The StakePool still routes deposits through the StakePoolDepositStakeAuthority
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::DepositStakeAuthorityActive as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_close_registered_deposit_stake_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        sweep_fees_ixs,
+    ) = setup().await;
+    sweep_fees(&mut ctx, &sweep_fees_ixs).await;
+
+    // ctx.payer is the StakePool manager
+    let register_ix = create_register_stake_pool_deposit_stake_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[register_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    retire_deposit_stake_authority(&mut ctx, &stake_pool_accounts).await;
+
+    // The StakePoolRegistry still lists the StakePoolDepositStakeAuthority
+    let ix = close_ix(
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(
+            StakeDepositInterceptorError::DepositStakeAuthorityRegistered as u32,
+        ),
+    )
+    .await;
+}
@@ -0,0 +1,380 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_deposit_authority, create_token_account,
+    get_account, get_account_data_deserialized, program_test_context_with_stake_pool_state,
+    update_sol_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use spl_stake_pool::error::StakePoolError;
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority},
+    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    spl_stake_pool::state::StakePool,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Keypair,
+    Keypair,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's sol_deposit_authority to the interceptor program's PDA
+    update_sol_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let _depositor_lst_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    (
+        ctx,
+        stake_pool_accounts,
+        stake_pool,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_authority_base,
+    )
+}
+
+#[tokio::test]
+async fn test_deposit_sol() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_authority_base,
+    ) = setup().await;
+
+    let deposit_sol_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_sol_instruction],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let vault_account = get_account(&mut ctx.banks_client, &deposit_stake_authority.vault).await;
+    let vault = spl_token::state::Account::unpack(&vault_account.data).unwrap();
+    assert!(vault.amount > 0);
+
+    // Assert DepositReceipt has correct data.
+    let (deposit_receipt_pda, bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.owner, depositor.pubkey());
+    assert_eq!(deposit_receipt.base, deposit_receipt_base.pubkey());
+    assert_eq!(deposit_receipt.stake_pool, stake_pool_accounts.stake_pool);
+    assert_eq!(
+        deposit_receipt.stake_pool_deposit_stake_authority,
+        deposit_stake_authority_pubkey
+    );
+    assert_eq!(deposit_receipt.bump_seed, bump_seed);
+    // assert all minted LST is tracked by the DepositReceipt
+    assert_eq!(u64::from(deposit_receipt.lst_amount), vault.amount);
+    assert_eq!(
+        deposit_receipt.cool_down_seconds,
+        deposit_stake_authority.cool_down_seconds
+    );
+    assert_eq!(
+        deposit_receipt.initial_fee_bps,
+        deposit_stake_authority.inital_fee_bps
+    );
+    assert_eq!(deposit_receipt.fee_curve, deposit_stake_authority.fee_curve);
+    let deposit_time: u64 = deposit_receipt.deposit_time.into();
+    assert!(deposit_time > 0);
+}
+
+#[tokio::test]
+async fn success_error_with_slippage() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        stake_pool,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_authority_base,
+    ) = setup().await;
+
+    let pool_tokens_amount = spl_stake_pool::state::StakePool::calc_pool_tokens_for_deposit(
+        &stake_pool,
+        LAMPORTS_PER_SOL,
+    )
+    .unwrap();
+
+    // Requesting more than the full amount (before any SOL deposit fee) must fail
+    let deposit_sol_with_slippage_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_with_slippage_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+            pool_tokens_amount + 1,
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_sol_with_slippage_instruction],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakePoolError::ExceededSlippage as u32),
+    )
+    .await;
+
+    let deposit_sol_with_slippage_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_with_slippage_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+            1,
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_sol_with_slippage_instruction],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+async fn setup_with_ix() -> (ProgramTestContext, Keypair, Keypair, Instruction) {
+    let (
+        ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_authority_base,
+    ) = setup().await;
+
+    let deposit_sol_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+        );
+
+    (
+        ctx,
+        depositor,
+        deposit_receipt_base,
+        deposit_sol_instruction,
+    )
+}
+
+#[tokio::test]
+async fn test_fail_invalid_system_program() {
+    let (mut ctx, depositor, deposit_receipt_base, mut ix) = setup_with_ix().await;
+    ix.accounts[14] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(&mut ctx, tx, InstructionError::IncorrectProgramId).await;
+}
+
+#[tokio::test]
+async fn test_fail_invalid_stake_pool_program() {
+    let (mut ctx, depositor, deposit_receipt_base, mut ix) = setup_with_ix().await;
+    ix.accounts[1] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidStakePoolProgram as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_invalid_vault() {
+    let (mut ctx, depositor, deposit_receipt_base, mut ix) = setup_with_ix().await;
+    ix.accounts[9] = AccountMeta::new(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidVault as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_base_not_signer() {
+    let (mut ctx, depositor, deposit_receipt_base, mut ix) = setup_with_ix().await;
+    ix.accounts[5] = AccountMeta::new_readonly(deposit_receipt_base.pubkey(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::SignatureMissing as u32),
+    )
+    .await;
+}
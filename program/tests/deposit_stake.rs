@@ -7,6 +7,7 @@ use helpers::{
     program_test_context_with_stake_pool_state, stake_pool_update_all,
     update_stake_deposit_authority, StakePoolAccounts, ValidatorStakeAccount,
 };
+use jito_bytemuck::AccountDeserialize;
 use solana_program_test::ProgramTestContext;
 use solana_sdk::{
     account::AccountSharedData,
@@ -22,12 +23,19 @@ use solana_sdk::{
     transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
 use spl_pod::primitives::PodU64;
 use spl_stake_pool::error::StakePoolError;
+use spl_token_2022::extension::StateWithExtensions;
 use stake_deposit_interceptor::{
     error::StakeDepositInterceptorError,
-    instruction::{derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority},
+    instruction::{
+        derive_receipt_mint, derive_stake_deposit_receipt,
+        derive_stake_pool_deposit_stake_authority, DepositStakeArgs,
+        StakeDepositInterceptorInstruction,
+    },
     state::{DepositReceipt, StakePoolDepositStakeAuthority},
 };
 
@@ -255,8 +263,21 @@ async fn test_deposit_stake() {
         deposit_receipt.initial_fee_bps,
         deposit_stake_authority.inital_fee_bps
     );
+    assert_eq!(deposit_receipt.fee_curve, deposit_stake_authority.fee_curve);
     let deposit_time: u64 = deposit_receipt.deposit_time.into();
     assert!(deposit_time > 0);
+
+    // The DepositReceipt is counted as outstanding
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.outstanding_receipt_count),
+        1
+    );
 }
 
 #[tokio::test]
@@ -359,6 +380,79 @@ async fn success_error_with_slippage() {
     ctx.banks_client.process_transaction(tx).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_deposit_stake_tokenized() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_tokenized_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The DepositReceipt is tokenized and its receipt token was minted to the owner
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let (receipt_mint, _bump_seed) =
+        derive_receipt_mint(&stake_deposit_interceptor::id(), &deposit_receipt_pda);
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.owner, depositor.pubkey());
+    assert_eq!(deposit_receipt.receipt_mint, receipt_mint);
+
+    let receipt_token_account = get_associated_token_address_with_program_id(
+        &depositor.pubkey(),
+        &receipt_mint,
+        &spl_token_2022::id(),
+    );
+    let receipt_token_account = get_account(&mut ctx.banks_client, &receipt_token_account).await;
+    let receipt_token_account =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&receipt_token_account.data)
+            .unwrap();
+    assert_eq!(receipt_token_account.base.amount, 1);
+}
+
 async fn setup_with_ix() -> (
     ProgramTestContext,
     StakePoolAccounts,
@@ -511,7 +605,7 @@ async fn test_fail_invalid_stake_deposit_authority_address() {
         mut instructions,
     ) = setup_with_ix().await;
     let bad_account = clone_account_to_new_address(&mut ctx, &deposit_stake_authority_pubkey).await;
-    instructions[2].accounts[5] = AccountMeta::new_readonly(bad_account, false);
+    instructions[2].accounts[5] = AccountMeta::new(bad_account, false);
 
     let tx = Transaction::new_signed_with_payer(
         &instructions,
@@ -671,3 +765,237 @@ async fn test_fail_invalid_stake_pool() {
     )
     .await;
 }
+
+/// Overwrite the StakePoolDepositStakeAuthority's deposit limits.
+async fn set_deposit_limits(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    max_deposit_lst_per_receipt: u64,
+    max_total_outstanding_lst: u64,
+    min_deposit_lst: u64,
+) {
+    let mut account = ctx
+        .banks_client
+        .get_account(*deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let deposit_stake_authority =
+        StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    deposit_stake_authority.max_deposit_lst_per_receipt = max_deposit_lst_per_receipt.into();
+    deposit_stake_authority.max_total_outstanding_lst = max_total_outstanding_lst.into();
+    deposit_stake_authority.min_deposit_lst = min_deposit_lst.into();
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+}
+
+#[tokio::test]
+async fn test_fail_max_deposit_per_receipt_exceeded() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        _deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        instructions,
+    ) = setup_with_ix().await;
+    set_deposit_limits(&mut ctx, &deposit_stake_authority_pubkey, 1, 0, 0).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::MaxDepositPerReceiptExceeded as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_max_total_outstanding_lst_exceeded() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        _deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        instructions,
+    ) = setup_with_ix().await;
+    set_deposit_limits(&mut ctx, &deposit_stake_authority_pubkey, 0, 1, 0).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(
+            StakeDepositInterceptorError::MaxTotalOutstandingLstExceeded as u32,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_deposit_below_minimum() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        _deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        instructions,
+    ) = setup_with_ix().await;
+    set_deposit_limits(&mut ctx, &deposit_stake_authority_pubkey, 0, 0, u64::MAX).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::DepositBelowMinimum as u32),
+    )
+    .await;
+}
+
+/// Overwrite whether the StakePoolDepositStakeAuthority requires the owner to sign deposits.
+async fn set_require_owner_signature(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    require_owner_signature: bool,
+) {
+    let mut account = ctx
+        .banks_client
+        .get_account(*deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let deposit_stake_authority =
+        StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    deposit_stake_authority.require_owner_signature = require_owner_signature.into();
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+}
+
+#[tokio::test]
+async fn test_success_owner_signature_required() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        instructions,
+    ) = setup_with_ix().await;
+    set_require_owner_signature(&mut ctx, &deposit_stake_authority_pubkey, true).await;
+
+    // The depositor signs the authorize instructions, and so the deposit, as the owner
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.owner, depositor.pubkey());
+}
+
+#[tokio::test]
+async fn test_fail_owner_signature_required_owner_not_signer() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        _deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        mut instructions,
+    ) = setup_with_ix().await;
+    set_require_owner_signature(&mut ctx, &deposit_stake_authority_pubkey, true).await;
+
+    // A relayer of the already authorized stake account names itself as the owner
+    let relayer = Keypair::new();
+    let deposit_stake_ix = instructions.last_mut().unwrap();
+    deposit_stake_ix.data = borsh::to_vec(&StakeDepositInterceptorInstruction::DepositStake(
+        DepositStakeArgs {
+            owner: relayer.pubkey(),
+        },
+    ))
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::OwnerSignatureRequired as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_owner_signature_required_without_fee_exemption() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_receipt_base,
+        deposit_receipt_pda,
+        deposit_stake_authority_pubkey,
+        depositor,
+        mut instructions,
+    ) = setup_with_ix().await;
+    set_require_owner_signature(&mut ctx, &deposit_stake_authority_pubkey, true).await;
+
+    // The owner is found by key, so it may directly follow the System program
+    let deposit_stake_ix = instructions.last_mut().unwrap();
+    deposit_stake_ix.accounts.remove(20);
+    assert_eq!(deposit_stake_ix.accounts[20].pubkey, depositor.pubkey());
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.owner, depositor.pubkey());
+}
@@ -0,0 +1,739 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, stake_pool_update_all,
+    update_stake_deposit_authority, StakePoolAccounts, ValidatorStakeAccount,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_pod::primitives::PodU32;
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_add_fee_exemption_instruction, create_deposit_stake_instruction,
+        create_remove_fee_exemption_instruction, derive_fee_exemption,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority, DepositStakeArgs,
+        StakeDepositInterceptorInstruction,
+    },
+    state::{DepositReceipt, FeeExemption, StakePoolDepositStakeAuthority},
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    spl_stake_pool::state::StakePool,
+    ValidatorStakeAccount,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Keypair,
+    Pubkey,
+    Keypair,
+    Keypair,
+    u64,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let total_staked_amount =
+        rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>()) + stake_amount;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let _depositor_lst_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    (
+        ctx,
+        stake_pool_accounts,
+        stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        total_staked_amount,
+    )
+}
+
+/// Build the DepositStake instructions for the `depositor`'s stake account.
+fn deposit_stake_instructions(
+    stake_pool_accounts: &StakePoolAccounts,
+    validator_stake_accounts: &ValidatorStakeAccount,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    depositor: &Keypair,
+    depositor_stake_account: &Pubkey,
+    deposit_receipt_base: &Keypair,
+    deposit_authority_base: &Keypair,
+) -> Vec<Instruction> {
+    create_deposit_stake_instruction(
+        &stake_deposit_interceptor::id(),
+        &depositor.pubkey(),
+        &spl_stake_pool::id(),
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.validator_list,
+        &stake_pool_accounts.withdraw_authority,
+        depositor_stake_account,
+        &depositor.pubkey(),
+        &validator_stake_accounts.stake_account,
+        &stake_pool_accounts.reserve_stake_account,
+        &deposit_stake_authority.vault,
+        &stake_pool_accounts.pool_fee_account,
+        &stake_pool_accounts.pool_fee_account,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &deposit_receipt_base.pubkey(),
+        &deposit_authority_base.pubkey(),
+    )
+}
+
+/// Grant the `depositor` a FeeExemption with the given `initial_fee_bps`. Returns the logged
+/// events.
+async fn add_fee_exemption(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    authority: &Keypair,
+    depositor: &Pubkey,
+    initial_fee_bps: u32,
+) -> Vec<StakeDepositInterceptorEvent> {
+    let ix = create_add_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        deposit_stake_authority_pubkey,
+        &authority.pubkey(),
+        depositor,
+        initial_fee_bps,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, authority],
+        ctx.last_blockhash,
+    );
+    process_transaction_events(ctx, tx).await
+}
+
+#[tokio::test]
+async fn test_add_fee_exemption() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        authority,
+        depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    let events = add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &depositor.pubkey(),
+        5,
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::AddFeeExemption {
+            deposit_stake_authority: deposit_stake_authority_pubkey,
+            depositor: depositor.pubkey(),
+            initial_fee_bps: 5,
+        }]
+    );
+
+    let (fee_exemption_pda, bump_seed) = derive_fee_exemption(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let fee_exemption =
+        get_account_data_deserialized::<FeeExemption>(&mut ctx.banks_client, &fee_exemption_pda)
+            .await;
+    assert_eq!(
+        fee_exemption.stake_pool_deposit_stake_authority,
+        deposit_stake_authority_pubkey
+    );
+    assert_eq!(fee_exemption.depositor, depositor.pubkey());
+    assert_eq!(fee_exemption.initial_fee_bps, PodU32::from(5));
+    assert_eq!(fee_exemption.bump_seed, bump_seed);
+}
+
+#[tokio::test]
+async fn test_deposit_stake_with_fee_exemption() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &depositor.pubkey(),
+        5,
+    )
+    .await;
+
+    let instructions = deposit_stake_instructions(
+        &stake_pool_accounts,
+        &validator_stake_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &depositor_stake_account,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.initial_fee_bps, PodU32::from(5));
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::Deposit {
+            deposit_receipt: deposit_receipt_pda,
+            deposit_stake_authority: deposit_stake_authority_pubkey,
+            owner: depositor.pubkey(),
+            pool_tokens_minted: deposit_receipt.lst_amount.into(),
+            deposit_time: deposit_receipt.deposit_time.into(),
+            initial_fee_bps: 5,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_fee_exemption_does_not_raise_fee() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    // FeeExemption rate is higher than the StakePoolDepositStakeAuthority's
+    add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &depositor.pubkey(),
+        5_000,
+    )
+    .await;
+
+    let instructions = deposit_stake_instructions(
+        &stake_pool_accounts,
+        &validator_stake_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &depositor_stake_account,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(
+        deposit_receipt.initial_fee_bps,
+        deposit_stake_authority.inital_fee_bps
+    );
+}
+
+#[tokio::test]
+async fn test_remove_fee_exemption() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &depositor.pubkey(),
+        0,
+    )
+    .await;
+
+    let destination = Pubkey::new_unique();
+    let ix = create_remove_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &authority.pubkey(),
+        &depositor.pubkey(),
+        &destination,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (fee_exemption_pda, _bump_seed) = derive_fee_exemption(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let fee_exemption_account = ctx
+        .banks_client
+        .get_account(fee_exemption_pda)
+        .await
+        .unwrap();
+    assert!(fee_exemption_account.is_none());
+    let destination_account = ctx
+        .banks_client
+        .get_account(destination)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(destination_account.lamports > 0);
+
+    // DepositReceipt falls back to the StakePoolDepositStakeAuthority's rate
+    let instructions = deposit_stake_instructions(
+        &stake_pool_accounts,
+        &validator_stake_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &depositor_stake_account,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(
+        deposit_receipt.initial_fee_bps,
+        deposit_stake_authority.inital_fee_bps
+    );
+}
+
+#[tokio::test]
+async fn test_fail_add_fee_exemption_invalid_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        _authority,
+        depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    let bad_authority = Keypair::new();
+    let ix = create_add_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &bad_authority.pubkey(),
+        &depositor.pubkey(),
+        0,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_add_fee_exemption_fee_too_high() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        authority,
+        depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    let ix = create_add_fee_exemption_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &authority.pubkey(),
+        &depositor.pubkey(),
+        10_001,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InitialFeeRateMaxExceeded as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_deposit_stake_with_other_depositors_fee_exemption() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    let other_depositor = Pubkey::new_unique();
+    add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &other_depositor,
+        0,
+    )
+    .await;
+    let (other_fee_exemption, _bump_seed) = derive_fee_exemption(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &other_depositor,
+    );
+
+    let mut instructions = deposit_stake_instructions(
+        &stake_pool_accounts,
+        &validator_stake_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &depositor_stake_account,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+    );
+    // Replace the depositor's FeeExemption with another depositor's
+    let deposit_ix = instructions.last_mut().unwrap();
+    deposit_ix.accounts[20] = AccountMeta::new_readonly(other_fee_exemption, false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeExemption as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_deposit_stake_naming_exempt_owner_without_signature() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+
+    let exempt_depositor = Pubkey::new_unique();
+    add_fee_exemption(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        &authority,
+        &exempt_depositor,
+        0,
+    )
+    .await;
+    let (exempt_fee_exemption, _bump_seed) = derive_fee_exemption(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &exempt_depositor,
+    );
+
+    let mut instructions = deposit_stake_instructions(
+        &stake_pool_accounts,
+        &validator_stake_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &depositor_stake_account,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+    );
+    // Name the exempt depositor as the owner, with their FeeExemption, but without their signature
+    let deposit_ix = instructions.last_mut().unwrap();
+    deposit_ix.accounts[20] = AccountMeta::new_readonly(exempt_fee_exemption, false);
+    deposit_ix.data = borsh::to_vec(&StakeDepositInterceptorInstruction::DepositStake(
+        DepositStakeArgs {
+            owner: exempt_depositor,
+        },
+    ))
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::OwnerSignatureRequired as u32),
+    )
+    .await;
+}
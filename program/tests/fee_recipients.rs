@@ -0,0 +1,677 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account, get_account,
+    get_account_data_deserialized, program_test_context_with_stake_pool_state, set_clock_time,
+    stake_pool_update_all, update_stake_deposit_authority, StakePoolAccounts,
+    ValidatorStakeAccount,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token_2022::state::Account;
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_claim_pool_tokens_instruction, create_set_fee_recipients_instruction,
+        create_sweep_fees_instruction, derive_stake_deposit_receipt,
+        derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, FeeRecipient, StakePoolDepositStakeAuthority},
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    spl_stake_pool::state::StakePool,
+    ValidatorStakeAccount,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Pubkey,
+    Keypair,
+    Keypair,
+    u64,
+    Pubkey,
+    Keypair,
+    Keypair,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let total_staked_amount =
+        rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>()) + stake_amount;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    (
+        ctx,
+        stake_pool_accounts,
+        stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        total_staked_amount,
+        depositor_pool_token_account,
+        fee_wallet,
+        authority,
+    )
+}
+
+/// Replace the StakePoolDepositStakeAuthority's fee recipients.
+async fn set_fee_recipients(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    authority: &Keypair,
+    deposit_authority_base: &Keypair,
+    fee_recipients: Vec<FeeRecipient>,
+) {
+    let ix = create_set_fee_recipients_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+        fee_recipients,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+/// Create the ATA of each wallet for the StakePool's `pool_mint`.
+async fn create_fee_token_accounts(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    wallets: &[Pubkey],
+) -> Vec<Pubkey> {
+    let instructions: Vec<_> = wallets
+        .iter()
+        .map(|wallet| {
+            create_associated_token_account(
+                &ctx.payer.pubkey(),
+                wallet,
+                &stake_pool_accounts.pool_mint,
+                &spl_token::id(),
+            )
+        })
+        .collect();
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    wallets
+        .iter()
+        .map(|wallet| get_associated_token_address(wallet, &stake_pool_accounts.pool_mint))
+        .collect()
+}
+
+async fn get_token_amount(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = get_account(&mut ctx.banks_client, token_account).await;
+    Account::unpack(&account.data.as_slice()).unwrap().amount
+}
+
+/// Claim the DepositReceipt halfway through its cool down so fees accrue in the vault.
+/// Returns the fee amount.
+async fn claim_pool_tokens_during_cool_down(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    depositor: &Keypair,
+    deposit_receipt_base: &Keypair,
+    deposit_authority_base: &Keypair,
+    depositor_pool_token_account: &Pubkey,
+) -> u64 {
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+    );
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let fee_amount = deposit_receipt.calculate_fee_amount(clock_time);
+    assert!(fee_amount > 0);
+    fee_amount
+}
+
+#[tokio::test]
+async fn test_set_fee_recipients() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        _depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        _depositor_pool_token_account,
+        _fee_wallet,
+        authority,
+    ) = setup().await;
+
+    let fee_recipients = vec![
+        FeeRecipient::new(Pubkey::new_unique(), 5_000),
+        FeeRecipient::new(Pubkey::new_unique(), 2_500),
+    ];
+    set_fee_recipients(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        fee_recipients.clone(),
+    )
+    .await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(
+        deposit_stake_authority.fee_recipients,
+        [
+            fee_recipients[0],
+            fee_recipients[1],
+            FeeRecipient::default()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_sweep_fees_with_fee_recipients() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        depositor_pool_token_account,
+        fee_wallet,
+        authority,
+    ) = setup().await;
+
+    let recipient_wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+    set_fee_recipients(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        vec![
+            FeeRecipient::new(recipient_wallets[0], 5_000),
+            FeeRecipient::new(recipient_wallets[1], 2_500),
+        ],
+    )
+    .await;
+    let fee_token_accounts = create_fee_token_accounts(
+        &mut ctx,
+        &stake_pool_accounts,
+        &[
+            fee_wallet.pubkey(),
+            recipient_wallets[0],
+            recipient_wallets[1],
+        ],
+    )
+    .await;
+    let fee_amount = claim_pool_tokens_during_cool_down(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+        &depositor_pool_token_account,
+    )
+    .await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_accounts[0],
+        &fee_token_accounts[1..],
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let first_share = fee_amount * 5_000 / 10_000;
+    let second_share = fee_amount * 2_500 / 10_000;
+    assert_eq!(
+        get_token_amount(&mut ctx, &fee_token_accounts[1]).await,
+        first_share
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &fee_token_accounts[2]).await,
+        second_share
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &fee_token_accounts[0]).await,
+        fee_amount - first_share - second_share
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &deposit_stake_authority.vault).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_fail_sweep_invalid_fee_recipient_token_account() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        depositor_pool_token_account,
+        fee_wallet,
+        authority,
+    ) = setup().await;
+
+    set_fee_recipients(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        vec![FeeRecipient::new(Pubkey::new_unique(), 5_000)],
+    )
+    .await;
+    let fee_token_accounts =
+        create_fee_token_accounts(&mut ctx, &stake_pool_accounts, &[fee_wallet.pubkey()]).await;
+    claim_pool_tokens_during_cool_down(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+        &depositor_pool_token_account,
+    )
+    .await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Pass the depositor's token account in place of the fee recipient's
+    let ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_accounts[0],
+        &[depositor_pool_token_account],
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeTokenAccount as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_sweep_missing_fee_recipient_token_account() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        depositor_pool_token_account,
+        fee_wallet,
+        authority,
+    ) = setup().await;
+
+    set_fee_recipients(
+        &mut ctx,
+        &stake_pool_accounts,
+        &authority,
+        &deposit_authority_base,
+        vec![FeeRecipient::new(Pubkey::new_unique(), 5_000)],
+    )
+    .await;
+    let fee_token_accounts =
+        create_fee_token_accounts(&mut ctx, &stake_pool_accounts, &[fee_wallet.pubkey()]).await;
+    claim_pool_tokens_during_cool_down(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &depositor,
+        &deposit_receipt_base,
+        &deposit_authority_base,
+        &depositor_pool_token_account,
+    )
+    .await;
+
+    let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_accounts[0],
+        &[],
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(&mut ctx, tx, InstructionError::NotEnoughAccountKeys).await;
+}
+
+#[tokio::test]
+async fn test_fail_set_fee_recipients_exceeds_max_bps() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        _depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        _depositor_pool_token_account,
+        _fee_wallet,
+        authority,
+    ) = setup().await;
+
+    let ix = create_set_fee_recipients_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+        vec![
+            FeeRecipient::new(Pubkey::new_unique(), 6_000),
+            FeeRecipient::new(Pubkey::new_unique(), 4_001),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeRecipients as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_set_fee_recipients_invalid_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        _depositor,
+        _depositor_stake_account,
+        _deposit_receipt_base,
+        deposit_authority_base,
+        _total_staked_amount,
+        _depositor_pool_token_account,
+        _fee_wallet,
+        _authority,
+    ) = setup().await;
+
+    let bad_authority = Keypair::new();
+    let ix = create_set_fee_recipients_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &bad_authority.pubkey(),
+        &deposit_authority_base.pubkey(),
+        vec![FeeRecipient::new(Pubkey::new_unique(), 5_000)],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
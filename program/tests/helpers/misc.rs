@@ -1,3 +1,4 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
 use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
 use solana_sdk::{
     account::AccountSharedData,
@@ -7,8 +8,11 @@ use solana_sdk::{
     transaction::{Transaction, TransactionError},
     transport::TransportError,
 };
+use stake_deposit_interceptor::events::StakeDepositInterceptorEvent;
 
-use super::{create_stake_pool, StakePoolAccounts};
+use super::{
+    create_stake_pool, create_stake_pool_with_mint, create_transfer_fee_mint, StakePoolAccounts,
+};
 
 pub fn program_test_with_stake_pool_program() -> ProgramTest {
     let mut program_test = ProgramTest::default();
@@ -34,6 +38,19 @@ pub async fn program_test_context_with_stake_pool_state() -> (ProgramTestContext
     (ctx, stake_pool_accounts)
 }
 
+/// Same as `program_test_context_with_stake_pool_state`, but the StakePool's `pool_mint` is a
+/// Token-2022 mint charging `transfer_fee_basis_points` on every transfer.
+pub async fn program_test_context_with_transfer_fee_stake_pool_state(
+    transfer_fee_basis_points: u16,
+) -> (ProgramTestContext, StakePoolAccounts) {
+    let mut ctx = program_test_with_stake_pool_program()
+        .start_with_context()
+        .await;
+    let pool_mint = create_transfer_fee_mint(&mut ctx, transfer_fee_basis_points, u64::MAX).await;
+    let stake_pool_accounts = create_stake_pool_with_mint(&mut ctx, &pool_mint).await;
+    (ctx, stake_pool_accounts)
+}
+
 /// Clones all the existing account information and data to a new account. Returns the
 /// new address of the account.
 pub async fn clone_account_to_new_address(
@@ -76,3 +93,26 @@ pub async fn assert_transaction_err(
         _ => panic!("Wrong error"),
     };
 }
+
+/// Process the transaction, asserting it succeeds, and return the StakeDepositInterceptorEvents
+/// decoded from its `Program data:` logs in order.
+pub async fn process_transaction_events(
+    ctx: &mut ProgramTestContext,
+    tx: Transaction,
+) -> Vec<StakeDepositInterceptorEvent> {
+    let result = ctx
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    result
+        .metadata
+        .expect("Should have metadata")
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| BASE64_STANDARD.decode(data).ok())
+        .filter_map(|data| StakeDepositInterceptorEvent::from_log_data(&data))
+        .collect()
+}
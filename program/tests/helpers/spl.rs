@@ -7,7 +7,11 @@ use solana_sdk::{
     system_instruction::{self, create_account},
     transaction::Transaction,
 };
-use spl_token::state::{Account, Mint};
+use spl_token::state::Mint;
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::Account,
+};
 
 /// Create a SPL Token mint account and return the Pubkey.
 /// ProgramTestContext `payer`` is the Mint's `mint_authority`.`
@@ -42,20 +46,82 @@ pub async fn create_mint(ctx: &mut ProgramTestContext) -> Pubkey {
     keypair.pubkey()
 }
 
-/// Create a SPL Token Account owned by the ProgramTestContext `payer`
+/// Create a Token-2022 mint account with a `TransferFeeConfig` and return the Pubkey.
+/// ProgramTestContext `payer`` is the Mint's `mint_authority` and transfer fee authority.
+pub async fn create_transfer_fee_mint(
+    ctx: &mut ProgramTestContext,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Pubkey {
+    let keypair = Keypair::new();
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ])
+    .unwrap();
+    let init_account_ix = create_account(
+        &ctx.payer.pubkey(),
+        &keypair.pubkey(),
+        rent.minimum_balance(space),
+        space as u64,
+        &spl_token_2022::id(),
+    );
+    let init_transfer_fee_ix =
+        spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config(
+            &spl_token_2022::id(),
+            &keypair.pubkey(),
+            Some(&ctx.payer.pubkey()),
+            Some(&ctx.payer.pubkey()),
+            transfer_fee_basis_points,
+            maximum_fee,
+        )
+        .unwrap();
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint(
+        &spl_token_2022::id(),
+        &keypair.pubkey(),
+        &ctx.payer.pubkey(),
+        None,
+        9,
+    )
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[init_account_ix, init_transfer_fee_ix, init_mint_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &keypair],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    keypair.pubkey()
+}
+
+/// Create a Token Account owned by the ProgramTestContext `payer`, under the mint's token
+/// program and with any extensions the mint requires.
 pub async fn create_token_account(ctx: &mut ProgramTestContext, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
     let keypair = Keypair::new();
     let rent = ctx.banks_client.get_rent().await.unwrap();
+    let mint_account = ctx.banks_client.get_account(*mint).await.unwrap().unwrap();
+    let token_program_id = mint_account.owner;
+    let mint_state =
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data).unwrap();
+    let space = ExtensionType::try_calculate_account_len::<Account>(
+        &ExtensionType::get_required_init_account_extensions(
+            &mint_state.get_extension_types().unwrap(),
+        ),
+    )
+    .unwrap();
 
     let init_account_ix = system_instruction::create_account(
         &ctx.payer.pubkey(),
         &keypair.pubkey(),
-        rent.minimum_balance(Account::LEN),
-        Account::LEN as u64,
-        &spl_token::id(),
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program_id,
     );
-    let init_token_account_ix = spl_token::instruction::initialize_account3(
-        &spl_token::id(),
+    let init_token_account_ix = spl_token_2022::instruction::initialize_account3(
+        &token_program_id,
         &keypair.pubkey(),
         mint,
         owner,
@@ -1,6 +1,7 @@
 use solana_program::pubkey::Pubkey;
 use solana_program_test::ProgramTestContext;
 use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+use stake_deposit_interceptor::state::FeeCurveConfig;
 
 /// Create and initialize a `StakePoolDepositStakeAuthority`.
 pub async fn create_stake_deposit_authority(
@@ -17,6 +18,13 @@ pub async fn create_stake_deposit_authority(
     }
     let cool_down_seconds = 100;
     let initial_fee_bps = 20;
+    let token_program_id = ctx
+        .banks_client
+        .get_account(*stake_pool_mint)
+        .await
+        .unwrap()
+        .unwrap()
+        .owner;
     let init_ix =
         stake_deposit_interceptor::instruction::create_init_deposit_stake_authority_instruction(
             &stake_deposit_interceptor::id(),
@@ -24,10 +32,11 @@ pub async fn create_stake_deposit_authority(
             &stake_pool_pubkey,
             stake_pool_mint,
             &spl_stake_pool::id(),
-            &spl_token::id(),
+            &token_program_id,
             &fee_wallet,
             cool_down_seconds,
             initial_fee_bps,
+            FeeCurveConfig::Linear,
             &authority.pubkey(),
             &base.pubkey(),
         );
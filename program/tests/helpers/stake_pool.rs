@@ -181,11 +181,24 @@ pub struct StakePoolAccounts {
     pub withdraw_authority: Pubkey,
     pub pool_fee_account: Pubkey,
     pub validator_list: Pubkey,
+    /// Token program of the `pool_mint`
+    pub token_program_id: Pubkey,
 }
 
 /// Create a stake pool and all of it's dependencies including the SPL Mint.
 pub async fn create_stake_pool(ctx: &mut ProgramTestContext) -> StakePoolAccounts {
     let pool_mint = create_mint(ctx).await;
+    create_stake_pool_with_mint(ctx, &pool_mint).await
+}
+
+/// Create a stake pool and all of it's dependencies for an existing `pool_mint`, which may
+/// belong to either SPL Token or Token-2022.
+pub async fn create_stake_pool_with_mint(
+    ctx: &mut ProgramTestContext,
+    pool_mint: &Pubkey,
+) -> StakePoolAccounts {
+    let pool_mint = *pool_mint;
+    let token_program_id = get_account(&mut ctx.banks_client, &pool_mint).await.owner;
     let pool_fee_account = create_token_account(ctx, &ctx.payer.pubkey(), &pool_mint).await;
     let max_validators = 5;
 
@@ -235,11 +248,11 @@ pub async fn create_stake_pool(ctx: &mut ProgramTestContext) -> StakePoolAccount
         validator_list_size as u64,
         &spl_stake_pool::id(),
     );
-    let update_mint_authority_ix = spl_token::instruction::set_authority(
-        &spl_token::id(),
+    let update_mint_authority_ix = spl_token_2022::instruction::set_authority(
+        &token_program_id,
         &pool_mint,
         Some(&withdraw_authority),
-        spl_token::instruction::AuthorityType::MintTokens,
+        spl_token_2022::instruction::AuthorityType::MintTokens,
         &ctx.payer.pubkey(),
         &[],
     )
@@ -255,7 +268,7 @@ pub async fn create_stake_pool(ctx: &mut ProgramTestContext) -> StakePoolAccount
         &reserve_stake_account,
         &pool_mint,
         &pool_fee_account,
-        &spl_token::id(),
+        &token_program_id,
         None,
         zero_fee,
         zero_fee,
@@ -285,6 +298,7 @@ pub async fn create_stake_pool(ctx: &mut ProgramTestContext) -> StakePoolAccount
         withdraw_authority,
         pool_fee_account,
         validator_list: validator_list_keypair.pubkey(),
+        token_program_id,
     }
 }
 
@@ -312,6 +326,30 @@ pub async fn update_stake_deposit_authority(
     banks_client.process_transaction(transaction).await.unwrap();
 }
 
+/// Set the StakePool's `sol_deposit_authority`
+pub async fn update_sol_deposit_authority(
+    banks_client: &mut BanksClient,
+    stake_pool_accounts: &StakePoolAccounts,
+    new_sol_deposit_authority: &Pubkey,
+    manager: &Keypair,
+    recent_blockhash: Hash,
+) {
+    let instruction = spl_stake_pool::instruction::set_funding_authority(
+        &spl_stake_pool::id(),
+        &stake_pool_accounts.stake_pool,
+        &manager.pubkey(),
+        Some(new_sol_deposit_authority),
+        spl_stake_pool::instruction::FundingType::SolDeposit,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&manager.pubkey()),
+        &[manager],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
 /// Deposit Sol into the stake pool
 pub async fn deposit_sol(
     banks_client: &mut BanksClient,
@@ -323,6 +361,7 @@ pub async fn deposit_sol(
     pool_fee_account: &Pubkey,
     recent_blockhash: &Hash,
     pool_mint_account: &Pubkey,
+    token_program_id: &Pubkey,
     amount: u64,
 ) {
     let signers = vec![payer];
@@ -336,7 +375,7 @@ pub async fn deposit_sol(
         pool_fee_account,
         pool_fee_account,
         pool_mint,
-        &spl_token::id(),
+        token_program_id,
         amount,
     );
     let transaction = Transaction::new_signed_with_payer(
@@ -380,6 +419,7 @@ pub async fn create_validator_and_add_to_pool(
         &stake_pool_accounts.pool_fee_account,
         &ctx.last_blockhash,
         &pool_token_account,
+        &stake_pool_accounts.token_program_id,
         current_minimum_delegation + stake_rent,
     )
     .await;
@@ -454,7 +494,7 @@ pub async fn stake_pool_update_all(
             &stake_pool_accounts.reserve_stake_account,
             &stake_pool_accounts.pool_fee_account,
             &stake_pool_accounts.pool_mint,
-            &spl_token::id(),
+            &stake_pool_accounts.token_program_id,
         ),
         spl_stake_pool::instruction::cleanup_removed_validator_entries(
             &spl_stake_pool::id(),
@@ -1,7 +1,8 @@
 mod helpers;
 
 use helpers::{
-    assert_transaction_err, program_test_context_with_stake_pool_state, StakePoolAccounts,
+    assert_transaction_err, process_transaction_events, program_test_context_with_stake_pool_state,
+    StakePoolAccounts,
 };
 use jito_bytemuck::AccountDeserialize;
 use solana_program_test::ProgramTestContext;
@@ -18,8 +19,10 @@ use solana_sdk::{
 };
 use spl_associated_token_account::get_associated_token_address;
 use stake_deposit_interceptor::{
-    error::StakeDepositInterceptorError, instruction::derive_stake_pool_deposit_stake_authority,
-    state::StakePoolDepositStakeAuthority,
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::derive_stake_pool_deposit_stake_authority,
+    state::{FeeCurve, FeeCurveConfig, StakePoolDepositStakeAuthority},
 };
 
 #[tokio::test]
@@ -42,6 +45,7 @@ async fn test_init_deposit_stake_authority() {
             &fee_wallet.pubkey(),
             cool_down_seconds,
             initial_fee_bps,
+            FeeCurveConfig::Linear,
             &authority.pubkey(),
             &deposit_authority_base.pubkey(),
         );
@@ -53,13 +57,27 @@ async fn test_init_deposit_stake_authority() {
         ctx.last_blockhash,
     );
 
-    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let events = process_transaction_events(&mut ctx, tx).await;
 
     let (deposit_stake_authority_pubkey, _bump_seed) = derive_stake_pool_deposit_stake_authority(
         &stake_deposit_interceptor::ID,
         &stake_pool_accounts.stake_pool,
         &deposit_authority_base.pubkey(),
     );
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::InitStakePoolDepositStakeAuthority {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                stake_pool: stake_pool_accounts.stake_pool,
+                authority: authority.pubkey(),
+                fee_wallet: fee_wallet.pubkey(),
+                cool_down_seconds,
+                initial_fee_bps,
+                fee_curve: FeeCurveConfig::Linear,
+            }
+        ]
+    );
     let vault_ata = get_associated_token_address(
         &deposit_stake_authority_pubkey,
         &stake_pool_accounts.pool_mint,
@@ -92,6 +110,7 @@ async fn test_init_deposit_stake_authority() {
     let actual_initial_fee_bps: u32 = deposit_stake_authority.inital_fee_bps.into();
     assert_eq!(actual_cool_down_seconds, cool_down_seconds);
     assert_eq!(actual_initial_fee_bps, initial_fee_bps);
+    assert_eq!(deposit_stake_authority.fee_curve, FeeCurve::default());
     assert_eq!(
         deposit_stake_authority.base,
         deposit_authority_base.pubkey()
@@ -137,6 +156,7 @@ async fn setup_with_ix() -> (
             &fee_wallet.pubkey(),
             cool_down_seconds,
             initial_fee_bps,
+            FeeCurveConfig::Linear,
             &authority.pubkey(),
             &deposit_authority_base.pubkey(),
         );
@@ -382,6 +402,7 @@ async fn test_fail_initial_fee_bps_cannot_exceed_10000() {
             &fee_wallet.pubkey(),
             cool_down_seconds,
             initial_fee_bps,
+            FeeCurveConfig::Linear,
             &authority.pubkey(),
             &deposit_authority_base.pubkey(),
         );
@@ -400,3 +421,43 @@ async fn test_fail_initial_fee_bps_cannot_exceed_10000() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_fail_invalid_fee_curve() {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+
+    let deposit_authority_base = Keypair::new();
+    let fee_wallet = Keypair::new();
+    let authority = Keypair::new();
+    let cool_down_seconds = 100;
+    let initial_fee_bps = 20;
+    let ix =
+        stake_deposit_interceptor::instruction::create_init_deposit_stake_authority_instruction(
+            &stake_deposit_interceptor::id(),
+            &ctx.payer.pubkey(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.pool_mint,
+            &spl_stake_pool::id(),
+            &spl_token::id(),
+            &fee_wallet.pubkey(),
+            cool_down_seconds,
+            initial_fee_bps,
+            FeeCurveConfig::Step { steps: 0 },
+            &authority.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &deposit_authority_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeCurve as u32),
+    )
+    .await;
+}
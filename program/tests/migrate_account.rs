@@ -0,0 +1,230 @@
+mod helpers;
+
+use helpers::{
+    assert_transaction_err, create_stake_deposit_authority, get_account,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state,
+};
+use jito_bytemuck::AccountDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::AccountSharedData, instruction::InstructionError, pubkey::Pubkey, signature::Keypair,
+    signer::Signer, transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_migrate_account_instruction, create_set_pause_state_instruction,
+        derive_stake_pool_deposit_stake_authority,
+    },
+    state::StakePoolDepositStakeAuthority,
+};
+
+/// Size of a StakePoolDepositStakeAuthority account created before layout versions were recorded.
+const LEGACY_DEPOSIT_STAKE_AUTHORITY_LEN: usize = 501;
+
+async fn setup() -> (ProgramTestContext, StakePoolDepositStakeAuthority, Keypair) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let deposit_authority_base = Keypair::new();
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    (ctx, deposit_stake_authority, authority)
+}
+
+/// Overwrite the StakePoolDepositStakeAuthority with a legacy, shorter layout.
+async fn set_legacy_layout(ctx: &mut ProgramTestContext, deposit_stake_authority_pubkey: &Pubkey) {
+    let mut account = get_account(&mut ctx.banks_client, deposit_stake_authority_pubkey).await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    account.data.truncate(LEGACY_DEPOSIT_STAKE_AUTHORITY_LEN);
+    account.lamports = rent.minimum_balance(LEGACY_DEPOSIT_STAKE_AUTHORITY_LEN);
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+}
+
+fn set_pause_state_transaction(
+    ctx: &ProgramTestContext,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    authority: &Keypair,
+) -> Transaction {
+    let ix = create_set_pause_state_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &authority.pubkey(),
+        &deposit_stake_authority.base,
+        true,
+        false,
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, authority],
+        ctx.last_blockhash,
+    )
+}
+
+fn migrate_account_transaction(ctx: &ProgramTestContext, account: &Pubkey) -> Transaction {
+    let ix = create_migrate_account_instruction(
+        &stake_deposit_interceptor::id(),
+        account,
+        &ctx.payer.pubkey(),
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn test_success_migrate_legacy_deposit_stake_authority() {
+    let (mut ctx, deposit_stake_authority, authority) = setup().await;
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &deposit_stake_authority.base,
+    );
+    set_legacy_layout(&mut ctx, &deposit_stake_authority_pubkey).await;
+
+    // Instructions reject the legacy layout until it is migrated
+    let tx = set_pause_state_transaction(&ctx, &deposit_stake_authority, &authority);
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::AccountMigrationRequired as u32),
+    )
+    .await;
+
+    let tx = migrate_account_transaction(&ctx, &deposit_stake_authority_pubkey);
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::MigrateAccount {
+            account: deposit_stake_authority_pubkey,
+            previous_version: 0,
+            version: StakePoolDepositStakeAuthority::CURRENT_VERSION,
+        }]
+    );
+
+    let account = get_account(&mut ctx.banks_client, &deposit_stake_authority_pubkey).await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), StakePoolDepositStakeAuthority::LEN);
+    assert_eq!(
+        account.lamports,
+        rent.minimum_balance(StakePoolDepositStakeAuthority::LEN)
+    );
+    let migrated = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(
+        migrated.version,
+        StakePoolDepositStakeAuthority::CURRENT_VERSION
+    );
+    assert_eq!(migrated.authority, deposit_stake_authority.authority);
+    assert_eq!(migrated.stake_pool, deposit_stake_authority.stake_pool);
+
+    // The migrated account is usable again
+    let tx = set_pause_state_transaction(&ctx, &deposit_stake_authority, &authority);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fail_unsupported_account_version() {
+    let (mut ctx, deposit_stake_authority, authority) = setup().await;
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &deposit_stake_authority.base,
+    );
+    let mut account = get_account(&mut ctx.banks_client, &deposit_stake_authority_pubkey).await;
+    StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data)
+        .unwrap()
+        .version = StakePoolDepositStakeAuthority::CURRENT_VERSION + 1;
+    ctx.set_account(
+        &deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+
+    let tx = set_pause_state_transaction(&ctx, &deposit_stake_authority, &authority);
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::UnsupportedAccountVersion as u32),
+    )
+    .await;
+
+    // A newer layout cannot be migrated down
+    let tx = migrate_account_transaction(&ctx, &deposit_stake_authority_pubkey);
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::UnsupportedAccountVersion as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_account_longer_than_current_layout() {
+    let (mut ctx, deposit_stake_authority, authority) = setup().await;
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &deposit_stake_authority.base,
+    );
+    // Simulate fields appended by a later layout of the same version
+    let mut account = get_account(&mut ctx.banks_client, &deposit_stake_authority_pubkey).await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let len = StakePoolDepositStakeAuthority::LEN + 16;
+    account.data.resize(len, 1);
+    account.lamports = rent.minimum_balance(len);
+    ctx.set_account(
+        &deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+
+    let tx = set_pause_state_transaction(&ctx, &deposit_stake_authority, &authority);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // Migrating keeps the account's size
+    let tx = migrate_account_transaction(&ctx, &deposit_stake_authority_pubkey);
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::MigrateAccount {
+            account: deposit_stake_authority_pubkey,
+            previous_version: StakePoolDepositStakeAuthority::CURRENT_VERSION,
+            version: StakePoolDepositStakeAuthority::CURRENT_VERSION,
+        }]
+    );
+    let account = get_account(&mut ctx.banks_client, &deposit_stake_authority_pubkey).await;
+    assert_eq!(account.data.len(), len);
+    assert_eq!(
+        StakePoolDepositStakeAuthority::try_from_account_data(&account.data)
+            .unwrap()
+            .pause_flags,
+        StakePoolDepositStakeAuthority::DEPOSITS_PAUSED
+    );
+}
@@ -0,0 +1,449 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account, get_account,
+    get_account_data_deserialized, program_test_context_with_stake_pool_state, set_clock_time,
+    stake_pool_update_all, update_stake_deposit_authority, StakePoolAccounts,
+};
+use jito_bytemuck::AccountDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::AccountSharedData,
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_token_2022::state::Account;
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_claim_pool_tokens_partial_instruction,
+        create_recover_excess_vault_tokens_instruction, derive_stake_deposit_receipt,
+        derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+};
+
+/// Deposit stake, leaving the vault holding only what is owed to the DepositReceipt.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Pubkey,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Keypair,
+    Pubkey,
+    Pubkey,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+    )
+}
+
+async fn get_token_amount(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = get_account(&mut ctx.banks_client, token_account).await;
+    Account::unpack(&account.data.as_slice()).unwrap().amount
+}
+
+async fn recover_ix(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    authority: &Pubkey,
+) -> (Instruction, Pubkey) {
+    let destination_token_account =
+        create_token_account(ctx, authority, &stake_pool_accounts.pool_mint).await;
+    let ix = create_recover_excess_vault_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.pool_mint,
+        authority,
+        &deposit_stake_authority.base,
+        &destination_token_account,
+        &spl_token::id(),
+    );
+    (ix, destination_token_account)
+}
+
+#[tokio::test]
+async fn test_success_recover_excess_vault_tokens() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+    ) = setup().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.total_outstanding_lst),
+        lst_amount
+    );
+
+    // Claim half without fees, then send part of it straight back to the vault
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds) as i64 + 1,
+    )
+    .await;
+    let claim_amount = lst_amount / 2;
+    let excess_amount = claim_amount / 2;
+    let claim_ix = create_claim_pool_tokens_partial_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+        claim_amount,
+    );
+    let transfer_ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &depositor_pool_token_account,
+        &deposit_stake_authority.vault,
+        &depositor.pubkey(),
+        &[],
+        excess_amount,
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix, transfer_ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (ix, destination_token_account) = recover_ix(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // Only the excess is recovered, the rest is still owed to the DepositReceipt
+    let remaining_lst_amount = lst_amount - claim_amount;
+    assert_eq!(
+        get_token_amount(&mut ctx, &destination_token_account).await,
+        excess_amount
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &deposit_stake_authority.vault).await,
+        remaining_lst_amount
+    );
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.total_outstanding_lst),
+        remaining_lst_amount
+    );
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.outstanding_receipt_count),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_fail_no_excess_vault_tokens() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        _depositor,
+        _deposit_receipt_pda,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    let (ix, _destination_token_account) = recover_ix(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::NoExcessVaultTokens as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_outstanding_lst_not_tracked() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        _depositor,
+        _deposit_receipt_pda,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    // Mimic a StakePoolDepositStakeAuthority created before outstanding LST was tracked
+    let mut account = ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let legacy_deposit_stake_authority =
+        StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    legacy_deposit_stake_authority.outstanding_lst_tracked = 0;
+    legacy_deposit_stake_authority.total_outstanding_lst = 0.into();
+    ctx.set_account(
+        &deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+
+    let (ix, _destination_token_account) = recover_ix(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &authority.pubkey(),
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::OutstandingLstNotTracked as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_recover_invalid_authority() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        _authority,
+        _depositor,
+        _deposit_receipt_pda,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    let bad_authority = Keypair::new();
+    let (ix, _destination_token_account) = recover_ix(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &bad_authority.pubkey(),
+    )
+    .await;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
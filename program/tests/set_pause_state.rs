@@ -0,0 +1,412 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, set_clock_time, stake_pool_update_all,
+    update_stake_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_claim_pool_tokens_instruction, create_set_pause_state_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+};
+
+/// Set up a StakePoolDepositStakeAuthority and return the (unprocessed) deposit instructions
+/// of a depositor.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Pubkey,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Keypair,
+    Keypair,
+    Vec<Instruction>,
+    Pubkey,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_stake_instructions,
+        depositor_pool_token_account,
+    )
+}
+
+fn set_pause_state_tx(
+    ctx: &ProgramTestContext,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    authority: &Keypair,
+    deposits_paused: bool,
+    early_claims_paused: bool,
+) -> Transaction {
+    let ix = create_set_pause_state_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &authority.pubkey(),
+        &deposit_stake_authority.base,
+        deposits_paused,
+        early_claims_paused,
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, authority],
+        ctx.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn test_set_pause_state() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        _depositor,
+        _deposit_receipt_base,
+        _deposit_stake_instructions,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &authority, true, true);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert!(updated_deposit_stake_authority.deposits_paused());
+    assert!(updated_deposit_stake_authority.early_claims_paused());
+
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &authority, false, true);
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::SetPauseState {
+            deposit_stake_authority: deposit_stake_authority_pubkey,
+            deposits_paused: false,
+            early_claims_paused: true,
+        }]
+    );
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert!(!updated_deposit_stake_authority.deposits_paused());
+    assert!(updated_deposit_stake_authority.early_claims_paused());
+}
+
+#[tokio::test]
+async fn test_fail_deposit_paused() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_stake_instructions,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &authority, true, false);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::Paused as u32),
+    )
+    .await;
+
+    // Deposits go through again once unpaused
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &authority, false, false);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_early_claims_paused() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        authority,
+        depositor,
+        deposit_receipt_base,
+        deposit_stake_instructions,
+        depositor_pool_token_account,
+    ) = setup().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    // Pausing deposits does not affect claims
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &authority, true, true);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let claim_ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::Paused as u32),
+    )
+    .await;
+
+    // Claims after the cool down stay open, even when paused
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds) as i64 + 1,
+    )
+    .await;
+    let claim_ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let deposit_receipt_account = ctx
+        .banks_client
+        .get_account(deposit_receipt_pda)
+        .await
+        .unwrap();
+    assert!(deposit_receipt_account.is_none());
+}
+
+#[tokio::test]
+async fn test_fail_set_pause_state_invalid_authority() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        _deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        _authority,
+        _depositor,
+        _deposit_receipt_base,
+        _deposit_stake_instructions,
+        _depositor_pool_token_account,
+    ) = setup().await;
+
+    let bad_authority = Keypair::new();
+    let tx = set_pause_state_tx(&ctx, &deposit_stake_authority, &bad_authority, true, true);
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidAuthority as u32),
+    )
+    .await;
+}
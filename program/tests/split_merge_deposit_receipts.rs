@@ -0,0 +1,897 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account,
+    get_account_data_deserialized, program_test_context_with_stake_pool_state, set_clock_time,
+    stake_pool_update_all, update_stake_deposit_authority, StakePoolAccounts,
+    ValidatorStakeAccount,
+};
+use jito_bytemuck::AccountDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::AccountSharedData,
+    borsh1::try_from_slice_unchecked,
+    instruction::{AccountMeta, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_merge_deposit_receipts_instruction, create_split_deposit_receipt_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, FeeCurve, FeeCurveConfig, StakePoolDepositStakeAuthority},
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    spl_stake_pool::state::StakePool,
+    ValidatorStakeAccount,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Pubkey,
+    Keypair,
+    u64,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let total_staked_amount =
+        rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>()) + stake_amount;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let _depositor_lst_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    (
+        ctx,
+        stake_pool_accounts,
+        stake_pool,
+        validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        depositor_stake_account,
+        base,
+        total_staked_amount,
+    )
+}
+
+/// Overwrite the `deposit_time` of an existing DepositReceipt.
+async fn set_deposit_time(ctx: &mut ProgramTestContext, deposit_receipt: &Pubkey, time: u64) {
+    let mut account = ctx
+        .banks_client
+        .get_account(*deposit_receipt)
+        .await
+        .unwrap()
+        .unwrap();
+    let receipt = DepositReceipt::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    receipt.deposit_time = time.into();
+    ctx.set_account(deposit_receipt, &AccountSharedData::from(account));
+}
+
+/// Address of the StakePoolDepositStakeAuthority that created the DepositReceipt.
+async fn get_deposit_stake_authority_address(
+    ctx: &mut ProgramTestContext,
+    deposit_receipt: &Pubkey,
+) -> Pubkey {
+    get_account_data_deserialized::<DepositReceipt>(&mut ctx.banks_client, deposit_receipt)
+        .await
+        .stake_pool_deposit_stake_authority
+}
+
+/// Split `lst_amount` off of the DepositReceipt derived from `base` into a new
+/// DepositReceipt. Returns the new DepositReceipt's base.
+async fn split(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    depositor: &Keypair,
+    base: &Keypair,
+    lst_amount: u64,
+) -> Keypair {
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(ctx, &deposit_receipt_pda).await;
+    let new_base = Keypair::new();
+    let ix = create_split_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_stake_authority_pubkey,
+        &new_base.pubkey(),
+        lst_amount,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[depositor, &new_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    new_base
+}
+
+#[tokio::test]
+async fn test_success_split_deposit_receipt() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let split_amount = lst_amount / 4;
+
+    let new_base = split(
+        &mut ctx,
+        &stake_pool_accounts,
+        &depositor,
+        &base,
+        split_amount,
+    )
+    .await;
+
+    let (new_deposit_receipt_pda, new_bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &new_base.pubkey(),
+    );
+    let updated_deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let new_deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &new_deposit_receipt_pda,
+    )
+    .await;
+
+    assert_eq!(
+        u64::from(updated_deposit_receipt.lst_amount),
+        lst_amount - split_amount
+    );
+    assert_eq!(u64::from(new_deposit_receipt.lst_amount), split_amount);
+    assert_eq!(new_deposit_receipt.base, new_base.pubkey());
+    assert_eq!(new_deposit_receipt.bump_seed, new_bump_seed);
+    assert_eq!(new_deposit_receipt.owner, deposit_receipt.owner);
+    assert_eq!(new_deposit_receipt.stake_pool, deposit_receipt.stake_pool);
+    assert_eq!(
+        new_deposit_receipt.stake_pool_deposit_stake_authority,
+        deposit_receipt.stake_pool_deposit_stake_authority
+    );
+    assert_eq!(
+        new_deposit_receipt.deposit_time,
+        deposit_receipt.deposit_time
+    );
+    assert_eq!(
+        new_deposit_receipt.cool_down_seconds,
+        deposit_receipt.cool_down_seconds
+    );
+    assert_eq!(
+        new_deposit_receipt.initial_fee_bps,
+        deposit_receipt.initial_fee_bps
+    );
+
+    // Both DepositReceipts are outstanding
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_receipt.stake_pool_deposit_stake_authority,
+    )
+    .await;
+    assert_eq!(
+        u64::from(deposit_stake_authority.outstanding_receipt_count),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_fail_split_invalid_amount() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    // Splitting the full amount would leave an empty DepositReceipt
+    let new_base = Keypair::new();
+    let ix = create_split_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_stake_authority_pubkey,
+        &new_base.pubkey(),
+        u64::from(deposit_receipt.lst_amount),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor, &new_base],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidSplitAmount as u32),
+    )
+    .await;
+
+    // Zero amount
+    let ix = create_split_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &depositor.pubkey(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_stake_authority_pubkey,
+        &new_base.pubkey(),
+        0,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor, &new_base],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidSplitAmount as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_split_invalid_owner() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        _depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let bad_owner = Keypair::new();
+    let new_base = Keypair::new();
+    let ix = create_split_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        &deposit_receipt_pda,
+        &bad_owner.pubkey(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_stake_authority_pubkey,
+        &new_base.pubkey(),
+        1,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &bad_owner, &new_base],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidDepositReceiptOwner as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_merge_deposit_receipts() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let lst_amount = u64::from(deposit_receipt.lst_amount);
+    let split_amount = lst_amount / 4;
+    let new_base = split(
+        &mut ctx,
+        &stake_pool_accounts,
+        &depositor,
+        &base,
+        split_amount,
+    )
+    .await;
+    let (new_deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &new_base.pubkey(),
+    );
+
+    // Move the split DepositReceipt's deposit time forward to check weighting
+    let deposit_time = u64::from(deposit_receipt.deposit_time);
+    let later_deposit_time = deposit_time + 1_000;
+    set_deposit_time(&mut ctx, &new_deposit_receipt_pda, later_deposit_time).await;
+
+    let ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &new_deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let merged_deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(u64::from(merged_deposit_receipt.lst_amount), lst_amount);
+    let remaining_amount = (lst_amount - split_amount) as u128;
+    let expected_deposit_time = (deposit_time as u128 * remaining_amount
+        + later_deposit_time as u128 * split_amount as u128)
+        .div_ceil(lst_amount as u128) as u64;
+    assert_eq!(
+        u64::from(merged_deposit_receipt.deposit_time),
+        expected_deposit_time
+    );
+
+    // Source DepositReceipt should be closed
+    let source_account = ctx
+        .banks_client
+        .get_account(new_deposit_receipt_pda)
+        .await
+        .unwrap();
+    assert!(source_account.is_none());
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(
+        u64::from(deposit_stake_authority.outstanding_receipt_count),
+        1
+    );
+}
+
+/// Split two DepositReceipts of `destination_amount` and `source_amount` off of the
+/// DepositReceipt derived from `base`, with the given deposit times and fee curve.
+/// Returns the addresses of the destination and source DepositReceipts.
+#[allow(clippy::too_many_arguments)]
+async fn split_merge_pair(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    depositor: &Keypair,
+    base: &Keypair,
+    destination_amount: u64,
+    destination_deposit_time: u64,
+    source_amount: u64,
+    source_deposit_time: u64,
+    fee_curve: FeeCurve,
+) -> (Pubkey, Pubkey) {
+    let mut deposit_receipts = vec![];
+    for (amount, deposit_time) in [
+        (destination_amount, destination_deposit_time),
+        (source_amount, source_deposit_time),
+    ] {
+        let new_base = split(ctx, stake_pool_accounts, depositor, base, amount).await;
+        let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+            &stake_deposit_interceptor::id(),
+            &stake_pool_accounts.stake_pool,
+            &new_base.pubkey(),
+        );
+        let mut account = ctx
+            .banks_client
+            .get_account(deposit_receipt_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let receipt = DepositReceipt::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+        receipt.deposit_time = deposit_time.into();
+        receipt.fee_curve = fee_curve;
+        ctx.set_account(&deposit_receipt_pda, &AccountSharedData::from(account));
+        deposit_receipts.push(deposit_receipt_pda);
+    }
+    (deposit_receipts[0], deposit_receipts[1])
+}
+
+#[tokio::test]
+async fn test_success_merge_fresh_into_expired_deposit_receipt() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let now = 1_000_000;
+    let cool_down_seconds = u64::from(deposit_stake_authority.cool_down_seconds);
+    // The destination's cool down ended long ago while the source was just deposited
+    let (destination_pda, source_pda) = split_merge_pair(
+        &mut ctx,
+        &stake_pool_accounts,
+        &depositor,
+        &base,
+        1_000_000,
+        0,
+        3_000_000,
+        now,
+        FeeCurve::default(),
+    )
+    .await;
+    set_clock_time(&mut ctx, now as i64).await;
+
+    let destination_deposit_receipt =
+        get_account_data_deserialized::<DepositReceipt>(&mut ctx.banks_client, &destination_pda)
+            .await;
+    let source_deposit_receipt =
+        get_account_data_deserialized::<DepositReceipt>(&mut ctx.banks_client, &source_pda).await;
+    let fee_before = destination_deposit_receipt.calculate_fee_amount(now as i64)
+        + source_deposit_receipt.calculate_fee_amount(now as i64);
+    assert!(fee_before > 0);
+
+    let ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &destination_pda,
+        &source_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let merged_deposit_receipt =
+        get_account_data_deserialized::<DepositReceipt>(&mut ctx.banks_client, &destination_pda)
+            .await;
+    // The expired side is weighted as ending its cool down now
+    assert_eq!(
+        u64::from(merged_deposit_receipt.deposit_time),
+        now - cool_down_seconds / 4
+    );
+    assert_eq!(
+        merged_deposit_receipt.calculate_fee_amount(now as i64),
+        fee_before
+    );
+}
+
+#[tokio::test]
+async fn test_fail_merge_non_linear_different_fee_schedules() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let now = 1_000_000;
+    let fee_curve = FeeCurve::from_config(&FeeCurveConfig::Step { steps: 4 }).unwrap();
+    let (destination_pda, source_pda) = split_merge_pair(
+        &mut ctx,
+        &stake_pool_accounts,
+        &depositor,
+        &base,
+        1_000_000,
+        now - 10,
+        3_000_000,
+        now,
+        fee_curve,
+    )
+    .await;
+    set_clock_time(&mut ctx, now as i64).await;
+
+    let ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &destination_pda,
+        &source_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::IncompatibleDepositReceipts as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_merge_same_deposit_receipt() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidDepositReceipt as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_merge_incompatible_deposit_receipts() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let new_base = split(&mut ctx, &stake_pool_accounts, &depositor, &base, 1).await;
+    let (new_deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &new_base.pubkey(),
+    );
+
+    // Give the split DepositReceipt a different fee schedule
+    let mut account = ctx
+        .banks_client
+        .get_account(new_deposit_receipt_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let receipt = DepositReceipt::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    receipt.cool_down_seconds = (u64::from(receipt.cool_down_seconds) + 1).into();
+    ctx.set_account(&new_deposit_receipt_pda, &AccountSharedData::from(account));
+
+    let ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &new_deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::IncompatibleDepositReceipts as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_merge_owner_not_signer() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _stake_pool,
+        _validator_stake_accounts,
+        _deposit_stake_authority,
+        depositor,
+        _depositor_stake_account,
+        base,
+        _total_staked_amount,
+    ) = setup().await;
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &base.pubkey(),
+    );
+    let deposit_stake_authority_pubkey =
+        get_deposit_stake_authority_address(&mut ctx, &deposit_receipt_pda).await;
+    let new_base = split(&mut ctx, &stake_pool_accounts, &depositor, &base, 1).await;
+    let (new_deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &new_base.pubkey(),
+    );
+
+    let mut ix = create_merge_deposit_receipts_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &new_deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+        &depositor.pubkey(),
+    );
+    ix.accounts[2] = AccountMeta::new(depositor.pubkey(), false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::SignatureMissing as u32),
+    )
+    .await;
+}
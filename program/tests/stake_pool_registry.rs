@@ -0,0 +1,343 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_deposit_authority,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, update_stake_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::InstructionError, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signature::Keypair, signer::Signer, transaction::Transaction,
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_deregister_stake_pool_deposit_stake_authority_instruction,
+        create_refresh_stake_pool_registry_instruction,
+        create_register_stake_pool_deposit_stake_authority_instruction,
+        derive_stake_pool_deposit_stake_authority, derive_stake_pool_registry,
+    },
+    state::StakePoolRegistry,
+};
+
+/// Set up two StakePoolDepositStakeAuthority accounts for the same StakePool, the first of which
+/// is installed as the StakePool's `stake_deposit_authority`.
+async fn setup() -> (ProgramTestContext, StakePoolAccounts, Pubkey, Pubkey) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+
+    let mut deposit_stake_authorities = vec![];
+    for _ in 0..2 {
+        let deposit_authority_base = Keypair::new();
+        let authority = Keypair::new();
+        create_stake_deposit_authority(
+            &mut ctx,
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.pool_mint,
+            &authority,
+            &deposit_authority_base,
+            None,
+        )
+        .await;
+        let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+            &stake_deposit_interceptor::id(),
+            &stake_pool_accounts.stake_pool,
+            &deposit_authority_base.pubkey(),
+        );
+        deposit_stake_authorities.push(deposit_stake_authority_pubkey);
+    }
+
+    // Set the StakePool's stake_deposit_authority to the first interceptor PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authorities[0],
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authorities[0],
+        deposit_stake_authorities[1],
+    )
+}
+
+fn register_transaction(
+    ctx: &ProgramTestContext,
+    stake_pool: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+    manager: &Keypair,
+) -> Transaction {
+    let ix = create_register_stake_pool_deposit_stake_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        &ctx.payer.pubkey(),
+        stake_pool,
+        deposit_stake_authority,
+        &manager.pubkey(),
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, manager],
+        ctx.last_blockhash,
+    )
+}
+
+fn deregister_transaction(
+    ctx: &ProgramTestContext,
+    stake_pool: &Pubkey,
+    deposit_stake_authority: &Pubkey,
+) -> Transaction {
+    let ix = create_deregister_stake_pool_deposit_stake_authority_instruction(
+        &stake_deposit_interceptor::id(),
+        stake_pool,
+        deposit_stake_authority,
+        &ctx.payer.pubkey(),
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn test_success_register_and_deregister() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        active_deposit_stake_authority,
+        inactive_deposit_stake_authority,
+    ) = setup().await;
+    let manager = ctx.payer.insecure_clone();
+
+    for deposit_stake_authority in [
+        inactive_deposit_stake_authority,
+        active_deposit_stake_authority,
+    ] {
+        let tx = register_transaction(
+            &ctx,
+            &stake_pool_accounts.stake_pool,
+            &deposit_stake_authority,
+            &manager,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // The registry can be found from the StakePool alone
+    let (stake_pool_registry_pubkey, bump_seed) = derive_stake_pool_registry(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+    );
+    let stake_pool_registry = get_account_data_deserialized::<StakePoolRegistry>(
+        &mut ctx.banks_client,
+        &stake_pool_registry_pubkey,
+    )
+    .await;
+    assert_eq!(
+        stake_pool_registry.stake_pool,
+        stake_pool_accounts.stake_pool
+    );
+    assert_eq!(
+        stake_pool_registry.stake_pool_program_id,
+        spl_stake_pool::id()
+    );
+    assert_eq!(stake_pool_registry.bump_seed, bump_seed);
+    assert_eq!(
+        stake_pool_registry.registered(),
+        &[
+            inactive_deposit_stake_authority,
+            active_deposit_stake_authority
+        ]
+    );
+    assert_eq!(
+        stake_pool_registry.active_deposit_stake_authority,
+        active_deposit_stake_authority
+    );
+
+    let tx = deregister_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &inactive_deposit_stake_authority,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::DeregisterStakePoolDepositStakeAuthority {
+                stake_pool_registry: stake_pool_registry_pubkey,
+                stake_pool: stake_pool_accounts.stake_pool,
+                deposit_stake_authority: inactive_deposit_stake_authority,
+                active_deposit_stake_authority,
+            }
+        ]
+    );
+
+    let stake_pool_registry = get_account_data_deserialized::<StakePoolRegistry>(
+        &mut ctx.banks_client,
+        &stake_pool_registry_pubkey,
+    )
+    .await;
+    assert_eq!(
+        stake_pool_registry.registered(),
+        &[active_deposit_stake_authority]
+    );
+    assert_eq!(
+        stake_pool_registry.active_deposit_stake_authority,
+        active_deposit_stake_authority
+    );
+}
+
+#[tokio::test]
+async fn test_fail_register_invalid_manager() {
+    let (mut ctx, stake_pool_accounts, active_deposit_stake_authority, _) = setup().await;
+    let bad_manager = Keypair::new();
+
+    let tx = register_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &active_deposit_stake_authority,
+        &bad_manager,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidStakePoolManager as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_deregister_active_deposit_stake_authority() {
+    let (mut ctx, stake_pool_accounts, active_deposit_stake_authority, _) = setup().await;
+    let manager = ctx.payer.insecure_clone();
+
+    let tx = register_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &active_deposit_stake_authority,
+        &manager,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = deregister_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &active_deposit_stake_authority,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::DepositStakeAuthorityActive as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_deregister_not_registered() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        active_deposit_stake_authority,
+        inactive_deposit_stake_authority,
+    ) = setup().await;
+    let manager = ctx.payer.insecure_clone();
+
+    let tx = register_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &active_deposit_stake_authority,
+        &manager,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = deregister_transaction(
+        &ctx,
+        &stake_pool_accounts.stake_pool,
+        &inactive_deposit_stake_authority,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(
+            StakeDepositInterceptorError::DepositStakeAuthorityNotRegistered as u32,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_refresh_after_stake_deposit_authority_changed() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        active_deposit_stake_authority,
+        inactive_deposit_stake_authority,
+    ) = setup().await;
+    let manager = ctx.payer.insecure_clone();
+
+    for deposit_stake_authority in [
+        active_deposit_stake_authority,
+        inactive_deposit_stake_authority,
+    ] {
+        let tx = register_transaction(
+            &ctx,
+            &stake_pool_accounts.stake_pool,
+            &deposit_stake_authority,
+            &manager,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // The StakePool manager installs the other authority directly on the StakePool
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &inactive_deposit_stake_authority,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Anyone can refresh the registry
+    let keeper = Keypair::new();
+    airdrop_lamports(&mut ctx, &keeper.pubkey(), LAMPORTS_PER_SOL).await;
+    let ix = create_refresh_stake_pool_registry_instruction(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&keeper.pubkey()),
+        &[&keeper],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    let (stake_pool_registry_pubkey, _bump_seed) = derive_stake_pool_registry(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+    );
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::RefreshStakePoolRegistry {
+            stake_pool_registry: stake_pool_registry_pubkey,
+            stake_pool: stake_pool_accounts.stake_pool,
+            active_deposit_stake_authority: inactive_deposit_stake_authority,
+        }]
+    );
+
+    let stake_pool_registry = get_account_data_deserialized::<StakePoolRegistry>(
+        &mut ctx.banks_client,
+        &stake_pool_registry_pubkey,
+    )
+    .await;
+    assert_eq!(
+        stake_pool_registry.active_deposit_stake_authority,
+        inactive_deposit_stake_authority
+    );
+}
@@ -0,0 +1,387 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account, get_account,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, set_clock_time, stake_pool_update_all,
+    update_stake_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token_2022::state::Account;
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_claim_pool_tokens_instruction, create_sweep_fees_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority, MAX_FEE_RECIPIENTS},
+};
+
+/// Deposit and claim during the cool down so fees accrue in the vault. Returns the accrued fee
+/// amount and a SweepFees instruction paying out to the fee wallet's ATA.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    StakePoolDepositStakeAuthority,
+    Pubkey,
+    u64,
+    Instruction,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let claim_ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+    );
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(&mut ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[claim_ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let fee_amount = deposit_receipt.calculate_fee_amount(clock_time);
+
+    let fee_token_account =
+        get_associated_token_address(&fee_wallet.pubkey(), &stake_pool_accounts.pool_mint);
+    let create_fee_token_account_ix = create_associated_token_account(
+        &ctx.payer.pubkey(),
+        &fee_wallet.pubkey(),
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[create_fee_token_account_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_account,
+        &[],
+        &stake_pool.pool_mint,
+        &spl_token::id(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority,
+        fee_token_account,
+        fee_amount,
+        ix,
+    )
+}
+
+async fn get_token_amount(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = get_account(&mut ctx.banks_client, token_account).await;
+    Account::unpack(&account.data.as_slice()).unwrap().amount
+}
+
+#[tokio::test]
+async fn test_success_sweep_fees() {
+    let (mut ctx, _stake_pool_accounts, deposit_stake_authority, fee_token_account, fee_amount, ix) =
+        setup().await;
+    assert!(fee_amount > 0);
+    let deposit_stake_authority_pubkey = ix.accounts[0].pubkey;
+    let accrued_fees = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await
+    .accrued_fees;
+    assert_eq!(u64::from(accrued_fees), fee_amount);
+
+    // Anyone can sweep
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![StakeDepositInterceptorEvent::SweepFees {
+            deposit_stake_authority: deposit_stake_authority_pubkey,
+            fee_wallet_amount: fee_amount,
+            fee_recipient_amounts: vec![0; MAX_FEE_RECIPIENTS],
+        }]
+    );
+
+    assert_eq!(
+        get_token_amount(&mut ctx, &fee_token_account).await,
+        fee_amount
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &deposit_stake_authority.vault).await,
+        0
+    );
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(u64::from(updated_deposit_stake_authority.accrued_fees), 0);
+}
+
+#[tokio::test]
+async fn test_fail_sweep_fees_invalid_fee_token_account() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        _deposit_stake_authority,
+        _fee_token_account,
+        _fee_amount,
+        mut ix,
+    ) = setup().await;
+    let bad_owner = ctx.payer.pubkey();
+    let bad_fee_token_account =
+        create_token_account(&mut ctx, &bad_owner, &stake_pool_accounts.pool_mint).await;
+    ix.accounts[2] = AccountMeta::new(bad_fee_token_account, false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeTokenAccount as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_sweep_fees_invalid_vault() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        _deposit_stake_authority,
+        _fee_token_account,
+        _fee_amount,
+        mut ix,
+    ) = setup().await;
+    ix.accounts[1] = AccountMeta::new(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidVault as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_sweep_fees_invalid_pool_mint() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        _deposit_stake_authority,
+        _fee_token_account,
+        _fee_amount,
+        mut ix,
+    ) = setup().await;
+    ix.accounts[3] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidPoolMint as u32),
+    )
+    .await;
+}
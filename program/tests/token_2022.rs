@@ -0,0 +1,455 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_account, create_stake_deposit_authority,
+    create_token_account, create_validator_and_add_to_pool, delegate_stake_account, get_account,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_transfer_fee_stake_pool_state, set_clock_time, stake_pool_update_all,
+    update_stake_deposit_authority, StakePoolAccounts,
+};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
+    clock::Clock,
+    instruction::{AccountMeta, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    stake::{self},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account, Mint},
+};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
+    instruction::{
+        create_claim_pool_tokens_instruction, create_sweep_fees_instruction,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, StakePoolDepositStakeAuthority, MAX_FEE_RECIPIENTS},
+};
+
+const TRANSFER_FEE_BPS: u16 = 100;
+
+/// Deposit into a StakePool whose `pool_mint` is a Token-2022 mint with a transfer fee.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    StakePoolDepositStakeAuthority,
+    Pubkey,
+    Keypair,
+    Pubkey,
+    Pubkey,
+    Keypair,
+) {
+    let (mut ctx, stake_pool_accounts) =
+        program_test_context_with_transfer_fee_stake_pool_state(TRANSFER_FEE_BPS).await;
+    let stake_pool_account = ctx
+        .banks_client
+        .get_account(stake_pool_accounts.stake_pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let stake_pool =
+        try_from_slice_unchecked::<spl_stake_pool::state::StakePool>(&stake_pool_account.data)
+            .unwrap();
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's stake_deposit_authority to the interceptor program's PDA
+    update_stake_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+    // Add a validator to the stake_pool
+    let validator_stake_accounts =
+        create_validator_and_add_to_pool(&mut ctx, &stake_pool_accounts).await;
+
+    let fee_wallet = Keypair::new();
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        Some(&fee_wallet.pubkey()),
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    // Create "Depositor" owned stake account
+    let authorized = stake::state::Authorized {
+        staker: depositor.pubkey(),
+        withdrawer: depositor.pubkey(),
+    };
+    let lockup = stake::state::Lockup::default();
+    let stake_amount = 2 * LAMPORTS_PER_SOL;
+    let depositor_stake_account = create_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &authorized,
+        &lockup,
+        stake_amount,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    // Create a TokenAccount for the "Depositor" of the StakePool's `pool_mint`.
+    let depositor_pool_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    // Delegate the "Depositor" stake account to a validator from
+    // the relevant StakePool.
+    delegate_stake_account(
+        &mut ctx.banks_client,
+        &depositor,
+        &ctx.last_blockhash,
+        &depositor_stake_account,
+        &depositor,
+        &validator_stake_accounts.vote.pubkey(),
+    )
+    .await;
+
+    // Fast forward to next epoch so stake is active
+    let first_normal_slot = ctx.genesis_config().epoch_schedule.first_normal_slot;
+    ctx.warp_to_slot(first_normal_slot + 1).unwrap();
+
+    // Update relevant stake_pool state
+    stake_pool_update_all(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &stake_pool_accounts,
+        &ctx.last_blockhash,
+        false,
+    )
+    .await;
+
+    // Get latest `StakePoolDepositStakeAuthority``
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    // Generate a random Pubkey as seed for DepositReceipt PDA.
+    let deposit_receipt_base = Keypair::new();
+    let deposit_stake_instructions =
+        stake_deposit_interceptor::instruction::create_deposit_stake_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.validator_list,
+            &stake_pool_accounts.withdraw_authority,
+            &depositor_stake_account,
+            &depositor.pubkey(),
+            &validator_stake_accounts.stake_account,
+            &stake_pool_accounts.reserve_stake_account,
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &stake_pool_accounts.token_program_id,
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+        );
+
+    let tx = Transaction::new_signed_with_payer(
+        &deposit_stake_instructions,
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let (deposit_receipt_pda, _bump_seed) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority,
+        deposit_stake_authority_pubkey,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+        fee_wallet,
+    )
+}
+
+async fn get_token_amount(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = get_account(&mut ctx.banks_client, token_account).await;
+    StateWithExtensions::<Account>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+async fn calculate_transfer_fee(ctx: &mut ProgramTestContext, mint: &Pubkey, amount: u64) -> u64 {
+    let mint_account = get_account(&mut ctx.banks_client, mint).await;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_account.data).unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    mint.get_extension::<TransferFeeConfig>()
+        .unwrap()
+        .calculate_epoch_fee(clock.epoch, amount)
+        .unwrap()
+}
+
+/// Claim the DepositReceipt halfway through its cool down. Returns the fee amount and the logged
+/// events.
+async fn claim_pool_tokens_during_cool_down(
+    ctx: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    deposit_stake_authority: &StakePoolDepositStakeAuthority,
+    deposit_stake_authority_pubkey: &Pubkey,
+    depositor: &Keypair,
+    deposit_receipt_pda: &Pubkey,
+    depositor_pool_token_account: &Pubkey,
+) -> (u64, Vec<StakeDepositInterceptorEvent>) {
+    let deposit_receipt =
+        get_account_data_deserialized::<DepositReceipt>(&mut ctx.banks_client, deposit_receipt_pda)
+            .await;
+    let ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        depositor_pool_token_account,
+        deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &stake_pool_accounts.token_program_id,
+        &depositor.pubkey(),
+        false,
+    );
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let half_cool_down = u64::from(deposit_receipt.cool_down_seconds).saturating_div(2);
+    let clock_time = clock.unix_timestamp + half_cool_down as i64;
+    set_clock_time(ctx, clock_time).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[depositor],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(ctx, tx).await;
+    (deposit_receipt.calculate_fee_amount(clock_time), events)
+}
+
+#[tokio::test]
+async fn test_claim_pool_tokens_with_transfer_fee() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority,
+        deposit_stake_authority_pubkey,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+        _fee_wallet,
+    ) = setup().await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+
+    let (fee_amount, events) = claim_pool_tokens_during_cool_down(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &deposit_stake_authority_pubkey,
+        &depositor,
+        &deposit_receipt_pda,
+        &depositor_pool_token_account,
+    )
+    .await;
+
+    // The owner bears the Token-2022 transfer fee: the destination receives the claimed amount
+    // less the fee and the transfer fee, which is the `net_amount` logged
+    let amount = u64::from(deposit_receipt.lst_amount) - fee_amount;
+    let transfer_fee =
+        calculate_transfer_fee(&mut ctx, &stake_pool_accounts.pool_mint, amount).await;
+    assert!(transfer_fee > 0);
+    let destination_amount = get_token_amount(&mut ctx, &depositor_pool_token_account).await;
+    assert_eq!(destination_amount, amount - transfer_fee);
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::ClaimPoolTokens {
+                deposit_receipt: deposit_receipt_pda,
+                owner: depositor.pubkey(),
+                destination: depositor_pool_token_account,
+                fee_amount,
+                net_amount: destination_amount,
+                remaining_lst_amount: 0,
+            },
+            StakeDepositInterceptorEvent::ClaimPoolTokensTransferFee {
+                deposit_receipt: deposit_receipt_pda,
+                transfer_fee,
+            },
+        ]
+    );
+
+    // Fees accrue in the vault, unaffected by the transfer fee
+    assert_eq!(
+        get_token_amount(&mut ctx, &deposit_stake_authority.vault).await,
+        fee_amount
+    );
+    let updated_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+    assert_eq!(
+        u64::from(updated_deposit_stake_authority.accrued_fees),
+        fee_amount
+    );
+}
+
+#[tokio::test]
+async fn test_sweep_fees_with_transfer_fee() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority,
+        deposit_stake_authority_pubkey,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+        fee_wallet,
+    ) = setup().await;
+    let (fee_amount, _events) = claim_pool_tokens_during_cool_down(
+        &mut ctx,
+        &stake_pool_accounts,
+        &deposit_stake_authority,
+        &deposit_stake_authority_pubkey,
+        &depositor,
+        &deposit_receipt_pda,
+        &depositor_pool_token_account,
+    )
+    .await;
+
+    // The fee wallet's ATA lives under Token-2022
+    let fee_token_account = get_associated_token_address_with_program_id(
+        &fee_wallet.pubkey(),
+        &stake_pool_accounts.pool_mint,
+        &stake_pool_accounts.token_program_id,
+    );
+    let create_fee_token_account_ix = create_associated_token_account(
+        &ctx.payer.pubkey(),
+        &fee_wallet.pubkey(),
+        &stake_pool_accounts.pool_mint,
+        &stake_pool_accounts.token_program_id,
+    );
+    let ix = create_sweep_fees_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority_pubkey,
+        &deposit_stake_authority.vault,
+        &fee_token_account,
+        &[],
+        &stake_pool_accounts.pool_mint,
+        &stake_pool_accounts.token_program_id,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[create_fee_token_account_ix, ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+
+    // The fee wallet bears the Token-2022 transfer fee on its share, which is only charged once
+    // since the fees stayed in the vault when they were claimed
+    let transfer_fee =
+        calculate_transfer_fee(&mut ctx, &stake_pool_accounts.pool_mint, fee_amount).await;
+    assert!(transfer_fee > 0);
+    let fee_wallet_amount = get_token_amount(&mut ctx, &fee_token_account).await;
+    assert_eq!(fee_wallet_amount, fee_amount - transfer_fee);
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::SweepFees {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                fee_wallet_amount,
+                fee_recipient_amounts: vec![0; MAX_FEE_RECIPIENTS],
+            },
+            StakeDepositInterceptorEvent::SweepFeesTransferFee {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                transfer_fee,
+            },
+        ]
+    );
+    assert_eq!(
+        get_token_amount(&mut ctx, &deposit_stake_authority.vault).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_fail_claim_invalid_token_program() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority,
+        deposit_stake_authority_pubkey,
+        depositor,
+        deposit_receipt_pda,
+        depositor_pool_token_account,
+        _fee_wallet,
+    ) = setup().await;
+    let mut ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &depositor_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &stake_pool_accounts.token_program_id,
+        &depositor.pubkey(),
+        false,
+    );
+    // SPL Token does not own the Token-2022 pool mint
+    ix.accounts[6] = AccountMeta::new_readonly(spl_token::id(), false);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidTokenProgram as u32),
+    )
+    .await;
+}
@@ -0,0 +1,568 @@
+mod helpers;
+
+use helpers::{
+    airdrop_lamports, assert_transaction_err, create_stake_deposit_authority, create_token_account,
+    get_account, get_account_data_deserialized, program_test_context_with_stake_pool_state,
+    set_clock_time, update_sol_deposit_authority, StakePoolAccounts,
+};
+use jito_bytemuck::AccountDeserialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    account::AccountSharedData, clock::Clock, instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, signature::Keypair,
+    signer::Signer, transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{extension::StateWithExtensions, state::Mint};
+use stake_deposit_interceptor::{
+    error::StakeDepositInterceptorError,
+    instruction::{
+        create_claim_pool_tokens_instruction, create_claim_tokenized_deposit_receipt_instruction,
+        create_tokenize_deposit_receipt_instruction, derive_receipt_mint,
+        derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority,
+    },
+    state::{DepositReceipt, ReceiptTransferPolicy, StakePoolDepositStakeAuthority},
+};
+
+/// Set up a StakePool with the StakePoolDepositStakeAuthority as its sol deposit authority and
+/// a tokenized DepositReceipt for 1 SOL deposited into the reserve.
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    Pubkey,
+    StakePoolDepositStakeAuthority,
+    Keypair,
+    Pubkey,
+) {
+    let (mut ctx, stake_pool_accounts) = program_test_context_with_stake_pool_state().await;
+    let deposit_authority_base = Keypair::new();
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_authority_base.pubkey(),
+    );
+    // Set the StakePool's sol_deposit_authority to the interceptor program's PDA
+    update_sol_deposit_authority(
+        &mut ctx.banks_client,
+        &stake_pool_accounts,
+        &deposit_stake_authority_pubkey,
+        &ctx.payer,
+        ctx.last_blockhash,
+    )
+    .await;
+
+    let authority = Keypair::new();
+    create_stake_deposit_authority(
+        &mut ctx,
+        &stake_pool_accounts.stake_pool,
+        &stake_pool_accounts.pool_mint,
+        &authority,
+        &deposit_authority_base,
+        None,
+    )
+    .await;
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+
+    let depositor = Keypair::new();
+    airdrop_lamports(&mut ctx, &depositor.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+
+    let deposit_receipt_base = Keypair::new();
+    let (deposit_receipt_pda, _bump) = derive_stake_deposit_receipt(
+        &stake_deposit_interceptor::id(),
+        &stake_pool_accounts.stake_pool,
+        &deposit_receipt_base.pubkey(),
+    );
+    let deposit_sol_instruction =
+        stake_deposit_interceptor::instruction::create_deposit_sol_instruction(
+            &stake_deposit_interceptor::id(),
+            &depositor.pubkey(),
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool,
+            &stake_pool_accounts.withdraw_authority,
+            &stake_pool_accounts.reserve_stake_account,
+            &depositor.pubkey(),
+            &deposit_stake_authority.vault,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_fee_account,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &deposit_receipt_base.pubkey(),
+            &deposit_authority_base.pubkey(),
+            LAMPORTS_PER_SOL,
+        );
+    // Tokenize the DepositReceipt in the same transaction as the deposit
+    let tokenize_instruction = create_tokenize_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_sol_instruction, tokenize_instruction],
+        Some(&depositor.pubkey()),
+        &[&depositor, &deposit_receipt_base],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    (
+        ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    )
+}
+
+/// Transfer the depositor's receipt token to a new buyer.
+async fn sell_receipt_token(
+    ctx: &mut ProgramTestContext,
+    depositor: &Keypair,
+    receipt_mint: &Pubkey,
+) -> Keypair {
+    let buyer = Keypair::new();
+    airdrop_lamports(ctx, &buyer.pubkey(), LAMPORTS_PER_SOL).await;
+    let depositor_receipt_token_account = get_associated_token_address_with_program_id(
+        &depositor.pubkey(),
+        receipt_mint,
+        &spl_token_2022::id(),
+    );
+    let buyer_receipt_token_account = get_associated_token_address_with_program_id(
+        &buyer.pubkey(),
+        receipt_mint,
+        &spl_token_2022::id(),
+    );
+    let create_ata_ix =
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &buyer.pubkey(),
+            &buyer.pubkey(),
+            receipt_mint,
+            &spl_token_2022::id(),
+        );
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::id(),
+        &depositor_receipt_token_account,
+        receipt_mint,
+        &buyer_receipt_token_account,
+        &depositor.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[create_ata_ix, transfer_ix],
+        Some(&buyer.pubkey()),
+        &[&buyer, depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    buyer
+}
+
+#[tokio::test]
+async fn test_success_tokenize_deposit_receipt() {
+    let (mut ctx, _stake_pool_accounts, _, _, depositor, deposit_receipt_pda) = setup().await;
+    let (receipt_mint, _bump) =
+        derive_receipt_mint(&stake_deposit_interceptor::id(), &deposit_receipt_pda);
+
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert!(deposit_receipt.is_tokenized());
+    assert_eq!(deposit_receipt.receipt_mint, receipt_mint);
+
+    // A single receipt token exists and no more can be minted
+    let mint_account = get_account(&mut ctx.banks_client, &receipt_mint).await;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_account.data).unwrap();
+    assert_eq!(mint.base.supply, 1);
+    assert_eq!(mint.base.decimals, 0);
+    assert!(mint.base.mint_authority.is_none());
+
+    let receipt_token_account = get_associated_token_address_with_program_id(
+        &depositor.pubkey(),
+        &receipt_mint,
+        &spl_token_2022::id(),
+    );
+    let receipt_token_account = get_account(&mut ctx.banks_client, &receipt_token_account).await;
+    let receipt_token_account =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&receipt_token_account.data)
+            .unwrap();
+    assert_eq!(receipt_token_account.base.amount, 1);
+}
+
+#[tokio::test]
+async fn test_success_claim_tokenized_deposit_receipt_by_token_holder() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let (receipt_mint, _bump) =
+        derive_receipt_mint(&stake_deposit_interceptor::id(), &deposit_receipt_pda);
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let deposit_receipt_lamports = get_account(&mut ctx.banks_client, &deposit_receipt_pda)
+        .await
+        .lamports;
+
+    // Sell the receipt token to a buyer
+    let buyer = sell_receipt_token(&mut ctx, &depositor, &receipt_mint).await;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    set_clock_time(
+        &mut ctx,
+        clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds) as i64 + 10,
+    )
+    .await;
+
+    let buyer_pool_token_account =
+        create_token_account(&mut ctx, &buyer.pubkey(), &stake_pool_accounts.pool_mint).await;
+    let depositor_lamports_before = get_account(&mut ctx.banks_client, &depositor.pubkey())
+        .await
+        .lamports;
+    let receipt_mint_lamports = get_account(&mut ctx.banks_client, &receipt_mint)
+        .await
+        .lamports;
+    let buyer_lamports_before = get_account(&mut ctx.banks_client, &buyer.pubkey())
+        .await
+        .lamports;
+
+    let ix = create_claim_tokenized_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &buyer.pubkey(),
+        &deposit_stake_authority.vault,
+        &buyer_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &buyer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // The buyer received all of the pool tokens, as the cool down has ended
+    let buyer_pool_token_account =
+        get_account(&mut ctx.banks_client, &buyer_pool_token_account).await;
+    let buyer_pool_token_account =
+        spl_token::state::Account::unpack(&buyer_pool_token_account.data).unwrap();
+    assert_eq!(
+        buyer_pool_token_account.amount,
+        u64::from(deposit_receipt.lst_amount)
+    );
+
+    // The receipt mint's rent went to the buyer and the DepositReceipt's to the depositor
+    assert!(ctx
+        .banks_client
+        .get_account(deposit_receipt_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(ctx
+        .banks_client
+        .get_account(receipt_mint)
+        .await
+        .unwrap()
+        .is_none());
+    let buyer_lamports = get_account(&mut ctx.banks_client, &buyer.pubkey())
+        .await
+        .lamports;
+    assert_eq!(
+        buyer_lamports - buyer_lamports_before,
+        receipt_mint_lamports
+    );
+    let depositor_lamports = get_account(&mut ctx.banks_client, &depositor.pubkey())
+        .await
+        .lamports;
+    assert_eq!(
+        depositor_lamports - depositor_lamports_before,
+        deposit_receipt_lamports
+    );
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(u64::from(deposit_stake_authority.total_outstanding_lst), 0);
+    assert_eq!(
+        u64::from(deposit_stake_authority.outstanding_receipt_count),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_fail_claim_pool_tokens_tokenized_deposit_receipt() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let destination_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+
+    let ix = create_claim_pool_tokens_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &destination_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::DepositReceiptTokenized as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_tokenize_deposit_receipt_twice() {
+    let (
+        mut ctx,
+        _stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        _,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+
+    let ix = create_tokenize_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority_pubkey,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::DepositReceiptTokenized as u32),
+    )
+    .await;
+}
+
+/// Change the StakePoolDepositStakeAuthority's ReceiptTransferPolicy after tokenization.
+async fn set_receipt_transfer_policy(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    receipt_transfer_policy: ReceiptTransferPolicy,
+) {
+    let mut account = get_account(&mut ctx.banks_client, deposit_stake_authority_pubkey).await;
+    StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data)
+        .unwrap()
+        .receipt_transfer_policy = receipt_transfer_policy as u8;
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+}
+
+#[tokio::test]
+async fn test_fail_claim_tokenized_deposit_receipt_transfer_forbidden_during_cool_down() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    let (receipt_mint, _bump) =
+        derive_receipt_mint(&stake_deposit_interceptor::id(), &deposit_receipt_pda);
+    let buyer = sell_receipt_token(&mut ctx, &depositor, &receipt_mint).await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        ReceiptTransferPolicy::ForbidDuringCoolDown,
+    )
+    .await;
+
+    // The policy in force at the claim applies to the receipt token changing hands
+    let buyer_pool_token_account =
+        create_token_account(&mut ctx, &buyer.pubkey(), &stake_pool_accounts.pool_mint).await;
+    let ix = create_claim_tokenized_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &buyer.pubkey(),
+        &deposit_stake_authority.vault,
+        &buyer_pool_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &buyer],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::ReceiptTransferForbidden as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_claim_tokenized_deposit_receipt_returned_to_owner_during_cool_down() {
+    let (
+        mut ctx,
+        stake_pool_accounts,
+        deposit_stake_authority_pubkey,
+        deposit_stake_authority,
+        depositor,
+        deposit_receipt_pda,
+    ) = setup().await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &deposit_stake_authority_pubkey,
+        ReceiptTransferPolicy::ForbidDuringCoolDown,
+    )
+    .await;
+
+    // The owner holding the token cannot claim either, as it may have changed hands meanwhile
+    let destination_token_account = create_token_account(
+        &mut ctx,
+        &depositor.pubkey(),
+        &stake_pool_accounts.pool_mint,
+    )
+    .await;
+    let ix = create_claim_tokenized_deposit_receipt_instruction(
+        &stake_deposit_interceptor::id(),
+        &deposit_receipt_pda,
+        &depositor.pubkey(),
+        &deposit_stake_authority.vault,
+        &destination_token_account,
+        &deposit_stake_authority_pubkey,
+        &stake_pool_accounts.pool_mint,
+        &spl_token::id(),
+        &depositor.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::ReceiptTransferForbidden as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_claim_tokenized_deposit_receipt_after_cool_down_under_each_policy() {
+    for receipt_transfer_policy in [
+        ReceiptTransferPolicy::Allow,
+        ReceiptTransferPolicy::ForbidDuringCoolDown,
+        ReceiptTransferPolicy::ResetDepositTime,
+    ] {
+        let (
+            mut ctx,
+            stake_pool_accounts,
+            deposit_stake_authority_pubkey,
+            deposit_stake_authority,
+            depositor,
+            deposit_receipt_pda,
+        ) = setup().await;
+        let (receipt_mint, _bump) =
+            derive_receipt_mint(&stake_deposit_interceptor::id(), &deposit_receipt_pda);
+        let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+            &mut ctx.banks_client,
+            &deposit_receipt_pda,
+        )
+        .await;
+        let buyer = sell_receipt_token(&mut ctx, &depositor, &receipt_mint).await;
+        set_receipt_transfer_policy(
+            &mut ctx,
+            &deposit_stake_authority_pubkey,
+            receipt_transfer_policy,
+        )
+        .await;
+
+        let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+        set_clock_time(
+            &mut ctx,
+            clock.unix_timestamp + u64::from(deposit_receipt.cool_down_seconds) as i64 + 10,
+        )
+        .await;
+
+        let buyer_pool_token_account =
+            create_token_account(&mut ctx, &buyer.pubkey(), &stake_pool_accounts.pool_mint).await;
+        let ix = create_claim_tokenized_deposit_receipt_instruction(
+            &stake_deposit_interceptor::id(),
+            &deposit_receipt_pda,
+            &buyer.pubkey(),
+            &deposit_stake_authority.vault,
+            &buyer_pool_token_account,
+            &deposit_stake_authority_pubkey,
+            &stake_pool_accounts.pool_mint,
+            &spl_token::id(),
+            &depositor.pubkey(),
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &buyer],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+
+        // No fee is owed once the cool down has ended, whatever the policy
+        let buyer_pool_token_account =
+            get_account(&mut ctx.banks_client, &buyer_pool_token_account).await;
+        let buyer_pool_token_account =
+            spl_token::state::Account::unpack(&buyer_pool_token_account.data).unwrap();
+        assert_eq!(
+            buyer_pool_token_account.amount,
+            u64::from(deposit_receipt.lst_amount)
+        );
+    }
+}
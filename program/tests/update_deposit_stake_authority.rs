@@ -2,12 +2,14 @@ mod helpers;
 
 use helpers::{
     assert_transaction_err, clone_account_to_new_address, create_stake_deposit_authority,
-    program_test_context_with_stake_pool_state, StakePoolAccounts,
+    get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, set_clock_time, StakePoolAccounts,
 };
 use jito_bytemuck::AccountDeserialize;
 use solana_program_test::ProgramTestContext;
 use solana_sdk::{
     borsh1::try_from_slice_unchecked,
+    clock::Clock,
     instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::Keypair,
@@ -16,11 +18,14 @@ use solana_sdk::{
 };
 use stake_deposit_interceptor::{
     error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
     instruction::{
         derive_stake_pool_deposit_stake_authority, StakeDepositInterceptorInstruction,
-        UpdateStakePoolDepositStakeAuthorityArgs,
+        UpdateStakePoolDepositStakeAuthorityArgs, UpdateStakePoolDepositStakeAuthorityV2Args,
+    },
+    state::{
+        DepositReceipt, FeeCurveConfig, ReceiptTransferPolicy, StakePoolDepositStakeAuthority,
     },
-    state::StakePoolDepositStakeAuthority,
 };
 
 #[tokio::test]
@@ -49,9 +54,11 @@ async fn test_update_deposit_stake_authority() {
     .await;
 
     let fee_wallet = Keypair::new();
-    let new_authority = Keypair::new();
     let cool_down_seconds = 78;
     let initial_fee_bps = 20;
+    let fee_curve = FeeCurveConfig::Exponential {
+        half_life_seconds: 10,
+    };
 
     let update_ix =
         stake_deposit_interceptor::instruction::create_update_deposit_stake_authority_instruction(
@@ -59,10 +66,17 @@ async fn test_update_deposit_stake_authority() {
             &stake_pool_accounts.stake_pool,
             &authority.pubkey(),
             &deposit_authority_base.pubkey(),
-            Some(new_authority.pubkey()),
             Some(fee_wallet.pubkey()),
             Some(cool_down_seconds),
             Some(initial_fee_bps),
+            Some(fee_curve.clone()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
         );
 
     let tx = Transaction::new_signed_with_payer(
@@ -90,19 +104,73 @@ async fn test_update_deposit_stake_authority() {
     let deposit_stake_authority =
         StakePoolDepositStakeAuthority::try_from_slice_unchecked(&account.data.as_slice()).unwrap();
 
+    // Changes are queued, not applied
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let effective_at: u64 = deposit_stake_authority
+        .pending_parameters_effective_at
+        .into();
+    assert_eq!(
+        effective_at,
+        (clock.unix_timestamp + StakePoolDepositStakeAuthority::MIN_PARAMETER_UPDATE_DELAY_SECONDS)
+            as u64
+    );
+    let pending_cool_down_seconds: u64 = deposit_stake_authority.pending_cool_down_seconds.into();
+    let pending_initial_fee_bps: u32 = deposit_stake_authority.pending_initial_fee_bps.into();
+    assert_eq!(pending_cool_down_seconds, cool_down_seconds);
+    assert_eq!(pending_initial_fee_bps, initial_fee_bps);
+    assert_eq!(
+        deposit_stake_authority.pending_fee_wallet,
+        fee_wallet.pubkey()
+    );
+    assert_eq!(
+        deposit_stake_authority.pending_fee_curve.config(),
+        fee_curve
+    );
+    assert_ne!(deposit_stake_authority.fee_wallet, fee_wallet.pubkey());
+    assert_eq!(
+        deposit_stake_authority.fee_curve.config(),
+        FeeCurveConfig::Linear
+    );
+
+    // Anyone can apply the changes once the delay has passed
+    set_clock_time(&mut ctx, effective_at as i64).await;
+    let apply_ix =
+        stake_deposit_interceptor::instruction::create_apply_pending_parameters_instruction(
+            &stake_deposit_interceptor::id(),
+            &stake_pool_accounts.stake_pool,
+            &deposit_authority_base.pubkey(),
+        );
+    let tx = Transaction::new_signed_with_payer(
+        &[apply_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let account = ctx
+        .banks_client
+        .get_account(deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let deposit_stake_authority =
+        StakePoolDepositStakeAuthority::try_from_slice_unchecked(&account.data.as_slice()).unwrap();
+
     let actual_cool_down_seconds: u64 = deposit_stake_authority.cool_down_seconds.into();
     let actual_initial_fee_bps: u32 = deposit_stake_authority.inital_fee_bps.into();
     assert_eq!(actual_cool_down_seconds, cool_down_seconds);
     assert_eq!(actual_initial_fee_bps, initial_fee_bps);
     assert_eq!(deposit_stake_authority.fee_wallet, fee_wallet.pubkey());
-    assert_eq!(deposit_stake_authority.authority, new_authority.pubkey());
+    assert_eq!(deposit_stake_authority.authority, authority.pubkey());
+    assert_eq!(deposit_stake_authority.fee_curve.config(), fee_curve);
+    assert!(!deposit_stake_authority.has_pending_parameters());
 }
 
 async fn setup_with_ix() -> (
     ProgramTestContext,
     StakePoolAccounts,
     Keypair,
-    Keypair,
     Pubkey,
     Instruction,
 ) {
@@ -130,7 +198,6 @@ async fn setup_with_ix() -> (
     .await;
 
     let fee_wallet = Keypair::new();
-    let new_authority = Keypair::new();
     let cool_down_seconds = 78;
     let initial_fee_bps = 20;
 
@@ -140,10 +207,17 @@ async fn setup_with_ix() -> (
             &stake_pool_accounts.stake_pool,
             &authority.pubkey(),
             &deposit_authority_base.pubkey(),
-            Some(new_authority.pubkey()),
             Some(fee_wallet.pubkey()),
             Some(cool_down_seconds),
             Some(initial_fee_bps),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
         );
 
     let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
@@ -155,7 +229,6 @@ async fn setup_with_ix() -> (
         ctx,
         stake_pool_accounts,
         authority,
-        new_authority,
         deposit_stake_authority_pubkey,
         update_ix,
     )
@@ -163,14 +236,8 @@ async fn setup_with_ix() -> (
 
 #[tokio::test]
 async fn test_fail_program_does_not_own_pda_account() {
-    let (
-        mut ctx,
-        _stake_pool_accounts,
-        authority,
-        _new_authority,
-        _deposit_stake_authority_pubkey,
-        mut ix,
-    ) = setup_with_ix().await;
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
     ix.accounts[0] = AccountMeta::new(Pubkey::new_unique(), false);
 
     let tx = Transaction::new_signed_with_payer(
@@ -185,14 +252,8 @@ async fn test_fail_program_does_not_own_pda_account() {
 
 #[tokio::test]
 async fn test_fail_authority_not_signer() {
-    let (
-        mut ctx,
-        _stake_pool_accounts,
-        authority,
-        _new_authority,
-        _deposit_stake_authority_pubkey,
-        mut ix,
-    ) = setup_with_ix().await;
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
     ix.accounts[1] = AccountMeta::new_readonly(authority.pubkey(), false);
 
     let tx = Transaction::new_signed_with_payer(
@@ -212,14 +273,8 @@ async fn test_fail_authority_not_signer() {
 
 #[tokio::test]
 async fn test_fail_authority_incorrect() {
-    let (
-        mut ctx,
-        _stake_pool_accounts,
-        _authority,
-        _new_authority,
-        _deposit_stake_authority_pubkey,
-        mut ix,
-    ) = setup_with_ix().await;
+    let (mut ctx, _stake_pool_accounts, _authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
     let bad_authority = Keypair::new();
     ix.accounts[1] = AccountMeta::new_readonly(bad_authority.pubkey(), true);
 
@@ -240,14 +295,8 @@ async fn test_fail_authority_incorrect() {
 
 #[tokio::test]
 async fn test_fail_invalid_stake_deposit_authority_address() {
-    let (
-        mut ctx,
-        _stake_pool_accounts,
-        authority,
-        _new_authority,
-        deposit_stake_authority_pubkey,
-        mut ix,
-    ) = setup_with_ix().await;
+    let (mut ctx, _stake_pool_accounts, authority, deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
     let bad_account = clone_account_to_new_address(&mut ctx, &deposit_stake_authority_pubkey).await;
     ix.accounts[0] = AccountMeta::new(bad_account, false);
 
@@ -270,15 +319,10 @@ async fn test_fail_invalid_stake_deposit_authority_address() {
 
 #[tokio::test]
 async fn test_fail_initial_fee_bps_cannot_exceed_10000() {
-    let (
-        mut ctx,
-        _stake_pool_accounts,
-        authority,
-        _new_authority,
-        _deposit_stake_authority_pubkey,
-        mut ix,
-    ) = setup_with_ix().await;
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
 
+    // The released args are still accepted
     let args = UpdateStakePoolDepositStakeAuthorityArgs {
         fee_wallet: None,
         initial_fee_bps: Some(10_001),
@@ -303,3 +347,365 @@ async fn test_fail_initial_fee_bps_cannot_exceed_10000() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_fail_invalid_fee_curve() {
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
+
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
+        fee_wallet: None,
+        initial_fee_bps: None,
+        cool_down_seconds: None,
+        fee_curve: Some(FeeCurveConfig::Exponential {
+            half_life_seconds: 0,
+        }),
+        effective_at: None,
+        max_deposit_lst_per_receipt: None,
+        max_total_outstanding_lst: None,
+        min_deposit_lst: None,
+        keeper_tip_lamports: None,
+        require_owner_signature: None,
+        receipt_transfer_policy: None,
+    };
+    ix.data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args),
+    )
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidFeeCurve as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_effective_at_before_min_delay() {
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, mut ix) =
+        setup_with_ix().await;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
+        fee_wallet: None,
+        initial_fee_bps: Some(10_000),
+        cool_down_seconds: None,
+        fee_curve: None,
+        effective_at: Some(
+            clock.unix_timestamp
+                + StakePoolDepositStakeAuthority::MIN_PARAMETER_UPDATE_DELAY_SECONDS
+                - 1,
+        ),
+        max_deposit_lst_per_receipt: None,
+        max_total_outstanding_lst: None,
+        min_deposit_lst: None,
+        keeper_tip_lamports: None,
+        require_owner_signature: None,
+        receipt_transfer_policy: None,
+    };
+    ix.data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args),
+    )
+    .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::ParameterUpdateDelayTooShort as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_apply_pending_parameters_before_effective_at() {
+    let (mut ctx, stake_pool_accounts, authority, deposit_stake_authority_pubkey, ix) =
+        setup_with_ix().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    let effective_at: u64 = deposit_stake_authority
+        .pending_parameters_effective_at
+        .into();
+    set_clock_time(&mut ctx, effective_at as i64 - 1).await;
+
+    let apply_ix =
+        stake_deposit_interceptor::instruction::create_apply_pending_parameters_instruction(
+            &stake_deposit_interceptor::id(),
+            &stake_pool_accounts.stake_pool,
+            &deposit_stake_authority.base,
+        );
+    let tx = Transaction::new_signed_with_payer(
+        &[apply_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(
+            StakeDepositInterceptorError::PendingParametersNotEffective as u32,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fail_apply_pending_parameters_none_pending() {
+    let (mut ctx, stake_pool_accounts, _authority, deposit_stake_authority_pubkey, _ix) =
+        setup_with_ix().await;
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    let apply_ix =
+        stake_deposit_interceptor::instruction::create_apply_pending_parameters_instruction(
+            &stake_deposit_interceptor::id(),
+            &stake_pool_accounts.stake_pool,
+            &deposit_stake_authority.base,
+        );
+    let tx = Transaction::new_signed_with_payer(
+        &[apply_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::NoPendingParameters as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_update_deposit_limits() {
+    let (mut ctx, _stake_pool_accounts, authority, deposit_stake_authority_pubkey, ix) =
+        setup_with_ix().await;
+
+    // Queue fee parameter changes
+    let tx = Transaction::new_signed_with_payer(
+        &[ix.clone()],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let queued_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+
+    // Only update the immediate settings
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
+        fee_wallet: None,
+        initial_fee_bps: None,
+        cool_down_seconds: None,
+        fee_curve: None,
+        effective_at: None,
+        max_deposit_lst_per_receipt: Some(1_000),
+        max_total_outstanding_lst: Some(10_000),
+        min_deposit_lst: Some(100),
+        keeper_tip_lamports: Some(5_000),
+        require_owner_signature: Some(true),
+        receipt_transfer_policy: Some(ReceiptTransferPolicy::ResetDepositTime),
+    };
+    let mut caps_ix = ix;
+    caps_ix.data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args),
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[caps_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::SetDepositCaps {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                max_deposit_lst_per_receipt: 1_000,
+                max_total_outstanding_lst: 10_000,
+            },
+            StakeDepositInterceptorEvent::SetMinDepositLst {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                min_deposit_lst: 100,
+            },
+            StakeDepositInterceptorEvent::SetKeeperTip {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                keeper_tip_lamports: 5_000,
+            },
+            StakeDepositInterceptorEvent::SetRequireOwnerSignature {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                require_owner_signature: true,
+            },
+            StakeDepositInterceptorEvent::SetReceiptTransferPolicy {
+                deposit_stake_authority: deposit_stake_authority_pubkey,
+                receipt_transfer_policy: ReceiptTransferPolicy::ResetDepositTime,
+            },
+        ]
+    );
+
+    // Limits take effect immediately and the queued fee parameters are untouched
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(
+        u64::from(deposit_stake_authority.max_deposit_lst_per_receipt),
+        1_000
+    );
+    assert_eq!(
+        u64::from(deposit_stake_authority.max_total_outstanding_lst),
+        10_000
+    );
+    assert_eq!(u64::from(deposit_stake_authority.min_deposit_lst), 100);
+    assert_eq!(
+        u64::from(deposit_stake_authority.keeper_tip_lamports),
+        5_000
+    );
+    assert!(deposit_stake_authority.owner_signature_required());
+    assert_eq!(
+        deposit_stake_authority.receipt_transfer_policy(),
+        Ok(ReceiptTransferPolicy::ResetDepositTime)
+    );
+    assert_eq!(
+        deposit_stake_authority.pending_parameters_effective_at,
+        queued_deposit_stake_authority.pending_parameters_effective_at
+    );
+    assert_eq!(
+        deposit_stake_authority.pending_fee_wallet,
+        queued_deposit_stake_authority.pending_fee_wallet
+    );
+}
+
+#[tokio::test]
+async fn test_update_without_fee_changes_keeps_pending_parameters() {
+    let (mut ctx, _stake_pool_accounts, authority, deposit_stake_authority_pubkey, ix) =
+        setup_with_ix().await;
+
+    // Queue fee parameter changes
+    let tx = Transaction::new_signed_with_payer(
+        &[ix.clone()],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let queued_deposit_stake_authority = get_account_data_deserialized::<
+        StakePoolDepositStakeAuthority,
+    >(
+        &mut ctx.banks_client, &deposit_stake_authority_pubkey
+    )
+    .await;
+
+    // An `effective_at` without any fee parameter is neither validated nor queued
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
+        fee_wallet: None,
+        initial_fee_bps: None,
+        cool_down_seconds: None,
+        fee_curve: None,
+        effective_at: Some(0),
+        max_deposit_lst_per_receipt: None,
+        max_total_outstanding_lst: None,
+        min_deposit_lst: None,
+        keeper_tip_lamports: None,
+        require_owner_signature: None,
+        receipt_transfer_policy: None,
+    };
+    let mut noop_ix = ix;
+    noop_ix.data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args),
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[noop_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_stake_authority = get_account_data_deserialized::<StakePoolDepositStakeAuthority>(
+        &mut ctx.banks_client,
+        &deposit_stake_authority_pubkey,
+    )
+    .await;
+    assert_eq!(deposit_stake_authority, queued_deposit_stake_authority);
+}
+
+#[tokio::test]
+async fn test_fail_keeper_tip_above_max() {
+    let (mut ctx, _stake_pool_accounts, authority, _deposit_stake_authority_pubkey, ix) =
+        setup_with_ix().await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let max_keeper_tip_lamports =
+        StakePoolDepositStakeAuthority::max_keeper_tip(rent.minimum_balance(DepositReceipt::LEN));
+
+    let args = UpdateStakePoolDepositStakeAuthorityV2Args {
+        fee_wallet: None,
+        initial_fee_bps: None,
+        cool_down_seconds: None,
+        fee_curve: None,
+        effective_at: None,
+        max_deposit_lst_per_receipt: None,
+        max_total_outstanding_lst: None,
+        min_deposit_lst: None,
+        keeper_tip_lamports: Some(max_keeper_tip_lamports + 1),
+        require_owner_signature: None,
+        receipt_transfer_policy: None,
+    };
+    let mut keeper_tip_ix = ix;
+    keeper_tip_ix.data = borsh::to_vec(
+        &StakeDepositInterceptorInstruction::UpdateStakePoolDepositStakeAuthorityV2(args),
+    )
+    .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[keeper_tip_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &authority],
+        ctx.last_blockhash,
+    );
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::InvalidKeeperTip as u32),
+    )
+    .await;
+}
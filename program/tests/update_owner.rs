@@ -1,14 +1,13 @@
 mod helpers;
 
-use std::mem;
-
 use helpers::{
     airdrop_lamports, assert_transaction_err, clone_account_to_new_address, create_stake_account,
     create_stake_deposit_authority, create_token_account, create_validator_and_add_to_pool,
-    delegate_stake_account, get_account_data_deserialized,
-    program_test_context_with_stake_pool_state, stake_pool_update_all,
+    delegate_stake_account, get_account_data_deserialized, process_transaction_events,
+    program_test_context_with_stake_pool_state, set_clock_time, stake_pool_update_all,
     update_stake_deposit_authority, StakePoolAccounts, ValidatorStakeAccount,
 };
+use jito_bytemuck::AccountDeserialize;
 use solana_program_test::ProgramTestContext;
 use solana_sdk::{
     account::AccountSharedData,
@@ -23,8 +22,9 @@ use solana_sdk::{
 };
 use stake_deposit_interceptor::{
     error::StakeDepositInterceptorError,
+    events::StakeDepositInterceptorEvent,
     instruction::{derive_stake_deposit_receipt, derive_stake_pool_deposit_stake_authority},
-    state::{DepositReceipt, StakePoolDepositStakeAuthority},
+    state::{DepositReceipt, ReceiptTransferPolicy, StakePoolDepositStakeAuthority},
 };
 
 async fn setup() -> (
@@ -193,7 +193,7 @@ async fn success() {
         stake_pool_accounts,
         _stake_pool,
         _validator_stake_accounts,
-        _deposit_stake_authority,
+        deposit_stake_authority,
         depositor,
         _depositor_stake_account,
         base,
@@ -206,6 +206,12 @@ async fn success() {
         &base.pubkey(),
     );
 
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &deposit_stake_authority.base,
+    );
+
     let new_owner = Pubkey::new_unique();
 
     // Update owner of DepositReceipt
@@ -214,6 +220,7 @@ async fn success() {
         &deposit_receipt_pda,
         &depositor.pubkey(),
         &new_owner,
+        &deposit_stake_authority_pubkey,
     );
 
     let tx = Transaction::new_signed_with_payer(
@@ -239,7 +246,7 @@ async fn setup_with_ix() -> (ProgramTestContext, Keypair, Pubkey, Instruction) {
         stake_pool_accounts,
         _stake_pool,
         _validator_stake_accounts,
-        _deposit_stake_authority,
+        deposit_stake_authority,
         depositor,
         _depositor_stake_account,
         base,
@@ -252,6 +259,12 @@ async fn setup_with_ix() -> (ProgramTestContext, Keypair, Pubkey, Instruction) {
         &base.pubkey(),
     );
 
+    let (deposit_stake_authority_pubkey, _bump) = derive_stake_pool_deposit_stake_authority(
+        &stake_deposit_interceptor::id(),
+        &deposit_stake_authority.stake_pool,
+        &deposit_stake_authority.base,
+    );
+
     let new_owner = Pubkey::new_unique();
 
     // Update owner of DepositReceipt
@@ -260,6 +273,7 @@ async fn setup_with_ix() -> (ProgramTestContext, Keypair, Pubkey, Instruction) {
         &deposit_receipt_pda,
         &depositor.pubkey(),
         &new_owner,
+        &deposit_stake_authority_pubkey,
     );
     (ctx, depositor, deposit_receipt_pda, ix)
 }
@@ -294,7 +308,7 @@ async fn test_fail_invalid_deposit_receipt_owner() {
         .await
         .unwrap()
         .unwrap();
-    const ACCOUNT_SIZE: usize = 8 + mem::size_of::<DepositReceipt>();
+    const ACCOUNT_SIZE: usize = DepositReceipt::LEN;
     let mut bad_account =
         AccountSharedData::new(original.lamports, ACCOUNT_SIZE, &Pubkey::new_unique());
     bad_account.set_data_from_slice(&original.data);
@@ -351,3 +365,166 @@ async fn test_fail_invalid_owner() {
     )
     .await;
 }
+
+/// Overwrite the StakePoolDepositStakeAuthority's ReceiptTransferPolicy.
+async fn set_receipt_transfer_policy(
+    ctx: &mut ProgramTestContext,
+    deposit_stake_authority_pubkey: &Pubkey,
+    receipt_transfer_policy: ReceiptTransferPolicy,
+) {
+    let mut account = ctx
+        .banks_client
+        .get_account(*deposit_stake_authority_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let deposit_stake_authority =
+        StakePoolDepositStakeAuthority::try_from_slice_unchecked_mut(&mut account.data).unwrap();
+    deposit_stake_authority.receipt_transfer_policy = receipt_transfer_policy as u8;
+    ctx.set_account(
+        deposit_stake_authority_pubkey,
+        &AccountSharedData::from(account),
+    );
+}
+
+#[tokio::test]
+async fn test_fail_transfer_forbidden_during_cool_down() {
+    let (mut ctx, depositor, _deposit_receipt_pda, ix) = setup_with_ix().await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &ix.accounts[3].pubkey,
+        ReceiptTransferPolicy::ForbidDuringCoolDown,
+    )
+    .await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::ReceiptTransferForbidden as u32),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_success_transfer_forbidden_during_cool_down_after_cool_down() {
+    let (mut ctx, depositor, deposit_receipt_pda, ix) = setup_with_ix().await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &ix.accounts[3].pubkey,
+        ReceiptTransferPolicy::ForbidDuringCoolDown,
+    )
+    .await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let cool_down_end_time =
+        u64::from(deposit_receipt.deposit_time) + u64::from(deposit_receipt.cool_down_seconds);
+    set_clock_time(&mut ctx, cool_down_end_time as i64).await;
+    let new_owner = ix.accounts[2].pubkey;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(deposit_receipt.owner, new_owner);
+}
+
+#[tokio::test]
+async fn test_success_transfer_resets_deposit_time() {
+    let (mut ctx, depositor, deposit_receipt_pda, ix) = setup_with_ix().await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &ix.accounts[3].pubkey,
+        ReceiptTransferPolicy::ResetDepositTime,
+    )
+    .await;
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    let transfer_time = u64::from(deposit_receipt.deposit_time) + 100;
+    set_clock_time(&mut ctx, transfer_time as i64).await;
+
+    let new_owner = ix.accounts[2].pubkey;
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+    let events = process_transaction_events(&mut ctx, tx).await;
+    assert_eq!(
+        events,
+        vec![
+            StakeDepositInterceptorEvent::ResetDepositTime {
+                deposit_receipt: deposit_receipt_pda,
+                deposit_time: transfer_time,
+            },
+            StakeDepositInterceptorEvent::ChangeDepositReceiptOwner {
+                deposit_receipt: deposit_receipt_pda,
+                previous_owner: depositor.pubkey(),
+                owner: new_owner,
+            },
+        ]
+    );
+
+    // The cool down restarts, so the new owner owes the full fee again
+    let deposit_receipt = get_account_data_deserialized::<DepositReceipt>(
+        &mut ctx.banks_client,
+        &deposit_receipt_pda,
+    )
+    .await;
+    assert_eq!(u64::from(deposit_receipt.deposit_time), transfer_time);
+}
+
+#[tokio::test]
+async fn test_fail_tokenize_when_transfers_restricted() {
+    let (mut ctx, depositor, deposit_receipt_pda, ix) = setup_with_ix().await;
+    set_receipt_transfer_policy(
+        &mut ctx,
+        &ix.accounts[3].pubkey,
+        ReceiptTransferPolicy::ResetDepositTime,
+    )
+    .await;
+
+    // The receipt token could be transferred without resetting `deposit_time`
+    let tokenize_ix =
+        stake_deposit_interceptor::instruction::create_tokenize_deposit_receipt_instruction(
+            &stake_deposit_interceptor::id(),
+            &deposit_receipt_pda,
+            &depositor.pubkey(),
+            &ix.accounts[3].pubkey,
+        );
+    let tx = Transaction::new_signed_with_payer(
+        &[tokenize_ix],
+        Some(&depositor.pubkey()),
+        &[&depositor],
+        ctx.last_blockhash,
+    );
+
+    assert_transaction_err(
+        &mut ctx,
+        tx,
+        InstructionError::Custom(StakeDepositInterceptorError::ReceiptTransferForbidden as u32),
+    )
+    .await;
+}